@@ -0,0 +1,450 @@
+//! Comparing a numeric outcome across the levels of a grouping: one-way ANOVA
+//! with Tukey's HSD and Kruskal-Wallis with Dunn's test.
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::correction::Correction;
+use crate::correlation::{ranks, stars};
+use crate::distributions::{ChiSquared, Continuous, FisherF, Normal, StudentizedRange};
+use crate::ols::CONFIDENCE_LEVEL;
+
+/// The observed outcome values of one group.
+pub type Sample = (String, Vec<f64>);
+
+#[derive(Debug, Clone)]
+pub struct GroupDescription {
+    pub name: String,
+    pub n: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub mean_rank: f64,
+}
+
+/// Welch's heteroscedasticity-robust one-way ANOVA.
+#[derive(Debug, Clone, Copy)]
+pub struct WelchAnova {
+    pub f_statistic: f64,
+    pub df1: f64,
+    pub df2: f64,
+    pub p_value: f64,
+}
+
+/// One Tukey-Kramer comparison of two group means.
+#[derive(Debug, Clone)]
+pub struct TukeyComparison {
+    pub first: String,
+    pub second: String,
+    /// Mean of `second` minus mean of `first`.
+    pub difference: f64,
+    pub q_statistic: f64,
+    /// Family-wise adjusted by the studentized range distribution.
+    pub p_value: f64,
+    pub ci: (f64, f64),
+}
+
+/// Classical one-way ANOVA assuming equal group variances.
+#[derive(Debug, Clone)]
+pub struct OneWayAnova {
+    pub ss_between: f64,
+    pub ss_within: f64,
+    pub df_between: f64,
+    pub df_within: f64,
+    pub f_statistic: f64,
+    pub p_value: f64,
+    /// SS between over SS total.
+    pub eta_squared: f64,
+    pub welch: Option<WelchAnova>,
+    pub tukey: Vec<TukeyComparison>,
+}
+
+/// One Dunn comparison of two groups' mean ranks.
+#[derive(Debug, Clone)]
+pub struct DunnComparison {
+    pub first: String,
+    pub second: String,
+    /// Mean rank of `second` minus mean rank of `first`.
+    pub rank_difference: f64,
+    pub z: f64,
+    pub p_value: f64,
+    pub adjusted_p: f64,
+}
+
+/// Kruskal-Wallis rank test, corrected for ties.
+#[derive(Debug, Clone)]
+pub struct KruskalWallis {
+    pub h_statistic: f64,
+    pub df: f64,
+    pub p_value: f64,
+    /// H / (n - 1), the rank analogue of eta².
+    pub epsilon_squared: f64,
+    pub correction: Correction,
+    pub dunn: Vec<DunnComparison>,
+}
+
+/// Parametric and rank-based comparison of one outcome across groups.
+#[derive(Debug, Clone)]
+pub struct GroupComparison {
+    pub outcome: String,
+    pub grouping: String,
+    pub n: usize,
+    pub groups: Vec<GroupDescription>,
+    pub anova: OneWayAnova,
+    pub kruskal_wallis: KruskalWallis,
+}
+
+impl GroupComparison {
+    /// Compares `samples` (one per group), ignoring groups with no values.
+    /// Dunn's p-values are adjusted with `correction`.
+    pub fn compute(
+        outcome: &str,
+        grouping: &str,
+        samples: &[Sample],
+        correction: Correction,
+    ) -> Result<GroupComparison, Box<dyn Error>> {
+        let samples: Vec<&Sample> = samples.iter().filter(|(_, values)| !values.is_empty()).collect();
+        let k = samples.len();
+        let n: usize = samples.iter().map(|(_, values)| values.len()).sum();
+        if k < 2 {
+            return Err(format!("{} needs at least two non-empty groups of {}", outcome, grouping).into());
+        }
+        if n <= k {
+            return Err("Need more observations than groups".into());
+        }
+
+        let pooled: Vec<f64> = samples.iter().flat_map(|(_, values)| values.iter().copied()).collect();
+        let pooled_ranks = ranks(&pooled);
+        let mut offset = 0;
+        let groups: Vec<GroupDescription> = samples
+            .iter()
+            .map(|(name, values)| {
+                let size = values.len();
+                let mean = values.iter().sum::<f64>() / size as f64;
+                let ss = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>();
+                let mean_rank = pooled_ranks[offset..offset + size].iter().sum::<f64>() / size as f64;
+                offset += size;
+                GroupDescription {
+                    name: name.clone(),
+                    n: size,
+                    mean,
+                    std_dev: if size > 1 { (ss / (size - 1) as f64).sqrt() } else { 0.0 },
+                    mean_rank,
+                }
+            })
+            .collect();
+
+        let anova = one_way_anova(&groups, &pooled)?;
+        let kruskal_wallis = kruskal_wallis(&groups, &pooled, correction)?;
+        Ok(GroupComparison {
+            outcome: outcome.to_string(),
+            grouping: grouping.to_string(),
+            n,
+            groups,
+            anova,
+            kruskal_wallis,
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "{} by {} ({} observations)", self.outcome, self.grouping, self.n).unwrap();
+        let name_width = self.groups.iter().map(|g| g.name.len()).max().unwrap_or(0).max(5);
+        writeln!(
+            out,
+            "  {:<name_width$} {:>6} {:>12} {:>12} {:>10}",
+            "Group", "n", "Mean", "Std. dev.", "Mean rank"
+        )
+        .unwrap();
+        for g in &self.groups {
+            writeln!(
+                out,
+                "  {:<name_width$} {:>6} {:>12.4} {:>12.4} {:>10.2}",
+                g.name, g.n, g.mean, g.std_dev, g.mean_rank
+            )
+            .unwrap();
+        }
+
+        let a = &self.anova;
+        writeln!(
+            out,
+            "One-way ANOVA: F = {:.4} on {} and {} DF, p = {:.4}, eta-squared = {:.4}",
+            a.f_statistic, a.df_between, a.df_within, a.p_value, a.eta_squared
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  Sum of squares: between {:.4}, within {:.4}",
+            a.ss_between, a.ss_within
+        )
+        .unwrap();
+        match &a.welch {
+            Some(w) => writeln!(
+                out,
+                "Welch ANOVA: F = {:.4} on {} and {:.2} DF, p = {:.4}",
+                w.f_statistic, w.df1, w.df2, w.p_value
+            )
+            .unwrap(),
+            None => writeln!(out, "Welch ANOVA: not available (a group has fewer than two values or no spread)").unwrap(),
+        }
+        let pair_width = a
+            .tukey
+            .iter()
+            .map(|c| c.first.len() + c.second.len() + 3)
+            .max()
+            .unwrap_or(0);
+        writeln!(out, "Tukey HSD ({:.0}% family-wise intervals):", CONFIDENCE_LEVEL * 100.0).unwrap();
+        for c in &a.tukey {
+            writeln!(
+                out,
+                "  {:<pair_width$} diff = {:>12.4}, q = {:>7.3}, p = {:.4}{}, CI [{:.4}, {:.4}]",
+                format!("{} - {}", c.second, c.first),
+                c.difference,
+                c.q_statistic,
+                c.p_value,
+                stars(c.p_value),
+                c.ci.0,
+                c.ci.1
+            )
+            .unwrap();
+        }
+
+        let kw = &self.kruskal_wallis;
+        writeln!(
+            out,
+            "Kruskal-Wallis: H = {:.4} on {} DF, p = {:.4}, epsilon-squared = {:.4}",
+            kw.h_statistic, kw.df, kw.p_value, kw.epsilon_squared
+        )
+        .unwrap();
+        writeln!(out, "Dunn's test ({} adjusted):", kw.correction).unwrap();
+        for c in &kw.dunn {
+            writeln!(
+                out,
+                "  {:<pair_width$} rank diff = {:>9.2}, z = {:>7.3}, p = {:.4}, adjusted p = {:.4}{}",
+                format!("{} - {}", c.second, c.first),
+                c.rank_difference,
+                c.z,
+                c.p_value,
+                c.adjusted_p,
+                stars(c.adjusted_p)
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+fn pairs(k: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..k).flat_map(move |i| (i + 1..k).map(move |j| (i, j)))
+}
+
+fn one_way_anova(groups: &[GroupDescription], pooled: &[f64]) -> Result<OneWayAnova, Box<dyn Error>> {
+    let n = pooled.len() as f64;
+    let k = groups.len() as f64;
+    let grand_mean = pooled.iter().sum::<f64>() / n;
+    let ss_total: f64 = pooled.iter().map(|v| (v - grand_mean).powi(2)).sum();
+    let ss_between: f64 = groups.iter().map(|g| g.n as f64 * (g.mean - grand_mean).powi(2)).sum();
+    let ss_within = groups
+        .iter()
+        .map(|g| (g.n as f64 - 1.0) * g.std_dev.powi(2))
+        .sum::<f64>();
+    if ss_within <= 0.0 {
+        return Err("Outcome does not vary within groups".into());
+    }
+    let (df_between, df_within) = (k - 1.0, n - k);
+    let ms_within = ss_within / df_within;
+    let f_statistic = (ss_between / df_between) / ms_within;
+
+    // Tukey-Kramer: the studentized range with standard error from the pooled variance
+    let range = StudentizedRange::new(k, df_within);
+    let critical = range.quantile(CONFIDENCE_LEVEL);
+    let tukey = pairs(groups.len())
+        .map(|(i, j)| {
+            let (a, b) = (&groups[i], &groups[j]);
+            let difference = b.mean - a.mean;
+            let std_error = (ms_within / 2.0 * (1.0 / a.n as f64 + 1.0 / b.n as f64)).sqrt();
+            let q_statistic = difference.abs() / std_error;
+            TukeyComparison {
+                first: a.name.clone(),
+                second: b.name.clone(),
+                difference,
+                q_statistic,
+                p_value: range.sf(q_statistic).clamp(0.0, 1.0),
+                ci: (difference - critical * std_error, difference + critical * std_error),
+            }
+        })
+        .collect();
+
+    Ok(OneWayAnova {
+        ss_between,
+        ss_within,
+        df_between,
+        df_within,
+        f_statistic,
+        p_value: FisherF::new(df_between, df_within).sf(f_statistic),
+        eta_squared: ss_between / ss_total,
+        welch: welch_anova(groups),
+        tukey,
+    })
+}
+
+/// Welch (1951); needs every group to have at least two distinct values.
+fn welch_anova(groups: &[GroupDescription]) -> Option<WelchAnova> {
+    if groups.iter().any(|g| g.n < 2 || g.std_dev == 0.0) {
+        return None;
+    }
+    let k = groups.len() as f64;
+    let weights: Vec<f64> = groups.iter().map(|g| g.n as f64 / g.std_dev.powi(2)).collect();
+    let total_weight: f64 = weights.iter().sum();
+    let weighted_mean = groups.iter().zip(&weights).map(|(g, w)| w * g.mean).sum::<f64>() / total_weight;
+    let between = groups
+        .iter()
+        .zip(&weights)
+        .map(|(g, w)| w * (g.mean - weighted_mean).powi(2))
+        .sum::<f64>()
+        / (k - 1.0);
+    let lambda = groups
+        .iter()
+        .zip(&weights)
+        .map(|(g, w)| (1.0 - w / total_weight).powi(2) / (g.n as f64 - 1.0))
+        .sum::<f64>();
+    let f_statistic = between / (1.0 + 2.0 * (k - 2.0) / (k * k - 1.0) * lambda);
+    let df2 = (k * k - 1.0) / (3.0 * lambda);
+    Some(WelchAnova {
+        f_statistic,
+        df1: k - 1.0,
+        df2,
+        p_value: FisherF::new(k - 1.0, df2).sf(f_statistic),
+    })
+}
+
+fn kruskal_wallis(
+    groups: &[GroupDescription],
+    pooled: &[f64],
+    correction: Correction,
+) -> Result<KruskalWallis, Box<dyn Error>> {
+    let n = pooled.len() as f64;
+    let mut sorted = pooled.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mut tie_sum = 0.0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let t = sorted[i..].iter().take_while(|&&v| v == sorted[i]).count();
+        tie_sum += (t * t * t - t) as f64;
+        i += t;
+    }
+    let tie_factor = 1.0 - tie_sum / (n * n * n - n);
+    if tie_factor <= 0.0 {
+        return Err("Every observation is tied".into());
+    }
+
+    let h_uncorrected = 12.0 / (n * (n + 1.0)) * groups.iter().map(|g| g.n as f64 * g.mean_rank.powi(2)).sum::<f64>()
+        - 3.0 * (n + 1.0);
+    let h_statistic = h_uncorrected / tie_factor;
+    let df = groups.len() as f64 - 1.0;
+
+    // Dunn (1964) with the tie-adjusted rank variance
+    let rank_variance = n * (n + 1.0) / 12.0 - tie_sum / (12.0 * (n - 1.0));
+    let mut dunn: Vec<DunnComparison> = pairs(groups.len())
+        .map(|(i, j)| {
+            let (a, b) = (&groups[i], &groups[j]);
+            let rank_difference = b.mean_rank - a.mean_rank;
+            let z = rank_difference / (rank_variance * (1.0 / a.n as f64 + 1.0 / b.n as f64)).sqrt();
+            DunnComparison {
+                first: a.name.clone(),
+                second: b.name.clone(),
+                rank_difference,
+                z,
+                p_value: Normal.two_sided_p(z),
+                adjusted_p: f64::NAN,
+            }
+        })
+        .collect();
+    let raw: Vec<f64> = dunn.iter().map(|c| c.p_value).collect();
+    for (comparison, adjusted) in dunn.iter_mut().zip(correction.adjust(&raw)) {
+        comparison.adjusted_p = adjusted;
+    }
+
+    Ok(KruskalWallis {
+        h_statistic,
+        df,
+        p_value: ChiSquared::new(df).sf(h_statistic),
+        epsilon_squared: h_statistic / (n - 1.0),
+        correction,
+        dunn,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    /// R's `PlantGrowth` data set.
+    fn plant_growth() -> Vec<Sample> {
+        vec![
+            ("ctrl".to_string(), vec![4.17, 5.58, 5.18, 6.11, 4.50, 4.61, 5.17, 4.53, 5.33, 5.14]),
+            ("trt1".to_string(), vec![4.81, 4.17, 4.41, 3.59, 5.87, 3.83, 6.03, 4.89, 4.32, 4.69]),
+            ("trt2".to_string(), vec![6.31, 5.12, 5.54, 5.50, 5.37, 5.29, 4.92, 6.15, 5.80, 5.26]),
+        ]
+    }
+
+    fn comparison() -> GroupComparison {
+        GroupComparison::compute("weight", "group", &plant_growth(), Correction::Holm).unwrap()
+    }
+
+    #[test]
+    fn anova_and_welch_match_r() {
+        let anova = comparison().anova;
+        // summary(aov(weight ~ group, PlantGrowth))
+        close(anova.ss_between, 3.76634, 1e-5);
+        close(anova.ss_within, 10.49209, 1e-5);
+        close(anova.f_statistic, 4.846088, 1e-6);
+        close(anova.p_value, 0.01590996, 1e-7);
+        // oneway.test(weight ~ group, PlantGrowth)
+        let welch = anova.welch.unwrap();
+        close(welch.f_statistic, 5.181, 1e-3);
+        close(welch.df2, 17.128, 1e-3);
+        close(welch.p_value, 0.01739, 1e-5);
+    }
+
+    #[test]
+    fn tukey_kramer_matches_r() {
+        // TukeyHSD(aov(weight ~ group, PlantGrowth))
+        let expected = [
+            (-0.371, -1.0622161, 0.3202161, 0.3908711),
+            (0.494, -0.1972161, 1.1852161, 0.1979960),
+            (0.865, 0.1737839, 1.5562161, 0.0120064),
+        ];
+        let tukey = comparison().anova.tukey;
+        assert_eq!(tukey.len(), 3);
+        for (c, (difference, lower, upper, p)) in tukey.iter().zip(expected) {
+            close(c.difference, difference, 1e-9);
+            close(c.ci.0, lower, 1e-5);
+            close(c.ci.1, upper, 1e-5);
+            close(c.p_value, p, 1e-5);
+        }
+        assert_eq!((tukey[2].first.as_str(), tukey[2].second.as_str()), ("trt1", "trt2"));
+    }
+
+    #[test]
+    fn kruskal_wallis_and_dunn_match_r() {
+        // kruskal.test(weight ~ group, PlantGrowth) and FSA::dunnTest(..., method = "holm")
+        let kw = comparison().kruskal_wallis;
+        close(kw.h_statistic, 7.988229, 1e-6);
+        close(kw.p_value, 0.01842, 1e-5);
+        let expected = [(-1.117725, 0.2636843, 0.2636843), (1.689290, 0.0911639, 0.1823279), (2.807015, 0.0050003, 0.0150009)];
+        for (c, (z, p, adjusted)) in kw.dunn.iter().zip(expected) {
+            close(c.z, z, 1e-6);
+            close(c.p_value, p, 1e-7);
+            close(c.adjusted_p, adjusted, 1e-7);
+        }
+    }
+
+    #[test]
+    fn needs_two_groups_with_spread() {
+        let one = vec![("a".to_string(), vec![1.0, 2.0]), ("b".to_string(), vec![])];
+        assert!(GroupComparison::compute("y", "g", &one, Correction::Holm).is_err());
+        let flat = vec![("a".to_string(), vec![1.0, 1.0]), ("b".to_string(), vec![2.0, 2.0])];
+        assert!(GroupComparison::compute("y", "g", &flat, Correction::Holm).is_err());
+    }
+}
@@ -0,0 +1,429 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, Write};
+
+use crate::correction::Correction;
+use crate::correlation::CorrelationMethod;
+use crate::crossval::DEFAULT_FOLDS;
+use crate::estimator::Learner;
+use crate::features::Scaling;
+use crate::groupby::GroupStat;
+use crate::individual::{Category, Field};
+use crate::outliers::{OutlierMethod, Treatment};
+use crate::rng::DEFAULT_SEED;
+use crate::robust::RegressionMethod;
+use crate::summary::DEFAULT_PERCENTILES;
+
+pub const HELP: &str = "\
+Career dataset analysis
+
+Usage: finalproject [COMMAND] [OPTIONS]
+
+Commands:
+  report      Run every analysis on the dataset (default when no command is given)
+  load        Load the dataset and print the load report
+  describe    Descriptive statistics for the selected columns
+  correlate   Correlation matrix of the selected columns
+  regress     Fit a multiple regression given by --formula
+  logistic    Fit a logistic regression of a boolean field given by --formula
+  cv          Cross-validate the --model given by --formula and --categories, reporting per-fold and
+              aggregate metrics and the fit on every complete case
+  groupby     Per-group statistics of the selected columns, grouped by --by
+  test        Two-sample tests of the selected columns between two groups of --by, a paired
+              t-test of --paired, or chi-square and Fisher tests of --by against --against
+  compare     ANOVA, Welch, Kruskal-Wallis and post-hoc tests of the selected columns across --by
+  export      Write the loaded individuals as CSV
+  outliers    Individuals flagged by the --outliers rules on the selected columns, with reasons
+  dist        CDF, survival function and quantiles of --dist, e.g. dist --dist t --params 10 --x 2.23
+  help        Print this help
+
+Options:
+  -i, --input <PATH>        Dataset to load [default: career_dataset.csv]
+      --schema <PATH>       Schema file [default: career_schema.toml if present, else built-in]
+      --alias <NAME=HEADER> Accept HEADER as a spelling of schema column NAME (repeatable)
+      --rejects <PATH>      Write rows dropped while loading to PATH
+  -c, --columns <LIST>      Comma-separated fields, e.g. age,salary [default: all]
+  -m, --method <METHOD>     pearson | spearman | kendall [default: pearson]
+      --correction <NAME>   none | bonferroni | holm | bh, for matrices and Dunn's test [default: holm]
+  -f, --formula <FORMULA>   e.g. \"salary ~ age + years_of_experience^2 + age:job_satisfaction\"
+                            [default: salary ~ every loaded column with role \"feature\"; for
+                            logistic, likely_to_change_occupation ~ the same; \"target\" columns are
+                            never predictors]
+  -p, --percentiles <LIST>  Extra percentiles for describe, e.g. 1,99 [default: 5,10,90,95]
+      --bootstrap <N>       Add bootstrap percentile and BCa intervals from N resamples (correlate, regress)
+      --diagnostics         Add residual, leverage and influence diagnostics with assumption tests (regress);
+                            with --format csv, write one row per observation instead
+      --robust <LIST>       Compare least squares with theil-sen, lad, huber, ransac or all
+                            (regress, formula with one term)
+      --evaluate            Add ROC/AUC, precision-recall, log loss, Brier score, calibration and optimal
+                            thresholds of the fitted probabilities (logistic); with --format csv, write
+                            the ROC and precision-recall curve instead
+      --l2 <LAMBDA>         L2 penalty on the logistic coefficients other than the intercept [default: 0]
+      --model <NAME>        Model to cross-validate: ols, theil-sen, lad, huber, ransac or logistic
+                            [default: ols]
+      --folds <K>           Cross-validation folds [default: 10]
+      --stratify            Keep each response value's share equal across folds (cv, categorical response)
+      --repeats <N>         Repeat cross-validation on N reshuffles of the folds [default: 1]
+      --loo                 Leave-one-out cross-validation instead of k folds
+      --categories <LIST>   Categorical features to add (cv): field_of_study, current_occupation, gender,
+                            encoded as the schema says, else one-hot
+      --scale <NAME>        Feature scaling learned on each training fold (cv): none, standardize or minmax
+                            [default: none]
+      --outliers <LIST>     Outlier rules: z, mad, iqr, mahalanobis or all [default: all]
+      --treat <ACTION>      exclude | winsorize the --outliers flags before correlations (report, correlate);
+                            winsorizing clamps to the univariate limits
+      --permutations <N>    Add permutation p-values from up to N shuffles, exact when the data allow
+                            (correlate, compare)
+      --seed <N>            Random seed for resampling and shuffling [default: 42]
+      --by <COLUMN>         Schema column to group by (groupby, compare)
+      --groups <A,B>        The two groups of --by to test [default: the only two]
+      --against <COLUMN>    Second categorical column for a contingency table test
+      --paired <F1,F2>      Two fields measured on the same individuals
+      --stat <LIST>         Per-group statistics: n, mean, median, sd, min, max [default: mean]
+      --pivot <TABLE>       groupby table: stats | correlations | regression
+                            [default: every table for text, stats for csv]
+      --format <FORMAT>     text | csv [default: text]
+  -o, --output <PATH>       Write results to PATH instead of stdout
+      --dist <NAME>         normal | t | f | chisq | beta | tukey (dist)
+      --params <LIST>       Distribution parameters: df for t and chisq, df1,df2 for f, a,b for beta,
+                            groups,df for tukey
+      --x <LIST>            Points at which to evaluate the CDF and survival function (dist)
+      --p <LIST>            Probabilities at which to evaluate the quantile function (dist)
+  -h, --help                Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Report,
+    Load,
+    Describe,
+    Correlate,
+    Regress,
+    Logistic,
+    CrossValidate,
+    GroupBy,
+    Test,
+    Compare,
+    Export,
+    Outliers,
+    Dist,
+    Help,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Csv,
+}
+
+/// Which pivot table `groupby` produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pivot {
+    Stats,
+    Correlations,
+    Regression,
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub command: Command,
+    pub input: String,
+    pub schema: Option<String>,
+    /// (schema column, extra header spelling)
+    pub aliases: Vec<(String, String)>,
+    pub rejects: Option<String>,
+    pub columns: Vec<Field>,
+    pub method: CorrelationMethod,
+    pub correction: Correction,
+    pub formula: Option<String>,
+    pub percentiles: Vec<f64>,
+    pub bootstrap: Option<usize>,
+    pub diagnostics: bool,
+    pub robust: Vec<RegressionMethod>,
+    pub penalty: f64,
+    pub evaluate: bool,
+    pub model: Learner,
+    pub folds: usize,
+    pub stratify: bool,
+    pub repeats: usize,
+    pub leave_one_out: bool,
+    pub categories: Vec<Category>,
+    pub scaling: Scaling,
+    pub outlier_methods: Vec<OutlierMethod>,
+    pub treatment: Option<Treatment>,
+    pub permutations: Option<usize>,
+    pub seed: u64,
+    pub by: Option<String>,
+    pub groups: Option<(String, String)>,
+    pub against: Option<String>,
+    pub paired: Option<(Field, Field)>,
+    pub stats: Vec<GroupStat>,
+    pub pivot: Option<Pivot>,
+    pub distribution: Option<String>,
+    pub params: Vec<f64>,
+    pub points: Vec<f64>,
+    pub probabilities: Vec<f64>,
+    pub format: OutputFormat,
+    pub output: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            command: Command::Report,
+            input: "career_dataset.csv".to_string(),
+            schema: None,
+            aliases: Vec::new(),
+            rejects: None,
+            columns: Field::ALL.to_vec(),
+            method: CorrelationMethod::Pearson,
+            correction: Correction::Holm,
+            formula: None,
+            percentiles: DEFAULT_PERCENTILES.to_vec(),
+            bootstrap: None,
+            diagnostics: false,
+            robust: Vec::new(),
+            penalty: 0.0,
+            evaluate: false,
+            model: Learner::Linear(RegressionMethod::Ols),
+            folds: DEFAULT_FOLDS,
+            stratify: false,
+            repeats: 1,
+            leave_one_out: false,
+            categories: Vec::new(),
+            scaling: Scaling::None,
+            outlier_methods: OutlierMethod::ALL.to_vec(),
+            treatment: None,
+            permutations: None,
+            seed: DEFAULT_SEED,
+            by: None,
+            groups: None,
+            against: None,
+            paired: None,
+            stats: vec![GroupStat::Mean],
+            pivot: None,
+            distribution: None,
+            params: Vec::new(),
+            points: Vec::new(),
+            probabilities: Vec::new(),
+            format: OutputFormat::Text,
+            output: None,
+        }
+    }
+}
+
+fn parse_numbers(list: &str) -> Result<Vec<f64>, Box<dyn Error>> {
+    list.split(',')
+        .map(|v| v.trim().parse::<f64>().map_err(|_| format!("'{}' is not a number", v.trim()).into()))
+        .collect()
+}
+
+impl Options {
+    /// Parses the arguments after the program name.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, Box<dyn Error>> {
+        let mut options = Options::default();
+        let mut args = args.into_iter().peekable();
+
+        if let Some(first) = args.peek() {
+            if !first.starts_with('-') {
+                options.command = match first.as_str() {
+                    "report" => Command::Report,
+                    "load" => Command::Load,
+                    "describe" => Command::Describe,
+                    "correlate" => Command::Correlate,
+                    "regress" => Command::Regress,
+                    "logistic" => Command::Logistic,
+                    "cv" => Command::CrossValidate,
+                    "groupby" => Command::GroupBy,
+                    "test" => Command::Test,
+                    "compare" => Command::Compare,
+                    "export" => Command::Export,
+                    "outliers" => Command::Outliers,
+                    "dist" => Command::Dist,
+                    "help" => Command::Help,
+                    other => return Err(format!("unknown command '{}' (see --help)", other).into()),
+                };
+                args.next();
+            }
+        }
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            if flag == "-h" || flag == "--help" {
+                options.command = Command::Help;
+                continue;
+            }
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{} needs a value", flag))
+            };
+            match flag.as_str() {
+                "-i" | "--input" => options.input = value()?,
+                "--schema" => options.schema = Some(value()?),
+                "--alias" => {
+                    let pair = value()?;
+                    let (name, header) = pair
+                        .split_once('=')
+                        .ok_or_else(|| format!("--alias expects NAME=HEADER, got '{}'", pair))?;
+                    options.aliases.push((name.trim().to_string(), header.trim().to_string()));
+                }
+                "--rejects" => options.rejects = Some(value()?),
+                "-c" | "--columns" => {
+                    options.columns = value()?
+                        .split(',')
+                        .map(|name| Field::parse(name.trim()))
+                        .collect::<Result<_, _>>()?;
+                }
+                "-m" | "--method" => options.method = CorrelationMethod::parse(&value()?)?,
+                "--correction" => options.correction = Correction::parse(&value()?)?,
+                "-f" | "--formula" => options.formula = Some(value()?),
+                "-p" | "--percentiles" => {
+                    options.percentiles = value()?
+                        .split(',')
+                        .map(|p| match p.trim().parse::<f64>() {
+                            Ok(p) if (0.0..=100.0).contains(&p) => Ok(p),
+                            _ => Err(format!("invalid percentile '{}' (expected 0 to 100)", p.trim())),
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "--bootstrap" => {
+                    let text = value()?;
+                    match text.parse::<usize>() {
+                        Ok(n) if n >= 2 => options.bootstrap = Some(n),
+                        _ => return Err(format!("--bootstrap expects a resample count of at least 2, got '{}'", text).into()),
+                    }
+                }
+                "--diagnostics" => options.diagnostics = true,
+                "--robust" => {
+                    let list = value()?;
+                    options.robust = if list.eq_ignore_ascii_case("all") {
+                        RegressionMethod::ALL.to_vec()
+                    } else {
+                        list.split(',').map(|m| RegressionMethod::parse(m.trim())).collect::<Result<_, _>>()?
+                    };
+                }
+                "--evaluate" => options.evaluate = true,
+                "--l2" => {
+                    let text = value()?;
+                    match text.parse::<f64>() {
+                        Ok(l) if l >= 0.0 && l.is_finite() => options.penalty = l,
+                        _ => return Err(format!("--l2 expects a non-negative penalty, got '{}'", text).into()),
+                    }
+                }
+                "--model" => options.model = Learner::parse(&value()?)?,
+                "--folds" => {
+                    let text = value()?;
+                    match text.parse::<usize>() {
+                        Ok(k) if k >= 2 => options.folds = k,
+                        _ => return Err(format!("--folds expects at least 2, got '{}'", text).into()),
+                    }
+                }
+                "--stratify" => options.stratify = true,
+                "--repeats" => {
+                    let text = value()?;
+                    match text.parse::<usize>() {
+                        Ok(n) if n >= 1 => options.repeats = n,
+                        _ => return Err(format!("--repeats expects a positive count, got '{}'", text).into()),
+                    }
+                }
+                "--loo" => options.leave_one_out = true,
+                "--categories" => {
+                    options.categories = value()?
+                        .split(',')
+                        .map(|name| Category::parse(name.trim()))
+                        .collect::<Result<_, _>>()?;
+                }
+                "--scale" => options.scaling = Scaling::parse(&value()?)?,
+                "--outliers" => {
+                    let list = value()?;
+                    options.outlier_methods = if list.eq_ignore_ascii_case("all") {
+                        OutlierMethod::ALL.to_vec()
+                    } else {
+                        list.split(',').map(|m| OutlierMethod::parse(m.trim())).collect::<Result<_, _>>()?
+                    };
+                }
+                "--treat" => options.treatment = Some(Treatment::parse(&value()?)?),
+                "--permutations" => {
+                    let text = value()?;
+                    match text.parse::<usize>() {
+                        Ok(n) if n >= 1 => options.permutations = Some(n),
+                        _ => return Err(format!("--permutations expects a positive count, got '{}'", text).into()),
+                    }
+                }
+                "--seed" => {
+                    let text = value()?;
+                    options.seed = text.parse().map_err(|_| format!("--seed expects a whole number, got '{}'", text))?;
+                }
+                "--by" => options.by = Some(value()?),
+                "--groups" => {
+                    let pair = value()?;
+                    let (a, b) = pair
+                        .split_once(',')
+                        .ok_or_else(|| format!("--groups expects A,B, got '{}'", pair))?;
+                    options.groups = Some((a.trim().to_string(), b.trim().to_string()));
+                }
+                "--against" => options.against = Some(value()?),
+                "--paired" => {
+                    let pair = value()?;
+                    let (a, b) = pair
+                        .split_once(',')
+                        .ok_or_else(|| format!("--paired expects FIELD,FIELD, got '{}'", pair))?;
+                    options.paired = Some((Field::parse(a.trim())?, Field::parse(b.trim())?));
+                }
+                "--stat" => {
+                    options.stats = value()?
+                        .split(',')
+                        .map(GroupStat::parse)
+                        .collect::<Result<_, _>>()?;
+                }
+                "--pivot" => {
+                    options.pivot = Some(match value()?.to_lowercase().as_str() {
+                        "stats" => Pivot::Stats,
+                        "correlations" => Pivot::Correlations,
+                        "regression" => Pivot::Regression,
+                        other => {
+                            return Err(format!(
+                                "unknown pivot '{}' (expected stats, correlations or regression)",
+                                other
+                            )
+                            .into())
+                        }
+                    })
+                }
+                "--dist" => options.distribution = Some(value()?),
+                "--params" => options.params = parse_numbers(&value()?)?,
+                "--x" => options.points = parse_numbers(&value()?)?,
+                "--p" => {
+                    options.probabilities = parse_numbers(&value()?)?;
+                    if options.probabilities.iter().any(|p| !(0.0..=1.0).contains(p)) {
+                        return Err("--p expects probabilities between 0 and 1".into());
+                    }
+                }
+                "--format" => {
+                    options.format = match value()?.to_lowercase().as_str() {
+                        "text" => OutputFormat::Text,
+                        "csv" => OutputFormat::Csv,
+                        other => return Err(format!("unknown format '{}' (expected text or csv)", other).into()),
+                    }
+                }
+                "-o" | "--output" => options.output = Some(value()?),
+                _ => return Err(format!("unknown option '{}' (see --help)", arg).into()),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Destination for command output: the `--output` file or stdout.
+    pub fn writer(&self) -> Result<Box<dyn Write>, Box<dyn Error>> {
+        Ok(match &self.output {
+            Some(path) => Box::new(File::create(path)?),
+            None => Box::new(io::stdout()),
+        })
+    }
+}
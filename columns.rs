@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+// Canonical column names as they appear in career_dataset.csv
+pub const AGE: &str = "Age";
+pub const YEARS_OF_EXPERIENCE: &str = "Years of Experience";
+pub const JOB_SATISFACTION: &str = "Job Satisfaction";
+pub const SALARY: &str = "Salary";
+pub const FAMILY_INFLUENCE: &str = "Family Influence";
+pub const PROFESSIONAL_NETWORKS: &str = "Professional Networks";
+pub const FIELD_OF_STUDY: &str = "Field of Study";
+pub const CURRENT_OCCUPATION: &str = "Current Occupation";
+pub const GENDER: &str = "Gender";
+pub const EDUCATION_LEVEL: &str = "Education Level";
+pub const INDUSTRY_GROWTH_RATE: &str = "Industry Growth Rate";
+pub const WORK_LIFE_BALANCE: &str = "Work-Life Balance";
+pub const JOB_OPPORTUNITIES: &str = "Job Opportunities";
+pub const JOB_SECURITY: &str = "Job Security";
+pub const CAREER_CHANGE_INTEREST: &str = "Career Change Interest";
+pub const SKILLS_GAP: &str = "Skills Gap";
+pub const MENTORSHIP_AVAILABLE: &str = "Mentorship Available";
+pub const CERTIFICATIONS: &str = "Certifications";
+pub const FREELANCING_EXPERIENCE: &str = "Freelancing Experience";
+pub const GEOGRAPHIC_MOBILITY: &str = "Geographic Mobility";
+pub const CAREER_CHANGE_EVENTS: &str = "Career Change Events";
+pub const TECHNOLOGY_ADOPTION: &str = "Technology Adoption";
+pub const LIKELY_TO_CHANGE_OCCUPATION: &str = "Likely to Change Occupation";
+
+/// Header spellings accepted for each canonical column.
+#[derive(Debug, Clone)]
+pub struct ColumnAliases {
+    aliases: HashMap<String, Vec<String>>,
+}
+
+impl ColumnAliases {
+    pub fn new() -> Self {
+        ColumnAliases { aliases: HashMap::new() }
+    }
+
+    /// Registers an extra header spelling for `canonical`.
+    pub fn add_alias(&mut self, canonical: &str, alias: &str) -> &mut Self {
+        self.aliases
+            .entry(canonical.to_string())
+            .or_default()
+            .push(alias.to_string());
+        self
+    }
+
+    /// All spellings for `canonical`, the canonical name itself first.
+    pub fn candidates(&self, canonical: &str) -> Vec<String> {
+        let mut names = vec![canonical.to_string()];
+        if let Some(extra) = self.aliases.get(canonical) {
+            names.extend(extra.iter().cloned());
+        }
+        names
+    }
+}
+
+impl Default for ColumnAliases {
+    fn default() -> Self {
+        let mut aliases = ColumnAliases::new();
+        aliases
+            .add_alias(YEARS_OF_EXPERIENCE, "Experience")
+            .add_alias(YEARS_OF_EXPERIENCE, "years_of_experience")
+            .add_alias(JOB_SATISFACTION, "job_satisfaction")
+            .add_alias(FAMILY_INFLUENCE, "family_influence")
+            .add_alias(PROFESSIONAL_NETWORKS, "Professional Network Size")
+            .add_alias(PROFESSIONAL_NETWORKS, "professional_network_size");
+        aliases
+    }
+}
+
+/// Lowercases and strips all whitespace so " Job  Satisfaction" matches "job satisfaction".
+pub fn normalize_header(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Positions of the canonical columns within one file's header row.
+#[derive(Debug, Clone)]
+pub struct HeaderIndex {
+    positions: HashMap<String, usize>,
+}
+
+impl HeaderIndex {
+    /// Resolves every `required` column against `headers`, failing with a single
+    /// error that names all columns that could not be found.
+    pub fn resolve<'a, I>(headers: I, required: &[&str], aliases: &ColumnAliases) -> Result<HeaderIndex, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let by_name: HashMap<String, usize> = headers
+            .into_iter()
+            .enumerate()
+            .map(|(i, h)| (normalize_header(h), i))
+            .collect();
+
+        let mut positions = HashMap::new();
+        let mut missing = Vec::new();
+        for &column in required {
+            let candidates = aliases.candidates(column);
+            match candidates.iter().find_map(|c| by_name.get(&normalize_header(c))) {
+                Some(&pos) => {
+                    positions.insert(column.to_string(), pos);
+                }
+                None => missing.push(format!("'{}' (tried: {})", column, candidates.join(", "))),
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(format!("Missing required column(s): {}", missing.join("; ")).into());
+        }
+        Ok(HeaderIndex { positions })
+    }
+
+    pub fn position(&self, column: &str) -> usize {
+        self.positions[column]
+    }
+
+    /// The trimmed cell for `column` in `record`, or "" if the row is short.
+    pub fn get<'r>(&self, record: &'r csv::StringRecord, column: &str) -> &'r str {
+        record.get(self.position(column)).unwrap_or("").trim()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_normalize_case_and_whitespace() {
+        assert_eq!(normalize_header(" Job  Satisfaction\t"), "jobsatisfaction");
+        assert_eq!(normalize_header("WORK-LIFE BALANCE"), "work-lifebalance");
+        assert_ne!(normalize_header("job_satisfaction"), normalize_header("Job Satisfaction"));
+    }
+
+    #[test]
+    fn columns_resolve_through_aliases_and_normalization() {
+        let headers = ["salary ", "EXPERIENCE", "Professional  Network Size", "Age"];
+        let required = [AGE, SALARY, YEARS_OF_EXPERIENCE, PROFESSIONAL_NETWORKS];
+        let index = HeaderIndex::resolve(headers, &required, &ColumnAliases::default()).unwrap();
+        assert_eq!(required.map(|c| index.position(c)), [3, 0, 1, 2]);
+
+        let record = csv::StringRecord::from(vec![" 52000 ", "7", "12"]);
+        assert_eq!(index.get(&record, SALARY), "52000");
+        assert_eq!(index.get(&record, AGE), "");
+    }
+
+    #[test]
+    fn canonical_names_win_over_aliases() {
+        let headers = ["Experience", "Years of Experience"];
+        let index = HeaderIndex::resolve(headers, &[YEARS_OF_EXPERIENCE], &ColumnAliases::default()).unwrap();
+        assert_eq!(index.position(YEARS_OF_EXPERIENCE), 1);
+
+        let mut aliases = ColumnAliases::new();
+        aliases.add_alias(GENDER, "Sex");
+        assert_eq!(aliases.candidates(GENDER), ["Gender", "Sex"]);
+        let index = HeaderIndex::resolve(["sex"], &[GENDER], &aliases).unwrap();
+        assert_eq!(index.position(GENDER), 0);
+    }
+
+    #[test]
+    fn every_missing_column_is_reported_at_once() {
+        let error = HeaderIndex::resolve(["Age"], &[AGE, SALARY, JOB_SATISFACTION], &ColumnAliases::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Missing required column(s): 'Salary' (tried: Salary); \
+             'Job Satisfaction' (tried: Job Satisfaction, job_satisfaction)"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Multiple-comparison adjustments of p-values.
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Correction {
+    None,
+    Bonferroni,
+    Holm,
+    BenjaminiHochberg,
+}
+
+impl Correction {
+    pub const ALL: [Correction; 4] = [
+        Correction::None,
+        Correction::Bonferroni,
+        Correction::Holm,
+        Correction::BenjaminiHochberg,
+    ];
+
+    pub fn parse(name: &str) -> Result<Correction, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "none" => Ok(Correction::None),
+            "bonferroni" => Ok(Correction::Bonferroni),
+            "holm" => Ok(Correction::Holm),
+            "bh" | "fdr" | "benjamini-hochberg" => Ok(Correction::BenjaminiHochberg),
+            _ => Err(format!("unknown correction '{}' (expected none, bonferroni, holm or bh)", name).into()),
+        }
+    }
+
+    /// Short identifier used in exported column names.
+    pub fn key(self) -> &'static str {
+        match self {
+            Correction::None => "none",
+            Correction::Bonferroni => "bonferroni",
+            Correction::Holm => "holm",
+            Correction::BenjaminiHochberg => "bh",
+        }
+    }
+
+    /// Adjusted p-values in the same order as `p_values`.
+    pub fn adjust(self, p_values: &[f64]) -> Vec<f64> {
+        let m = p_values.len();
+        let mf = m as f64;
+        let mut order: Vec<usize> = (0..m).collect();
+        order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+        let mut adjusted = vec![0.0; m];
+
+        match self {
+            Correction::None => adjusted.copy_from_slice(p_values),
+            Correction::Bonferroni => {
+                for (a, p) in adjusted.iter_mut().zip(p_values) {
+                    *a = (p * mf).min(1.0);
+                }
+            }
+            Correction::Holm => {
+                // Step-down: running maximum of (m - rank) * p over ascending p
+                let mut running = 0.0_f64;
+                for (rank, &i) in order.iter().enumerate() {
+                    running = running.max((mf - rank as f64) * p_values[i]);
+                    adjusted[i] = running.min(1.0);
+                }
+            }
+            Correction::BenjaminiHochberg => {
+                // Step-up: running minimum of m / rank * p over descending p
+                let mut running = 1.0_f64;
+                for (rank, &i) in order.iter().enumerate().rev() {
+                    running = running.min(mf / (rank as f64 + 1.0) * p_values[i]);
+                    adjusted[i] = running;
+                }
+            }
+        }
+        adjusted
+    }
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Correction::None => "none",
+            Correction::Bonferroni => "Bonferroni",
+            Correction::Holm => "Holm",
+            Correction::BenjaminiHochberg => "Benjamini-Hochberg",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    // Unsorted, with a tie at 0.04.
+    const P: [f64; 7] = [0.01, 0.04, 0.03, 0.005, 0.5, 0.2, 0.04];
+
+    fn assert_all_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            close(*a, *e, 1e-12);
+        }
+    }
+
+    #[test]
+    fn bonferroni_matches_p_adjust() {
+        // p.adjust(p, "bonferroni")
+        assert_all_close(&Correction::Bonferroni.adjust(&P), &[0.07, 0.28, 0.21, 0.035, 1.0, 1.0, 0.28]);
+    }
+
+    #[test]
+    fn holm_matches_p_adjust() {
+        // p.adjust(p, "holm"): 3 * 0.04 = 0.12 is raised to the 0.16 before it
+        assert_all_close(&Correction::Holm.adjust(&P), &[0.06, 0.16, 0.15, 0.035, 0.5, 0.4, 0.16]);
+    }
+
+    #[test]
+    fn benjamini_hochberg_matches_p_adjust() {
+        // p.adjust(p, "BH"): 7 / 3 * 0.03 = 0.07 is lowered to the 0.056 after it
+        let expected = [0.035, 0.056, 0.056, 0.035, 0.5, 0.7 / 3.0, 0.056];
+        assert_all_close(&Correction::BenjaminiHochberg.adjust(&P), &expected);
+    }
+
+    #[test]
+    fn adjustments_are_monotone_in_p_and_capped_at_one() {
+        let p = [0.3, 0.6, 0.9, 0.45];
+        assert_all_close(&Correction::Holm.adjust(&p), &[1.0, 1.0, 1.0, 1.0]);
+        assert_all_close(&Correction::Bonferroni.adjust(&p), &[1.0, 1.0, 1.0, 1.0]);
+        assert_all_close(&Correction::BenjaminiHochberg.adjust(&p), &[0.8, 0.8, 0.9, 0.8]);
+
+        for correction in Correction::ALL {
+            let adjusted = correction.adjust(&P);
+            let mut order: Vec<usize> = (0..P.len()).collect();
+            order.sort_by(|&a, &b| P[a].total_cmp(&P[b]));
+            for pair in order.windows(2) {
+                assert!(adjusted[pair[0]] <= adjusted[pair[1]], "{} is not monotone", correction);
+            }
+            assert!(adjusted.iter().zip(&P).all(|(a, p)| a >= p && *a <= 1.0));
+        }
+    }
+
+    #[test]
+    fn no_p_values_adjust_to_none() {
+        assert!(Correction::Holm.adjust(&[]).is_empty());
+        assert_all_close(&Correction::None.adjust(&P), &P);
+    }
+}
@@ -0,0 +1,427 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::correction::Correction;
+use crate::distributions::{Normal, StudentT};
+use crate::missing::pairwise_complete;
+use crate::table::NamedSeries;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrelationMethod {
+    Pearson,
+    Spearman,
+    Kendall,
+}
+
+impl CorrelationMethod {
+    pub const ALL: [CorrelationMethod; 3] = [
+        CorrelationMethod::Pearson,
+        CorrelationMethod::Spearman,
+        CorrelationMethod::Kendall,
+    ];
+
+    pub fn parse(name: &str) -> Result<CorrelationMethod, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "pearson" => Ok(CorrelationMethod::Pearson),
+            "spearman" => Ok(CorrelationMethod::Spearman),
+            "kendall" => Ok(CorrelationMethod::Kendall),
+            _ => Err(format!("unknown correlation method '{}' (expected pearson, spearman or kendall)", name).into()),
+        }
+    }
+
+    /// Conventional symbol of the coefficient, e.g. "Kendall tau-b".
+    pub fn statistic_name(self) -> &'static str {
+        match self {
+            CorrelationMethod::Pearson => "Pearson r",
+            CorrelationMethod::Spearman => "Spearman rho",
+            CorrelationMethod::Kendall => "Kendall tau-b",
+        }
+    }
+}
+
+impl fmt::Display for CorrelationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CorrelationMethod::Pearson => "Pearson",
+            CorrelationMethod::Spearman => "Spearman",
+            CorrelationMethod::Kendall => "Kendall",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A correlation estimate with its significance test.
+#[derive(Debug, Clone, Copy)]
+pub struct CorrelationTest {
+    pub estimate: f64,
+    pub n: usize,
+    pub p_value: f64,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Pearson's r; NaN when either variable is constant.
+pub fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let (mean_x, mean_y) = (mean(x), mean(y));
+    let mut sxy = 0.0;
+    let mut sxx = 0.0;
+    let mut syy = 0.0;
+    for (a, b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mean_x, b - mean_y);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    sxy / (sxx * syy).sqrt()
+}
+
+/// 1-based ranks, ties receiving the average of the ranks they span.
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let tied = order[i..].iter().take_while(|&&k| values[k] == values[order[i]]).count();
+        let average = i as f64 + (tied as f64 + 1.0) / 2.0;
+        for &k in &order[i..i + tied] {
+            ranks[k] = average;
+        }
+        i += tied;
+    }
+    ranks
+}
+
+/// Spearman's rho: Pearson's r of the average ranks.
+pub fn spearman(x: &[f64], y: &[f64]) -> f64 {
+    pearson(&ranks(x), &ranks(y))
+}
+
+/// Sums over the groups of tied values (of sizes t) of one variable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TieSums {
+    /// Σ t(t-1)/2, the number of tied pairs.
+    pub pairs: f64,
+    /// Σ t(t-1)(2t+5)
+    pub v: f64,
+    /// Σ t(t-1)
+    pub v1: f64,
+    /// Σ t(t-1)(t-2)
+    pub v2: f64,
+}
+
+/// Tie-group sums of an already sorted key.
+fn tie_sums<T: PartialEq>(sorted: &[T]) -> TieSums {
+    let mut sums = TieSums::default();
+    let mut i = 0;
+    while i < sorted.len() {
+        let run = sorted[i..].iter().take_while(|v| **v == sorted[i]).count();
+        let t = run as f64;
+        sums.pairs += t * (t - 1.0) / 2.0;
+        sums.v += t * (t - 1.0) * (2.0 * t + 5.0);
+        sums.v1 += t * (t - 1.0);
+        sums.v2 += t * (t - 1.0) * (t - 2.0);
+        i += run;
+    }
+    sums
+}
+
+/// Pair counts behind Kendall's tau-b.
+#[derive(Debug, Clone, Copy)]
+pub struct KendallCounts {
+    pub n: f64,
+    /// Concordant minus discordant pairs.
+    pub s: f64,
+    /// Total pairs n(n-1)/2.
+    pub pairs: f64,
+    pub ties_x: TieSums,
+    pub ties_y: TieSums,
+}
+
+impl KendallCounts {
+    /// Kendall's tau-b, which corrects tau for ties in either variable.
+    pub fn tau_b(&self) -> f64 {
+        self.s / ((self.pairs - self.ties_x.pairs) * (self.pairs - self.ties_y.pairs)).sqrt()
+    }
+
+    /// Variance of S under independence, with the tie corrections of Kendall (1970).
+    pub fn variance_s(&self) -> f64 {
+        let n = self.n;
+        let (x, y) = (&self.ties_x, &self.ties_y);
+        (n * (n - 1.0) * (2.0 * n + 5.0) - x.v - y.v) / 18.0
+            + x.v1 * y.v1 / (2.0 * n * (n - 1.0))
+            + x.v2 * y.v2 / (9.0 * n * (n - 1.0) * (n - 2.0))
+    }
+}
+
+/// Sorts `values` with a merge sort and returns the number of inversions.
+fn count_inversions(values: &mut [f64]) -> f64 {
+    let n = values.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let mid = n / 2;
+    let mut swaps = count_inversions(&mut values[..mid]) + count_inversions(&mut values[mid..]);
+    let mut merged = Vec::with_capacity(n);
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < n {
+        if values[j] < values[i] {
+            merged.push(values[j]);
+            swaps += (mid - i) as f64;
+            j += 1;
+        } else {
+            merged.push(values[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&values[i..mid]);
+    merged.extend_from_slice(&values[j..n]);
+    values.copy_from_slice(&merged);
+    swaps
+}
+
+/// Knight's O(n log n) count of concordant, discordant and tied pairs.
+pub fn kendall_counts(x: &[f64], y: &[f64]) -> KendallCounts {
+    let n = x.len() as f64;
+    let mut pairs_xy: Vec<(f64, f64)> = x.iter().copied().zip(y.iter().copied()).collect();
+    pairs_xy.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+    let xs: Vec<f64> = pairs_xy.iter().map(|p| p.0).collect();
+    let ties_x = tie_sums(&xs);
+    let tied_xy = tie_sums(&pairs_xy).pairs;
+
+    let mut ys: Vec<f64> = pairs_xy.iter().map(|p| p.1).collect();
+    let swaps = count_inversions(&mut ys);
+    let ties_y = tie_sums(&ys);
+
+    let pairs = n * (n - 1.0) / 2.0;
+    KendallCounts {
+        n,
+        s: pairs - ties_x.pairs - ties_y.pairs + tied_xy - 2.0 * swaps,
+        pairs,
+        ties_x,
+        ties_y,
+    }
+}
+
+/// Estimate and two-sided p-value of H0: no association. `None` if there are
+/// fewer than three observations or either variable is constant.
+pub fn correlation_test(method: CorrelationMethod, x: &[f64], y: &[f64]) -> Option<CorrelationTest> {
+    let n = x.len();
+    if n < 3 {
+        return None;
+    }
+    let nf = n as f64;
+    let (estimate, p_value) = match method {
+        // Spearman's rho is Pearson's r on average ranks, so ties need no special
+        // formula; its t approximation is adequate beyond very small n.
+        CorrelationMethod::Pearson | CorrelationMethod::Spearman => {
+            let r = if method == CorrelationMethod::Pearson {
+                pearson(x, y)
+            } else {
+                spearman(x, y)
+            };
+            let t = r * ((nf - 2.0) / (1.0 - r * r).max(0.0)).sqrt();
+            (r, StudentT::new(nf - 2.0).two_sided_p(t))
+        }
+        CorrelationMethod::Kendall => {
+            let counts = kendall_counts(x, y);
+            (counts.tau_b(), Normal.two_sided_p(counts.s / counts.variance_s().sqrt()))
+        }
+    };
+    if estimate.is_nan() {
+        return None;
+    }
+    Some(CorrelationTest { estimate, n, p_value })
+}
+
+/// Every method's test of the same pair, in `CorrelationMethod::ALL` order.
+pub fn all_methods(x: &[f64], y: &[f64]) -> Vec<(CorrelationMethod, Option<CorrelationTest>)> {
+    CorrelationMethod::ALL
+        .iter()
+        .map(|&method| (method, correlation_test(method, x, y)))
+        .collect()
+}
+
+/// Correlations between every pair of a set of columns, on pairwise-complete observations.
+#[derive(Debug, Clone)]
+pub struct CorrelationMatrix {
+    pub method: CorrelationMethod,
+    pub labels: Vec<String>,
+    /// `tests[i][j]`, symmetric; `None` on the diagonal and for untestable pairs.
+    pub tests: Vec<Vec<Option<CorrelationTest>>>,
+}
+
+impl CorrelationMatrix {
+    pub fn compute(columns: &[NamedSeries], method: CorrelationMethod) -> Self {
+        let k = columns.len();
+        let mut tests = vec![vec![None; k]; k];
+        for i in 0..k {
+            for j in i + 1..k {
+                let (x, y) = pairwise_complete(&columns[i].1, &columns[j].1);
+                let test = correlation_test(method, &x, &y);
+                tests[i][j] = test;
+                tests[j][i] = test;
+            }
+        }
+        CorrelationMatrix {
+            method,
+            labels: columns.iter().map(|(name, _)| name.clone()).collect(),
+            tests,
+        }
+    }
+
+    /// Upper-triangle pairs (i, j) that have a test, in row order.
+    fn tested_pairs(&self) -> Vec<(usize, usize)> {
+        let k = self.labels.len();
+        (0..k)
+            .flat_map(|i| (i + 1..k).map(move |j| (i, j)))
+            .filter(|&(i, j)| self.tests[i][j].is_some())
+            .collect()
+    }
+
+    /// p-values adjusted across all tested pairs, laid out like `tests`.
+    pub fn adjusted_p_values(&self, correction: Correction) -> Vec<Vec<Option<f64>>> {
+        let pairs = self.tested_pairs();
+        let raw: Vec<f64> = pairs
+            .iter()
+            .map(|&(i, j)| self.tests[i][j].map_or(1.0, |t| t.p_value))
+            .collect();
+        let k = self.labels.len();
+        let mut adjusted = vec![vec![None; k]; k];
+        for (&(i, j), p) in pairs.iter().zip(correction.adjust(&raw)) {
+            adjusted[i][j] = Some(p);
+            adjusted[j][i] = Some(p);
+        }
+        adjusted
+    }
+
+    /// Aligned table of estimates, starred by significance after `correction`.
+    pub fn render(&self, correction: Correction) -> String {
+        let adjusted = self.adjusted_p_values(correction);
+        let label_width = self.labels.iter().map(|l| l.len() + 5).max().unwrap_or(0);
+        let cell_width = 10;
+
+        let mut out = format!("{} correlations ({} adjusted significance)\n", self.method, correction);
+        out.push_str(&format!("{:label_width$}", ""));
+        for j in 0..self.labels.len() {
+            out.push_str(&format!("{:>cell_width$}", format!("({})", j + 1)));
+        }
+        out.push('\n');
+
+        for (i, label) in self.labels.iter().enumerate() {
+            out.push_str(&format!("{:<label_width$}", format!("({}) {}", i + 1, label)));
+            for (j, (test, p)) in self.tests[i].iter().zip(&adjusted[i]).enumerate() {
+                let cell = match (test, p) {
+                    _ if i == j => "1".to_string(),
+                    (Some(test), Some(p)) => format!("{:.3}{}", test.estimate, stars(*p)),
+                    _ => "-".to_string(),
+                };
+                out.push_str(&format!("{:>cell_width$}", cell));
+            }
+            out.push('\n');
+        }
+        out.push_str("* p < 0.05, ** p < 0.01, *** p < 0.001\n");
+        out
+    }
+
+    /// Appends one row per tested pair to `wtr`, with raw and adjusted p-values.
+    pub fn write_rows<W: std::io::Write>(&self, wtr: &mut csv::Writer<W>) -> Result<(), Box<dyn Error>> {
+        let adjusted: Vec<_> = Correction::ALL[1..]
+            .iter()
+            .map(|&c| self.adjusted_p_values(c))
+            .collect();
+        for (i, j) in self.tested_pairs() {
+            let test = self.tests[i][j].expect("tested pair");
+            let mut row = vec![
+                self.method.to_string(),
+                self.labels[i].clone(),
+                self.labels[j].clone(),
+                test.n.to_string(),
+                test.estimate.to_string(),
+                test.p_value.to_string(),
+            ];
+            row.extend(adjusted.iter().map(|a| a[i][j].map_or(String::new(), |p| p.to_string())));
+            wtr.write_record(&row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Header matching `CorrelationMatrix::write_rows`.
+pub fn csv_header() -> Vec<String> {
+    let mut header: Vec<String> = ["method", "variable_1", "variable_2", "n", "estimate", "p_value"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    header.extend(Correction::ALL[1..].iter().map(|c| format!("p_{}", c.key())));
+    header
+}
+
+pub fn stars(p: f64) -> &'static str {
+    if p < 0.001 {
+        "***"
+    } else if p < 0.01 {
+        "**"
+    } else if p < 0.05 {
+        "*"
+    } else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{close, relative};
+
+    // Ties within x, within y, and one tied (x, y) pair: (4, 6) twice.
+    const X: [f64; 12] = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0, 6.0, 7.0, 7.0, 8.0];
+    const Y: [f64; 12] = [2.0, 1.0, 3.0, 3.0, 5.0, 6.0, 6.0, 6.0, 6.0, 9.0, 8.0, 8.0];
+
+    #[test]
+    fn kendall_matches_cor_test_with_ties() {
+        // cor.test(x, y, method = "kendall", exact = FALSE)
+        let counts = kendall_counts(&X, &Y);
+        assert_eq!(counts.s, 50.0);
+        close(counts.tau_b(), 0.8406032989, 1e-9);
+        close(counts.variance_s(), 196.9515151515, 1e-9);
+
+        let test = correlation_test(CorrelationMethod::Kendall, &X, &Y).unwrap();
+        close(test.estimate, 0.8406032989, 1e-9);
+        relative(test.p_value, 0.000366932867, 1e-6);
+    }
+
+    #[test]
+    fn kendall_counts_agree_with_counting_every_pair() {
+        let mut s = 0.0;
+        for i in 0..X.len() {
+            for j in i + 1..X.len() {
+                s += match (X[i] - X[j]) * (Y[i] - Y[j]) {
+                    product if product > 0.0 => 1.0,
+                    product if product < 0.0 => -1.0,
+                    _ => 0.0,
+                };
+            }
+        }
+        assert_eq!(kendall_counts(&X, &Y).s, s);
+    }
+
+    #[test]
+    fn spearman_matches_cor_test_with_ties() {
+        // cor.test(x, y, method = "spearman", exact = FALSE)
+        assert_eq!(ranks(&X)[..4], [1.0, 2.5, 2.5, 4.0]);
+        close(spearman(&X, &Y), 0.9386832123, 1e-9);
+
+        let test = correlation_test(CorrelationMethod::Spearman, &X, &Y).unwrap();
+        relative(test.p_value, 6.15516239512e-6, 1e-6);
+    }
+
+    #[test]
+    fn constant_variables_have_no_test() {
+        assert!(correlation_test(CorrelationMethod::Kendall, &X, &[1.0; 12]).is_none());
+        assert!(correlation_test(CorrelationMethod::Spearman, &X[..2], &Y[..2]).is_none());
+    }
+}
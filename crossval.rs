@@ -0,0 +1,482 @@
+//! Cross-validated estimates of out-of-sample performance for any model that
+//! can be refitted on a subset of individuals and then predict the rest.
+
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::evaluation::{Evaluation, DEFAULT_THRESHOLD};
+use crate::formula::Formula;
+use crate::individual::Individual;
+use crate::rng::Rng;
+
+/// Folds when `--folds` is not given.
+pub const DEFAULT_FOLDS: usize = 10;
+/// Per-fold rows printed by [`CrossValidationResult::render`]; the CSV has them all.
+pub const FOLDS_SHOWN: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    /// Predictions are values of the response.
+    Regression,
+    /// Predictions are probabilities that a 0/1 response is 1.
+    Classification,
+}
+
+/// A model that can be fitted on some individuals and predict others.
+pub trait FitPredict {
+    /// The response and terms the model uses; individuals missing any of them are not scored.
+    fn formula(&self) -> &Formula;
+
+    fn task(&self) -> Task;
+
+    /// Fits on `train`, replacing any earlier fit.
+    fn fit(&mut self, train: &[Individual]) -> Result<(), Box<dyn Error>>;
+
+    /// Prediction for one individual, `None` before fitting or when a term is missing.
+    fn predict(&self, individual: &Individual) -> Option<f64>;
+}
+
+/// How individuals are split into folds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    KFold(usize),
+    /// k folds with each response value spread as evenly as possible.
+    Stratified(usize),
+    /// One fold per individual.
+    LeaveOneOut,
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scheme::KFold(k) => write!(f, "{}-fold", k),
+            Scheme::Stratified(k) => write!(f, "stratified {}-fold", k),
+            Scheme::LeaveOneOut => write!(f, "leave-one-out"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Rmse,
+    Mae,
+    /// 1 - SSE / SST about the mean of the scored individuals.
+    RSquared,
+    /// At `DEFAULT_THRESHOLD`.
+    Accuracy,
+    Auc,
+}
+
+impl Metric {
+    pub fn for_task(task: Task) -> &'static [Metric] {
+        match task {
+            Task::Regression => &[Metric::Rmse, Metric::Mae, Metric::RSquared],
+            Task::Classification => &[Metric::Accuracy, Metric::Auc],
+        }
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Metric::Rmse => "rmse",
+            Metric::Mae => "mae",
+            Metric::RSquared => "r_squared",
+            Metric::Accuracy => "accuracy",
+            Metric::Auc => "auc",
+        }
+    }
+
+    /// The metric of `predicted` against `observed`, NaN where it is undefined
+    /// (R-squared of one individual, AUC of a single class).
+    pub fn compute(self, predicted: &[f64], observed: &[f64]) -> f64 {
+        let n = observed.len() as f64;
+        let errors = predicted.iter().zip(observed).map(|(p, o)| p - o);
+        match self {
+            Metric::Rmse => (errors.map(|e| e * e).sum::<f64>() / n).sqrt(),
+            Metric::Mae => errors.map(f64::abs).sum::<f64>() / n,
+            Metric::RSquared => {
+                let mean = observed.iter().sum::<f64>() / n;
+                let sst: f64 = observed.iter().map(|o| (o - mean).powi(2)).sum();
+                if sst == 0.0 {
+                    f64::NAN
+                } else {
+                    1.0 - errors.map(|e| e * e).sum::<f64>() / sst
+                }
+            }
+            Metric::Accuracy => {
+                let correct = predicted
+                    .iter()
+                    .zip(observed)
+                    .filter(|(&p, &o)| (p >= DEFAULT_THRESHOLD) == (o == 1.0))
+                    .count();
+                correct as f64 / n
+            }
+            Metric::Auc => Evaluation::new(predicted, observed).map_or(f64::NAN, |e| e.auc),
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Metric::Rmse => "RMSE",
+            Metric::Mae => "MAE",
+            Metric::RSquared => "R-squared",
+            Metric::Accuracy => "Accuracy",
+            Metric::Auc => "AUC",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Metrics of one held-out fold.
+#[derive(Debug, Clone)]
+pub struct FoldResult {
+    /// From 1.
+    pub repeat: usize,
+    /// From 1.
+    pub fold: usize,
+    pub train: usize,
+    /// Individuals scored.
+    pub test: usize,
+    /// In the order of `CrossValidationResult::metrics`.
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrossValidation {
+    pub scheme: Scheme,
+    /// Independent reshuffles of the folds; 1 for leave-one-out.
+    pub repeats: usize,
+    pub seed: u64,
+}
+
+impl CrossValidation {
+    /// Fold of each of `y.len()` individuals for one repeat.
+    fn assign(&self, y: &[f64], rng: &mut Rng) -> Vec<usize> {
+        let n = y.len();
+        let mut folds = vec![0; n];
+        match self.scheme {
+            Scheme::KFold(k) => {
+                let mut order: Vec<usize> = (0..n).collect();
+                rng.shuffle(&mut order);
+                for (position, &i) in order.iter().enumerate() {
+                    folds[i] = position * k / n;
+                }
+            }
+            Scheme::Stratified(k) => {
+                // Deal each response value's shuffled individuals round the folds in turn
+                let mut order: Vec<usize> = (0..n).collect();
+                rng.shuffle(&mut order);
+                order.sort_by(|&a, &b| y[a].total_cmp(&y[b]));
+                for (position, &i) in order.iter().enumerate() {
+                    folds[i] = position % k;
+                }
+            }
+            Scheme::LeaveOneOut => folds = (0..n).collect(),
+        }
+        folds
+    }
+
+    /// Refits `model` once per fold and repeat on the rest of the complete
+    /// cases, scoring the held-out fold each time.
+    pub fn run(&self, model: &mut dyn FitPredict, individuals: &[Individual]) -> Result<CrossValidationResult, Box<dyn Error>> {
+        let formula = model.formula().clone();
+        let complete: Vec<&Individual> = individuals
+            .iter()
+            .filter(|i| formula.response.value(i).is_some() && formula.row(i).is_some())
+            .collect();
+        let n = complete.len();
+        let y: Vec<f64> = complete.iter().filter_map(|i| formula.response.value(i)).collect();
+        let k = match self.scheme {
+            Scheme::KFold(k) | Scheme::Stratified(k) => k,
+            Scheme::LeaveOneOut => n,
+        };
+        if k < 2 || k > n {
+            return Err(format!("{} folds need between 2 and {} complete cases", k, n).into());
+        }
+        if self.repeats == 0 || (self.scheme == Scheme::LeaveOneOut && self.repeats > 1) {
+            return Err("repeats must be positive, and leave-one-out cannot be repeated".into());
+        }
+        if matches!(self.scheme, Scheme::Stratified(_)) && !(formula.response.is_boolean() || formula.response.is_ordinal()) {
+            return Err(format!("stratifying needs a categorical response, and '{}' is continuous", formula.response.key()).into());
+        }
+
+        let task = model.task();
+        let metrics = Metric::for_task(task).to_vec();
+        let mut rng = Rng::new(self.seed);
+        let mut folds = Vec::with_capacity(k * self.repeats);
+        let (mut all_predicted, mut all_observed) = (Vec::new(), Vec::new());
+        let mut predictions = Vec::with_capacity(n * self.repeats);
+        for repeat in 1..=self.repeats {
+            let assignment = self.assign(&y, &mut rng);
+            for fold in 0..k {
+                let train: Vec<Individual> = (0..n)
+                    .filter(|&j| assignment[j] != fold)
+                    .map(|j| complete[j].clone())
+                    .collect();
+                model
+                    .fit(&train)
+                    .map_err(|e| format!("repeat {}, fold {}: {}", repeat, fold + 1, e))?;
+                let (mut predicted, mut observed) = (Vec::new(), Vec::new());
+                for j in (0..n).filter(|&j| assignment[j] == fold) {
+                    if let Some(prediction) = model.predict(complete[j]) {
+                        predicted.push(prediction);
+                        observed.push(y[j]);
+                        predictions.push((complete[j].id, prediction));
+                    }
+                }
+                folds.push(FoldResult {
+                    repeat,
+                    fold: fold + 1,
+                    train: train.len(),
+                    test: predicted.len(),
+                    values: metrics.iter().map(|m| m.compute(&predicted, &observed)).collect(),
+                });
+                all_predicted.extend(predicted);
+                all_observed.extend(observed);
+            }
+        }
+
+        Ok(CrossValidationResult {
+            formula,
+            scheme: self.scheme,
+            repeats: self.repeats,
+            seed: self.seed,
+            n,
+            pooled: metrics.iter().map(|m| m.compute(&all_predicted, &all_observed)).collect(),
+            metrics,
+            folds,
+            predictions,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CrossValidationResult {
+    pub formula: Formula,
+    pub scheme: Scheme,
+    pub repeats: usize,
+    pub seed: u64,
+    /// Complete cases split into folds.
+    pub n: usize,
+    pub metrics: Vec<Metric>,
+    pub folds: Vec<FoldResult>,
+    /// Metrics over every out-of-fold prediction at once (each repeat contributes all individuals).
+    pub pooled: Vec<f64>,
+    /// Out-of-fold prediction per `Individual::id`, repeat after repeat.
+    pub predictions: Vec<(usize, f64)>,
+}
+
+impl CrossValidationResult {
+    /// Mean and standard deviation of metric `j` over the folds where it is defined.
+    pub fn summary(&self, j: usize) -> (f64, f64) {
+        let values: Vec<f64> = self.folds.iter().map(|f| f.values[j]).filter(|v| !v.is_nan()).collect();
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let sd = if values.len() < 2 {
+            f64::NAN
+        } else {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1.0)).sqrt()
+        };
+        (mean, sd)
+    }
+
+    /// Fold means, fold standard deviations and pooled values, one entry per metric each.
+    fn aggregates(&self) -> [Vec<f64>; 3] {
+        let summaries: Vec<(f64, f64)> = (0..self.metrics.len()).map(|j| self.summary(j)).collect();
+        [
+            summaries.iter().map(|s| s.0).collect(),
+            summaries.iter().map(|s| s.1).collect(),
+            self.pooled.clone(),
+        ]
+    }
+
+    pub fn render(&self, title: &str) -> String {
+        let mut out = String::new();
+        writeln!(out, "Cross-validation: {}, {}", title, self.formula).unwrap();
+        let repeated = if self.repeats > 1 { format!(" repeated {} times", self.repeats) } else { String::new() };
+        writeln!(out, "{}{} on {} complete cases, seed {}", self.scheme, repeated, self.n, self.seed).unwrap();
+        write!(out, "{:<10} {:>6} {:>6} {:>6}", "Repeat", "Fold", "Train", "Test").unwrap();
+        for metric in &self.metrics {
+            write!(out, " {:>14}", metric.to_string()).unwrap();
+        }
+        writeln!(out).unwrap();
+        if self.folds.len() <= FOLDS_SHOWN {
+            for fold in &self.folds {
+                write!(out, "{:<10} {:>6} {:>6} {:>6}", fold.repeat, fold.fold, fold.train, fold.test).unwrap();
+                for value in &fold.values {
+                    write!(out, " {:>14.4}", value).unwrap();
+                }
+                writeln!(out).unwrap();
+            }
+        } else {
+            writeln!(out, "({} folds; use --format csv for each)", self.folds.len()).unwrap();
+        }
+        for (label, values) in ["Mean", "Std. dev.", "Pooled"].into_iter().zip(self.aggregates()) {
+            write!(out, "{:<31}", label).unwrap();
+            for value in values {
+                write!(out, " {:>14.4}", value).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        out
+    }
+
+    /// Column names matching [`CrossValidationResult::write_rows`].
+    pub fn csv_header(&self) -> Vec<String> {
+        let mut header: Vec<String> = ["repeat", "fold", "train", "test"].iter().map(|s| s.to_string()).collect();
+        header.extend(self.metrics.iter().map(|m| m.key().to_string()));
+        header
+    }
+
+    /// One row per fold, then `mean`, `sd` and `pooled` rows with the fold column empty.
+    pub fn write_rows<W: std::io::Write>(&self, wtr: &mut csv::Writer<W>) -> Result<(), Box<dyn Error>> {
+        for fold in &self.folds {
+            let mut row = vec![
+                fold.repeat.to_string(),
+                fold.fold.to_string(),
+                fold.train.to_string(),
+                fold.test.to_string(),
+            ];
+            row.extend(fold.values.iter().map(f64::to_string));
+            wtr.write_record(&row)?;
+        }
+        for (label, values) in ["mean", "sd", "pooled"].into_iter().zip(self.aggregates()) {
+            let mut row = vec![label.to_string(), String::new(), String::new(), String::new()];
+            row.extend(values.iter().map(f64::to_string));
+            wtr.write_record(&row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::individual::Field;
+
+    /// Predicts the training mean of the response.
+    struct MeanModel {
+        formula: Formula,
+        mean: Option<f64>,
+    }
+
+    impl FitPredict for MeanModel {
+        fn formula(&self) -> &Formula {
+            &self.formula
+        }
+
+        fn task(&self) -> Task {
+            Task::Regression
+        }
+
+        fn fit(&mut self, train: &[Individual]) -> Result<(), Box<dyn Error>> {
+            let values = self.formula.response.values(train);
+            let values: Vec<f64> = values.into_iter().flatten().collect();
+            self.mean = Some(values.iter().sum::<f64>() / values.len() as f64);
+            Ok(())
+        }
+
+        fn predict(&self, _: &Individual) -> Option<f64> {
+            self.mean
+        }
+    }
+
+    /// 30 individuals, 9 of them (every third from id 3) likely to change occupation.
+    fn individuals() -> Vec<Individual> {
+        (0..30)
+            .map(|id| Individual {
+                id,
+                age: Some(20.0 + id as f64),
+                salary: Some(1000.0 * (id % 7) as f64),
+                likely_to_change_occupation: Some(id >= 3 && id % 3 == 0),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn counts(assignment: &[usize], k: usize, keep: impl Fn(usize) -> bool) -> Vec<usize> {
+        let mut counts = vec![0; k];
+        for (_, &fold) in assignment.iter().enumerate().filter(|(i, _)| keep(*i)) {
+            counts[fold] += 1;
+        }
+        counts
+    }
+
+    fn spread(counts: &[usize]) -> usize {
+        counts.iter().max().unwrap() - counts.iter().min().unwrap()
+    }
+
+    #[test]
+    fn stratified_folds_preserve_class_ratios() {
+        let y: Vec<f64> = individuals().iter().map(|i| Field::LikelyToChangeOccupation.value(i).unwrap()).collect();
+        for k in [3, 4, 5] {
+            let cv = CrossValidation { scheme: Scheme::Stratified(k), repeats: 1, seed: 7 };
+            let assignment = cv.assign(&y, &mut Rng::new(7));
+            let positives = counts(&assignment, k, |i| y[i] == 1.0);
+            let negatives = counts(&assignment, k, |i| y[i] == 0.0);
+            assert!(spread(&positives) <= 1 && spread(&negatives) <= 1, "{:?} {:?}", positives, negatives);
+            assert_eq!(positives.iter().sum::<usize>(), 9);
+        }
+        // 3 folds split 9 positives and 21 negatives exactly
+        let cv = CrossValidation { scheme: Scheme::Stratified(3), repeats: 1, seed: 1 };
+        let assignment = cv.assign(&y, &mut Rng::new(1));
+        assert_eq!(counts(&assignment, 3, |i| y[i] == 1.0), vec![3, 3, 3]);
+    }
+
+    #[test]
+    fn k_fold_sizes_and_seeds() {
+        let y = vec![0.0; 23];
+        let cv = CrossValidation { scheme: Scheme::KFold(5), repeats: 1, seed: 3 };
+        let assignment = cv.assign(&y, &mut Rng::new(3));
+        let sizes = counts(&assignment, 5, |_| true);
+        assert!(spread(&sizes) <= 1 && sizes.iter().all(|&s| s >= 4), "{:?}", sizes);
+        assert_eq!(assignment, cv.assign(&y, &mut Rng::new(3)));
+        assert_ne!(assignment, cv.assign(&y, &mut Rng::new(4)));
+
+        let loo = CrossValidation { scheme: Scheme::LeaveOneOut, repeats: 1, seed: 3 };
+        assert_eq!(loo.assign(&y[..4], &mut Rng::new(3)), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn run_scores_every_complete_case_once_per_repeat() {
+        let mut individuals = individuals();
+        individuals[0].age = None;
+        let mut model = MeanModel { formula: Formula::parse("salary ~ age").unwrap(), mean: None };
+        let cv = CrossValidation { scheme: Scheme::KFold(4), repeats: 2, seed: 42 };
+        let result = cv.run(&mut model, &individuals).unwrap();
+        assert_eq!(result.n, 29);
+        assert_eq!(result.folds.len(), 8);
+        assert_eq!(result.predictions.len(), 58);
+        assert!(result.folds.iter().all(|f| f.train + f.test == 29));
+        assert_eq!(result.metrics, vec![Metric::Rmse, Metric::Mae, Metric::RSquared]);
+        // Training-fold means do no better than the mean of the held-out responses
+        assert!(result.pooled[2] <= 0.0);
+        assert_eq!(cv.run(&mut model, &individuals).unwrap().predictions, result.predictions);
+    }
+
+    #[test]
+    fn run_rejects_invalid_schemes() {
+        let individuals = individuals();
+        let mut model = MeanModel { formula: Formula::parse("salary ~ age").unwrap(), mean: None };
+        let run = |scheme, repeats, model: &mut MeanModel| CrossValidation { scheme, repeats, seed: 1 }.run(model, &individuals);
+        assert!(run(Scheme::KFold(1), 1, &mut model).is_err());
+        assert!(run(Scheme::KFold(31), 1, &mut model).is_err());
+        assert!(run(Scheme::KFold(5), 0, &mut model).is_err());
+        assert!(run(Scheme::LeaveOneOut, 2, &mut model).is_err());
+        assert!(run(Scheme::Stratified(5), 1, &mut model).is_err());
+        assert!(run(Scheme::LeaveOneOut, 1, &mut model).is_ok());
+    }
+
+    #[test]
+    fn metrics_on_known_predictions() {
+        let (predicted, observed) = ([1.0, 2.0, 5.0], [1.0, 3.0, 3.0]);
+        assert!((Metric::Rmse.compute(&predicted, &observed) - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert!((Metric::Mae.compute(&predicted, &observed) - 1.0).abs() < 1e-12);
+        // SST about 7/3 is 8/3
+        assert!((Metric::RSquared.compute(&predicted, &observed) - (1.0 - 5.0 / (8.0 / 3.0))).abs() < 1e-12);
+        assert!(Metric::RSquared.compute(&[1.0], &[2.0]).is_nan());
+        assert_eq!(Metric::Accuracy.compute(&[0.7, 0.2, 0.5], &[1.0, 1.0, 0.0]), 1.0 / 3.0);
+        assert_eq!(Metric::Auc.compute(&[0.9, 0.1], &[1.0, 0.0]), 1.0);
+    }
+}
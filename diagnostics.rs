@@ -0,0 +1,242 @@
+//! Residual and influence diagnostics of a least squares fit, with tests of
+//! independence, constant variance and normality of the errors.
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::distributions::{ChiSquared, Continuous};
+use crate::formula::Formula;
+use crate::individual::Individual;
+use crate::linalg::Qr;
+use crate::ols::Design;
+use crate::tests::TestResult;
+
+/// Influential observations listed by [`Diagnostics::render`].
+pub const INFLUENTIAL_SHOWN: usize = 5;
+
+/// Per-observation quantities of a fit.
+#[derive(Debug, Clone, Copy)]
+pub struct Observation {
+    /// `Individual::id` of the observation.
+    pub id: usize,
+    pub fitted: f64,
+    pub residual: f64,
+    /// Residual over its estimated standard deviation, s * sqrt(1 - h).
+    pub standardized: f64,
+    /// Externally studentized residual: as `standardized`, but with s estimated
+    /// without this observation, so it follows a t distribution on n - p - 1 df.
+    pub studentized: f64,
+    /// Diagonal of the hat matrix.
+    pub leverage: f64,
+    pub cooks_distance: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    pub formula: Formula,
+    /// Coefficients, including the intercept.
+    pub p: usize,
+    /// In data order.
+    pub observations: Vec<Observation>,
+    /// Near 2 without first-order autocorrelation of residuals in data order.
+    pub durbin_watson: f64,
+    /// Koenker's studentized Breusch-Pagan test of constant variance.
+    pub breusch_pagan: TestResult,
+    /// Jarque-Bera test of normal residuals.
+    pub jarque_bera: TestResult,
+}
+
+impl Diagnostics {
+    /// Fits `formula` by least squares on the complete cases and diagnoses the fit.
+    pub fn compute(individuals: &[Individual], formula: &Formula) -> Result<Diagnostics, Box<dyn Error>> {
+        let Design { ids, x, y } = Design::new(individuals, formula)?;
+        let (n, p) = (x.rows, x.cols);
+        let qr = Qr::new(&x);
+        if !qr.is_full_rank() {
+            return Err("design matrix is rank deficient (collinear or constant terms)".into());
+        }
+        let fitted = x.mul_vec(&qr.solve(&y));
+        let residuals: Vec<f64> = y.iter().zip(&fitted).map(|(a, b)| a - b).collect();
+        let nf = n as f64;
+        let df_residual = (n - p) as f64;
+        let sse: f64 = residuals.iter().map(|e| e * e).sum();
+        if sse == 0.0 {
+            return Err("the model fits exactly, so residuals carry no information".into());
+        }
+        let sigma2 = sse / df_residual;
+
+        let covariance = qr.xtx_inverse();
+        let observations = (0..n)
+            .map(|i| {
+                let row = x.row(i);
+                let leverage: f64 = (0..p)
+                    .map(|j| (0..p).map(|k| row[j] * covariance[(j, k)] * row[k]).sum::<f64>())
+                    .sum();
+                let residual = residuals[i];
+                let standardized = residual / (sigma2 * (1.0 - leverage)).sqrt();
+                let studentized = standardized * ((df_residual - 1.0) / (df_residual - standardized.powi(2))).sqrt();
+                Observation {
+                    id: ids[i],
+                    fitted: fitted[i],
+                    residual,
+                    standardized,
+                    studentized,
+                    leverage,
+                    cooks_distance: standardized.powi(2) * leverage / (p as f64 * (1.0 - leverage)),
+                }
+            })
+            .collect();
+
+        let durbin_watson = residuals.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum::<f64>() / sse;
+
+        // Regress the squared residuals on the same design: n R^2 is chi-square on p - 1 df
+        let squared: Vec<f64> = residuals.iter().map(|e| e * e).collect();
+        let mean_squared = sse / nf;
+        let auxiliary = x.mul_vec(&qr.solve(&squared));
+        let explained: f64 = auxiliary.iter().map(|v| (v - mean_squared).powi(2)).sum();
+        let total: f64 = squared.iter().map(|v| (v - mean_squared).powi(2)).sum();
+        let bp = if total > 0.0 { nf * explained / total } else { 0.0 };
+        let bp_df = (p - 1) as f64;
+        let breusch_pagan = TestResult {
+            test: "Breusch-Pagan",
+            statistic: bp,
+            df: Some(bp_df),
+            p_value: if bp_df > 0.0 { ChiSquared::new(bp_df).sf(bp) } else { 1.0 },
+            effect_size: None,
+            n,
+        };
+
+        // Residuals of a model with an intercept have mean zero
+        let moment = |k: i32| residuals.iter().map(|e| e.powi(k)).sum::<f64>() / nf;
+        let (m2, m3, m4) = (moment(2), moment(3), moment(4));
+        let skewness = m3 / m2.powf(1.5);
+        let kurtosis = m4 / (m2 * m2);
+        let jb = nf / 6.0 * (skewness.powi(2) + (kurtosis - 3.0).powi(2) / 4.0);
+        let jarque_bera = TestResult {
+            test: "Jarque-Bera",
+            statistic: jb,
+            df: Some(2.0),
+            p_value: ChiSquared::new(2.0).sf(jb),
+            effect_size: None,
+            n,
+        };
+
+        Ok(Diagnostics {
+            formula: formula.clone(),
+            p,
+            observations,
+            durbin_watson,
+            breusch_pagan,
+            jarque_bera,
+        })
+    }
+
+    /// The `k` observations with the largest Cook's distance, largest first.
+    pub fn most_influential(&self, k: usize) -> Vec<Observation> {
+        let mut sorted = self.observations.clone();
+        sorted.sort_by(|a, b| b.cooks_distance.total_cmp(&a.cooks_distance));
+        sorted.truncate(k);
+        sorted
+    }
+
+    pub fn render(&self) -> String {
+        let n = self.observations.len();
+        let nf = n as f64;
+        let count = |flagged: fn(&Observation, f64, f64) -> bool| {
+            self.observations
+                .iter()
+                .filter(|o| flagged(o, nf, self.p as f64))
+                .count()
+        };
+        let mut out = String::new();
+        writeln!(out, "Regression diagnostics: {}", self.formula).unwrap();
+        writeln!(out, "Durbin-Watson: {:.4} (about 2 when residuals in data order are uncorrelated)", self.durbin_watson)
+            .unwrap();
+        writeln!(out, "{}", self.breusch_pagan).unwrap();
+        writeln!(out, "{}", self.jarque_bera).unwrap();
+        writeln!(
+            out,
+            "Observations: {}, |studentized residual| > 3: {}, leverage > 2p/n: {}, Cook's distance > 4/n: {}",
+            n,
+            count(|o, _, _| o.studentized.abs() > 3.0),
+            count(|o, n, p| o.leverage > 2.0 * p / n),
+            count(|o, n, _| o.cooks_distance > 4.0 / n)
+        )
+        .unwrap();
+        writeln!(out, "Most influential observations (by Cook's distance):").unwrap();
+        writeln!(
+            out,
+            "{:>6} {:>14} {:>14} {:>10} {:>10} {:>9} {:>10}",
+            "Id", "Fitted", "Residual", "Std. res.", "Stud. res.", "Leverage", "Cook's D"
+        )
+        .unwrap();
+        for o in self.most_influential(INFLUENTIAL_SHOWN) {
+            writeln!(
+                out,
+                "{:>6} {:>14.4} {:>14.4} {:>10.3} {:>10.3} {:>9.4} {:>10.4}",
+                o.id, o.fitted, o.residual, o.standardized, o.studentized, o.leverage, o.cooks_distance
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// Column names matching [`Diagnostics::write_rows`].
+    pub fn csv_header() -> [&'static str; 7] {
+        ["id", "fitted", "residual", "standardized", "studentized", "leverage", "cooks_distance"]
+    }
+
+    /// One row per observation, in data order.
+    pub fn write_rows<W: std::io::Write>(&self, wtr: &mut csv::Writer<W>) -> Result<(), Box<dyn Error>> {
+        for o in &self.observations {
+            wtr.write_record([
+                o.id.to_string(),
+                o.fitted.to_string(),
+                o.residual.to_string(),
+                o.standardized.to_string(),
+                o.studentized.to_string(),
+                o.leverage.to_string(),
+                o.cooks_distance.to_string(),
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{close, DIST, SPEED};
+
+    fn diagnostics() -> Diagnostics {
+        let individuals: Vec<Individual> = SPEED
+            .iter()
+            .zip(DIST)
+            .enumerate()
+            .map(|(id, (&speed, dist))| Individual { id, age: Some(speed), salary: Some(dist), ..Default::default() })
+            .collect();
+        Diagnostics::compute(&individuals, &Formula::parse("salary ~ age").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn influence_measures_match_r() {
+        // hatvalues, rstudent and cooks.distance of lm(dist ~ speed, cars)
+        let d = diagnostics();
+        close(d.observations[0].leverage, 0.1148613, 1e-7);
+        let worst = d.most_influential(1)[0];
+        assert_eq!(worst.id, 48);
+        close(worst.cooks_distance, 0.3403959, 1e-7);
+        close(worst.studentized, 3.184993, 1e-6);
+        close(worst.fitted, -17.579095 + 3.932409 * 24.0, 1e-5);
+    }
+
+    #[test]
+    fn assumption_tests_match_r() {
+        // lmtest::dwtest and lmtest::bptest (studentized) of lm(dist ~ speed, cars)
+        let d = diagnostics();
+        close(d.durbin_watson, 1.676225, 1e-6);
+        close(d.breusch_pagan.statistic, 3.214880, 1e-6);
+        close(d.breusch_pagan.p_value, 0.07297, 1e-5);
+        close(d.jarque_bera.statistic, 8.188784, 1e-6);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+/// Lowercases, trims and collapses inner whitespace so "  medium " matches "Medium".
+pub fn normalize_level(raw: &str) -> String {
+    raw.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Maps ordered levels (and any synonyms) to numeric codes, ignoring case and spacing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrdinalEncoder {
+    levels: Vec<(String, f64)>,
+    codes: HashMap<String, f64>,
+}
+
+impl OrdinalEncoder {
+    pub fn new(levels: &[(String, f64)]) -> Self {
+        let codes = levels.iter().map(|(level, code)| (normalize_level(level), *code)).collect();
+        OrdinalEncoder { levels: levels.to_vec(), codes }
+    }
+
+    /// Accepts `alias` as another spelling of the existing `level`.
+    pub fn with_alias(mut self, alias: &str, level: &str) -> Result<Self, Box<dyn Error>> {
+        let code = *self
+            .codes
+            .get(&normalize_level(level))
+            .ok_or_else(|| format!("alias '{}' refers to unknown level '{}'", alias, level))?;
+        self.codes.insert(normalize_level(alias), code);
+        Ok(self)
+    }
+
+    pub fn encode(&self, raw: &str) -> Option<f64> {
+        self.codes.get(&normalize_level(raw)).copied()
+    }
+
+    /// Canonical level for a code, used when labelling groups.
+    pub fn level_name(&self, code: f64) -> Option<&str> {
+        self.levels.iter().find(|(_, c)| *c == code).map(|(level, _)| level.as_str())
+    }
+}
+
+/// One 0/1 indicator per level, omitting the reference level (dummy coding)
+/// when one is chosen.
+#[derive(Debug, Clone)]
+pub struct OneHotEncoder {
+    levels: Vec<String>,
+}
+
+impl OneHotEncoder {
+    /// Learns the sorted set of levels; `reference`, if given, must be one of them.
+    pub fn fit(values: &[Option<String>], reference: Option<&str>) -> Result<Self, Box<dyn Error>> {
+        let mut levels: Vec<String> = values.iter().flatten().cloned().collect();
+        levels.sort();
+        levels.dedup();
+        if let Some(reference) = reference {
+            let before = levels.len();
+            levels.retain(|level| level != reference);
+            if levels.len() == before {
+                return Err(format!("reference level '{}' does not occur in the data", reference).into());
+            }
+        }
+        Ok(OneHotEncoder { levels })
+    }
+
+    /// Output column names, `<prefix>=<level>`.
+    pub fn column_names(&self, prefix: &str) -> Vec<String> {
+        self.levels.iter().map(|level| format!("{}={}", prefix, level)).collect()
+    }
+
+    /// One indicator column per encoded level.
+    pub fn transform(&self, values: &[Option<String>]) -> Vec<Vec<Option<f64>>> {
+        self.levels
+            .iter()
+            .map(|level| {
+                values
+                    .iter()
+                    .map(|v| v.as_ref().map(|v| if v == level { 1.0 } else { 0.0 }))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Replaces each category by the smoothed mean of the target within it:
+/// `(n * category_mean + m * global_mean) / (n + m)` for smoothing weight `m`.
+/// Fit on the same rows it encodes, the code leaks target information, so
+/// treat associations with that target as optimistic.
+#[derive(Debug, Clone)]
+pub struct TargetEncoder {
+    means: HashMap<String, f64>,
+    global_mean: f64,
+}
+
+impl TargetEncoder {
+    pub fn fit(values: &[Option<String>], target: &[Option<f64>], smoothing: f64) -> Result<Self, Box<dyn Error>> {
+        let mut sums: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+        let mut total = 0.0;
+        let mut count = 0.0;
+        for (value, y) in values.iter().zip(target) {
+            if let (Some(v), Some(y)) = (value, y) {
+                let entry = sums.entry(v.as_str()).or_insert((0.0, 0.0));
+                entry.0 += y;
+                entry.1 += 1.0;
+                total += y;
+                count += 1.0;
+            }
+        }
+        if count == 0.0 {
+            return Err("target encoding needs at least one row with both category and target".into());
+        }
+        let global_mean = total / count;
+        let means = sums
+            .into_iter()
+            .map(|(level, (sum, n))| (level.to_string(), (sum + smoothing * global_mean) / (n + smoothing)))
+            .collect();
+        Ok(TargetEncoder { means, global_mean })
+    }
+
+    /// Unseen categories fall back to the global target mean.
+    pub fn transform(&self, values: &[Option<String>]) -> Vec<Option<f64>> {
+        values
+            .iter()
+            .map(|v| v.as_ref().map(|v| self.means.get(v).copied().unwrap_or(self.global_mean)))
+            .collect()
+    }
+}
+
+/// Replaces each category by the share of observed rows that have it.
+#[derive(Debug, Clone)]
+pub struct FrequencyEncoder {
+    frequencies: HashMap<String, f64>,
+}
+
+impl FrequencyEncoder {
+    pub fn fit(values: &[Option<String>]) -> Self {
+        let mut counts: HashMap<String, f64> = HashMap::new();
+        for v in values.iter().flatten() {
+            *counts.entry(v.clone()).or_insert(0.0) += 1.0;
+        }
+        let total: f64 = counts.values().sum();
+        let frequencies = counts.into_iter().map(|(level, n)| (level, n / total)).collect();
+        FrequencyEncoder { frequencies }
+    }
+
+    /// Unseen categories have frequency 0.
+    pub fn transform(&self, values: &[Option<String>]) -> Vec<Option<f64>> {
+        values
+            .iter()
+            .map(|v| v.as_ref().map(|v| self.frequencies.get(v).copied().unwrap_or(0.0)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    fn categories(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values.iter().map(|v| v.map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn one_hot_drops_the_reference_level() {
+        let train = categories(&[Some("Male"), Some("Female"), None, Some("Other"), Some("Male")]);
+        let encoder = OneHotEncoder::fit(&train, Some("Female")).unwrap();
+        assert_eq!(encoder.column_names("Gender"), ["Gender=Male", "Gender=Other"]);
+
+        // An unseen level, like the reference, is all zeros; a missing cell stays missing.
+        let columns = encoder.transform(&categories(&[Some("Female"), Some("Male"), Some("Unknown"), None]));
+        assert_eq!(columns[0], [Some(0.0), Some(1.0), Some(0.0), None]);
+        assert_eq!(columns[1], [Some(0.0), Some(0.0), Some(0.0), None]);
+
+        let full = OneHotEncoder::fit(&train, None).unwrap();
+        assert_eq!(full.column_names("G"), ["G=Female", "G=Male", "G=Other"]);
+        assert!(OneHotEncoder::fit(&train, Some("Nonbinary")).is_err());
+    }
+
+    #[test]
+    fn target_encoding_shrinks_towards_the_global_mean() {
+        let train = categories(&[Some("a"), Some("a"), Some("b"), Some("b"), None]);
+        let target = [Some(10.0), Some(20.0), Some(60.0), None, Some(1000.0)];
+        // Rows with both values: a = {10, 20}, b = {60}; global mean 30.
+        let encoder = TargetEncoder::fit(&train, &target, 2.0).unwrap();
+        let encoded = encoder.transform(&categories(&[Some("a"), Some("b"), Some("c"), None]));
+        close(encoded[0].unwrap(), (30.0 + 2.0 * 30.0) / 4.0, 1e-12);
+        close(encoded[1].unwrap(), (60.0 + 2.0 * 30.0) / 3.0, 1e-12);
+        assert_eq!(encoded[2], Some(30.0));
+        assert_eq!(encoded[3], None);
+
+        let unsmoothed = TargetEncoder::fit(&train, &target, 0.0).unwrap();
+        assert_eq!(unsmoothed.transform(&train)[..3], [Some(15.0), Some(15.0), Some(60.0)]);
+        assert!(TargetEncoder::fit(&train, &[None; 5], 2.0).is_err());
+    }
+
+    #[test]
+    fn frequency_encoding_uses_observed_shares() {
+        let train = categories(&[Some("a"), Some("b"), Some("a"), None, Some("a")]);
+        let encoder = FrequencyEncoder::fit(&train);
+        let encoded = encoder.transform(&categories(&[Some("a"), Some("b"), Some("c"), None]));
+        assert_eq!(encoded, [Some(0.75), Some(0.25), Some(0.0), None]);
+    }
+
+    #[test]
+    fn ordinal_levels_ignore_case_and_spacing() {
+        let encoder = OrdinalEncoder::new(&[("High School".to_string(), 1.0), ("PhD".to_string(), 4.0)])
+            .with_alias("Doctorate", "phd")
+            .unwrap();
+        assert_eq!(encoder.encode("  high   school "), Some(1.0));
+        assert_eq!(encoder.encode("DOCTORATE"), Some(4.0));
+        assert_eq!(encoder.encode("Master's"), None);
+        assert!(encoder.clone().with_alias("MSc", "Master's").is_err());
+    }
+}
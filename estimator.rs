@@ -0,0 +1,384 @@
+//! A common interface to the regression models: each is fitted on a
+//! `FeatureMatrix`, so any of them can be cross-validated, compared or
+//! reported the same way.
+
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::crossval::{FitPredict, Metric, Task};
+use crate::features::{FeatureMatrix, FeatureMatrixBuilder, Scaling};
+use crate::formula::Formula;
+use crate::individual::{Field, Individual};
+use crate::linalg::Matrix;
+use crate::logistic::{self, render_terms, sigmoid, LogisticFit, LogisticTerm};
+use crate::ols::{render_coefficients, Coefficient, TermEstimate};
+use crate::robust::{fit_design, RegressionMethod, RobustFit};
+
+pub trait Estimator {
+    fn name(&self) -> String;
+
+    fn task(&self) -> Task;
+
+    /// Fits on every row of `data`, replacing any earlier fit.
+    fn fit(&mut self, data: &FeatureMatrix) -> Result<(), Box<dyn Error>>;
+
+    /// One prediction per row of `data`, which must have the columns the model was fitted on.
+    fn predict(&self, data: &FeatureMatrix) -> Result<Vec<f64>, Box<dyn Error>>;
+
+    /// R-squared for regression, ROC AUC for classification.
+    fn score(&self, data: &FeatureMatrix) -> Result<f64, Box<dyn Error>> {
+        let predicted = self.predict(data)?;
+        let metric = match self.task() {
+            Task::Regression => Metric::RSquared,
+            Task::Classification => Metric::Auc,
+        };
+        Ok(metric.compute(&predicted, &data.y))
+    }
+
+    /// Coefficient table of the current fit.
+    fn report(&self) -> String;
+}
+
+/// What a fit learned, shared by the linear-predictor models.
+#[derive(Debug, Clone)]
+struct Fitted {
+    target: Field,
+    /// "(Intercept)", then the feature names.
+    names: Vec<String>,
+    n: usize,
+    coefficients: Vec<f64>,
+    std_errors: Vec<f64>,
+}
+
+impl Fitted {
+    fn new(data: &FeatureMatrix, coefficients: Vec<f64>, covariance: &Matrix) -> Self {
+        let mut names = vec!["(Intercept)".to_string()];
+        names.extend(data.names.iter().cloned());
+        Fitted {
+            target: data.target(),
+            names,
+            n: data.y.len(),
+            std_errors: (0..coefficients.len()).map(|j| covariance[(j, j)].sqrt()).collect(),
+            coefficients,
+        }
+    }
+}
+
+/// Intercept plus coefficients times the features of each row.
+fn linear_predictor(fitted: Option<&Fitted>, data: &FeatureMatrix) -> Result<Vec<f64>, Box<dyn Error>> {
+    let fitted = fitted.ok_or("the model has not been fitted")?;
+    if data.names != fitted.names[1..] {
+        return Err("prediction data must have the features the model was fitted on".into());
+    }
+    Ok(data.design().mul_vec(&fitted.coefficients))
+}
+
+/// A linear regression fitted by least squares or one of the robust estimators.
+#[derive(Debug, Clone)]
+pub struct LinearModel {
+    pub method: RegressionMethod,
+    /// Drives the random subsets of RANSAC.
+    pub seed: u64,
+    fitted: Option<(Fitted, RobustFit)>,
+}
+
+impl LinearModel {
+    pub fn new(method: RegressionMethod, seed: u64) -> Self {
+        LinearModel { method, seed, fitted: None }
+    }
+}
+
+impl Estimator for LinearModel {
+    fn name(&self) -> String {
+        self.method.to_string()
+    }
+
+    fn task(&self) -> Task {
+        Task::Regression
+    }
+
+    fn fit(&mut self, data: &FeatureMatrix) -> Result<(), Box<dyn Error>> {
+        let fit = fit_design(self.method, &data.design(), &data.y, self.seed)?;
+        self.fitted = Some((Fitted::new(data, fit.coefficients.clone(), &fit.covariance), fit));
+        Ok(())
+    }
+
+    fn predict(&self, data: &FeatureMatrix) -> Result<Vec<f64>, Box<dyn Error>> {
+        linear_predictor(self.fitted.as_ref().map(|(f, _)| f), data)
+    }
+
+    fn report(&self) -> String {
+        let mut out = String::new();
+        let Some((fitted, fit)) = &self.fitted else {
+            writeln!(out, "{}: not fitted", self.name()).unwrap();
+            return out;
+        };
+        writeln!(out, "{} fit of {} ({} observations)", self.name(), fitted.target.key(), fitted.n).unwrap();
+        let mut terms: Vec<TermEstimate> = fitted
+            .names
+            .iter()
+            .zip(&fitted.coefficients)
+            .zip(&fitted.std_errors)
+            .map(|((name, &estimate), &std_error)| TermEstimate {
+                name: name.clone(),
+                coefficient: Coefficient::new(estimate, std_error, fit.df_residual),
+                vif: None,
+            })
+            .collect();
+        if let (Some(interval), [_, slope]) = (fit.slope_interval, terms.as_mut_slice()) {
+            slope.coefficient.ci = interval;
+        }
+        write!(out, "{}", render_coefficients(&terms)).unwrap();
+        writeln!(out, "Residual scale: {:.4} on {} degrees of freedom", fit.scale, fit.df_residual).unwrap();
+        out
+    }
+}
+
+/// A logistic regression with an optional L2 penalty.
+#[derive(Debug, Clone)]
+pub struct LogisticModel {
+    pub penalty: f64,
+    fitted: Option<(Fitted, LogisticFit)>,
+}
+
+impl LogisticModel {
+    pub fn new(penalty: f64) -> Self {
+        LogisticModel { penalty, fitted: None }
+    }
+}
+
+impl Estimator for LogisticModel {
+    fn name(&self) -> String {
+        "Logistic regression".to_string()
+    }
+
+    fn task(&self) -> Task {
+        Task::Classification
+    }
+
+    fn fit(&mut self, data: &FeatureMatrix) -> Result<(), Box<dyn Error>> {
+        let fit = logistic::fit_design(&data.design(), &data.y, data.target(), self.penalty)?;
+        self.fitted = Some((Fitted::new(data, fit.coefficients.clone(), &fit.covariance), fit));
+        Ok(())
+    }
+
+    fn predict(&self, data: &FeatureMatrix) -> Result<Vec<f64>, Box<dyn Error>> {
+        let eta = linear_predictor(self.fitted.as_ref().map(|(f, _)| f), data)?;
+        Ok(eta.into_iter().map(sigmoid).collect())
+    }
+
+    fn report(&self) -> String {
+        let mut out = String::new();
+        let Some((fitted, fit)) = &self.fitted else {
+            writeln!(out, "{}: not fitted", self.name()).unwrap();
+            return out;
+        };
+        writeln!(
+            out,
+            "{} fit of {} ({} observations, L2 penalty: {})",
+            self.name(),
+            fitted.target.key(),
+            fitted.n,
+            self.penalty
+        )
+        .unwrap();
+        let terms: Vec<LogisticTerm> = fitted
+            .names
+            .iter()
+            .zip(&fitted.coefficients)
+            .zip(&fitted.std_errors)
+            .map(|((name, &estimate), &std_error)| LogisticTerm::new(name.clone(), estimate, std_error, None))
+            .collect();
+        write!(out, "{}", render_terms(&terms)).unwrap();
+        write!(out, "Log-likelihood: {:.4}, IRLS iterations: {}", fit.log_likelihood, fit.iterations).unwrap();
+        if !fit.converged {
+            write!(out, " (did not converge; the classes may be separable)").unwrap();
+        }
+        writeln!(out).unwrap();
+        out
+    }
+}
+
+/// An estimator together with the features it is fitted on, so that it can be
+/// fitted on individuals and predict them, as cross-validation does.
+pub struct Pipeline {
+    pub features: FeatureMatrixBuilder,
+    pub estimator: Box<dyn Estimator>,
+    /// Response and numeric terms of `features`.
+    formula: Formula,
+    /// Matrix of the last fit, holding the encoders and scaling learned from it.
+    trained: Option<FeatureMatrix>,
+}
+
+impl Pipeline {
+    pub fn new(features: FeatureMatrixBuilder, estimator: Box<dyn Estimator>) -> Self {
+        let formula = Formula { response: features.target, terms: features.terms.clone() };
+        Pipeline { features, estimator, formula, trained: None }
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.estimator.name())?;
+        if !self.features.categories.is_empty() {
+            let categories: Vec<&str> = self.features.categories.iter().map(|(c, _)| c.key()).collect();
+            write!(f, " with encoded {}", categories.join(", "))?;
+        }
+        if self.features.scaling != Scaling::None {
+            write!(f, ", {}", self.features.scaling)?;
+        }
+        Ok(())
+    }
+}
+
+impl FitPredict for Pipeline {
+    fn formula(&self) -> &Formula {
+        &self.formula
+    }
+
+    fn task(&self) -> Task {
+        self.estimator.task()
+    }
+
+    /// Builds the features from `train` and fits the estimator on them.
+    fn fit(&mut self, train: &[Individual]) -> Result<(), Box<dyn Error>> {
+        let data = self.features.build(train)?;
+        self.estimator.fit(&data)?;
+        self.trained = Some(data);
+        Ok(())
+    }
+
+    /// `None` also when `individual` lacks a category or the target.
+    fn predict(&self, individual: &Individual) -> Option<f64> {
+        let data = self.trained.as_ref()?.transform(std::slice::from_ref(individual));
+        self.estimator.predict(&data).ok()?.first().copied()
+    }
+}
+
+/// The estimators selectable with `--model`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Learner {
+    Linear(RegressionMethod),
+    Logistic,
+}
+
+impl Learner {
+    /// Accepts `logistic` or any `RegressionMethod` name.
+    pub fn parse(name: &str) -> Result<Learner, Box<dyn Error>> {
+        if name.eq_ignore_ascii_case("logistic") {
+            return Ok(Learner::Logistic);
+        }
+        RegressionMethod::parse(name)
+            .map(Learner::Linear)
+            .map_err(|_| format!("unknown model '{}' (expected ols, theil-sen, lad, huber, ransac or logistic)", name).into())
+    }
+
+    /// Response of the default formula: salary for linear models, career change for logistic.
+    pub fn default_response(self) -> Field {
+        match self {
+            Learner::Linear(_) => Field::Salary,
+            Learner::Logistic => Field::LikelyToChangeOccupation,
+        }
+    }
+
+    pub fn build(self, seed: u64, penalty: f64) -> Box<dyn Estimator> {
+        match self {
+            Learner::Linear(method) => Box::new(LinearModel::new(method, seed)),
+            Learner::Logistic => Box::new(LogisticModel::new(penalty)),
+        }
+    }
+}
+
+impl fmt::Display for Learner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Learner::Linear(method) => write!(f, "{}", method),
+            Learner::Logistic => write!(f, "Logistic regression"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossval::{CrossValidation, Scheme};
+    use crate::individual::Category;
+    use crate::ols::MultipleRegression;
+    use crate::schema::Encoding;
+    use crate::testing::{close, STACKLOSS};
+
+    /// Stackloss as individuals (see `ols::tests`), with an alternating gender.
+    fn stackloss() -> Vec<Individual> {
+        STACKLOSS
+            .iter()
+            .enumerate()
+            .map(|(id, r)| Individual {
+                id,
+                age: Some(r[0]),
+                years_of_experience: Some(r[1]),
+                job_satisfaction: Some(r[2]),
+                salary: Some(r[3]),
+                gender: Some(if id % 2 == 0 { "Female" } else { "Male" }.to_string()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn formula() -> Formula {
+        Formula::parse("salary ~ age + years_of_experience + job_satisfaction").unwrap()
+    }
+
+    #[test]
+    fn least_squares_model_matches_multiple_regression() {
+        let individuals = stackloss();
+        let data = FeatureMatrixBuilder::new(&formula()).build(&individuals).unwrap();
+        let mut model = LinearModel::new(RegressionMethod::Ols, 1);
+        model.fit(&data).unwrap();
+        let reference = MultipleRegression::fit(&individuals, &formula()).unwrap();
+
+        let (fitted, _) = model.fitted.as_ref().unwrap();
+        assert_eq!(fitted.names, reference.terms.iter().map(|t| t.name.clone()).collect::<Vec<_>>());
+        for ((estimate, std_error), term) in fitted.coefficients.iter().zip(&fitted.std_errors).zip(&reference.terms) {
+            close(*estimate, term.coefficient.estimate, 1e-9);
+            close(*std_error, term.coefficient.std_error, 1e-9);
+        }
+        close(model.score(&data).unwrap(), reference.r_squared, 1e-9);
+    }
+
+    #[test]
+    fn prediction_needs_the_fitted_features() {
+        let individuals = stackloss();
+        let data = FeatureMatrixBuilder::new(&formula()).build(&individuals).unwrap();
+        let mut model = LinearModel::new(RegressionMethod::Ols, 1);
+        assert_eq!(model.predict(&data).unwrap_err().to_string(), "the model has not been fitted");
+
+        model.fit(&data).unwrap();
+        assert_eq!(model.predict(&data).unwrap().len(), 21);
+        let other = Formula::parse("salary ~ age + job_satisfaction + years_of_experience").unwrap();
+        let reordered = FeatureMatrixBuilder::new(&other).build(&individuals).unwrap();
+        assert_eq!(
+            model.predict(&reordered).unwrap_err().to_string(),
+            "prediction data must have the features the model was fitted on"
+        );
+    }
+
+    #[test]
+    fn pipelines_cross_validate_like_refitting_by_hand() {
+        let individuals = stackloss();
+        let features = FeatureMatrixBuilder::new(&formula()).category(Category::Gender, Encoding::OneHot { reference: None });
+        let mut pipeline = Pipeline::new(features.clone(), Box::new(LinearModel::new(RegressionMethod::Ols, 1)));
+        assert_eq!(pipeline.to_string(), "Least squares with encoded gender");
+
+        let cv = CrossValidation { scheme: Scheme::LeaveOneOut, repeats: 1, seed: 1 };
+        let result = cv.run(&mut pipeline, &individuals).unwrap();
+        assert_eq!((result.n, result.folds.len(), result.predictions.len()), (21, 21, 21));
+
+        // The first held-out prediction, refitted on the other 20 individuals.
+        let data = features.build(&individuals[1..]).unwrap();
+        let mut model = LinearModel::new(RegressionMethod::Ols, 1);
+        model.fit(&data).unwrap();
+        let expected = model.predict(&data.transform(&individuals[..1])).unwrap()[0];
+        let prediction = result.predictions.iter().find(|(id, _)| *id == 0).map(|&(_, p)| p).unwrap();
+        close(prediction, expected, 1e-9);
+    }
+}
@@ -0,0 +1,488 @@
+//! Evaluation of probabilistic binary classifiers: ROC and precision-recall
+//! curves, proper scoring rules, calibration and threshold choice.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::distributions::{Continuous, Normal};
+use crate::individual::{Field, Individual};
+use crate::ols::CONFIDENCE_LEVEL;
+
+/// Probability at or above which an observation is classified positive.
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+/// Equal-width probability bins of the calibration table.
+pub const CALIBRATION_BINS: usize = 10;
+/// Probabilities are clipped this far from 0 and 1 in the log loss.
+const LOG_LOSS_EPSILON: f64 = 1e-15;
+
+/// Counts of a classification at a probability threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfusionMatrix {
+    pub threshold: f64,
+    pub true_positive: usize,
+    pub false_positive: usize,
+    pub true_negative: usize,
+    pub false_negative: usize,
+}
+
+impl ConfusionMatrix {
+    /// Classifies `probabilities` at `threshold` against the 0/1 outcomes `y`.
+    pub fn new(probabilities: &[f64], y: &[f64], threshold: f64) -> Self {
+        let mut matrix = ConfusionMatrix {
+            threshold,
+            true_positive: 0,
+            false_positive: 0,
+            true_negative: 0,
+            false_negative: 0,
+        };
+        for (&p, &v) in probabilities.iter().zip(y) {
+            match (p >= threshold, v == 1.0) {
+                (true, true) => matrix.true_positive += 1,
+                (true, false) => matrix.false_positive += 1,
+                (false, false) => matrix.true_negative += 1,
+                (false, true) => matrix.false_negative += 1,
+            }
+        }
+        matrix
+    }
+
+    pub fn total(&self) -> usize {
+        self.true_positive + self.false_positive + self.true_negative + self.false_negative
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        (self.true_positive + self.true_negative) as f64 / self.total() as f64
+    }
+
+    /// True positive rate (recall).
+    pub fn sensitivity(&self) -> f64 {
+        ratio(self.true_positive, self.true_positive + self.false_negative)
+    }
+
+    /// True negative rate.
+    pub fn specificity(&self) -> f64 {
+        ratio(self.true_negative, self.true_negative + self.false_positive)
+    }
+
+    /// Positive predictive value.
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_positive, self.true_positive + self.false_positive)
+    }
+
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.sensitivity());
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "Confusion matrix at threshold {:.4}:", self.threshold).unwrap();
+        writeln!(out, "{:<14} {:>12} {:>12}", "", "Predicted 1", "Predicted 0").unwrap();
+        writeln!(out, "{:<14} {:>12} {:>12}", "Observed 1", self.true_positive, self.false_negative).unwrap();
+        writeln!(out, "{:<14} {:>12} {:>12}", "Observed 0", self.false_positive, self.true_negative).unwrap();
+        writeln!(
+            out,
+            "Accuracy: {:.4}, Sensitivity: {:.4}, Specificity: {:.4}, Precision: {:.4}, F1: {:.4}",
+            self.accuracy(),
+            self.sensitivity(),
+            self.specificity(),
+            self.precision(),
+            self.f1()
+        )
+        .unwrap();
+        out
+    }
+}
+
+/// `numerator / denominator`, or NaN when nothing was counted.
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        f64::NAN
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// What a threshold is chosen to maximize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdCriterion {
+    /// Sensitivity + specificity - 1.
+    Youden,
+    F1,
+    Accuracy,
+}
+
+impl ThresholdCriterion {
+    pub const ALL: [ThresholdCriterion; 3] =
+        [ThresholdCriterion::Youden, ThresholdCriterion::F1, ThresholdCriterion::Accuracy];
+
+    /// Value of the criterion for `matrix`, NaN where it is undefined.
+    pub fn score(self, matrix: &ConfusionMatrix) -> f64 {
+        match self {
+            ThresholdCriterion::Youden => matrix.sensitivity() + matrix.specificity() - 1.0,
+            ThresholdCriterion::F1 => matrix.f1(),
+            ThresholdCriterion::Accuracy => matrix.accuracy(),
+        }
+    }
+}
+
+impl fmt::Display for ThresholdCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ThresholdCriterion::Youden => "Youden's J",
+            ThresholdCriterion::F1 => "F1",
+            ThresholdCriterion::Accuracy => "Accuracy",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Predictions whose probability falls in [lower, upper).
+#[derive(Debug, Clone, Copy)]
+pub struct CalibrationBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub n: usize,
+    pub mean_predicted: f64,
+    pub observed_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub n: usize,
+    pub positives: usize,
+    /// Area under the ROC curve, the probability that a random positive
+    /// scores above a random negative (ties count half).
+    pub auc: f64,
+    /// DeLong standard error of `auc`.
+    pub auc_std_error: f64,
+    /// `CONFIDENCE_LEVEL` DeLong interval, clipped to [0, 1].
+    pub auc_ci: (f64, f64),
+    /// One confusion matrix per distinct predicted probability, from threshold
+    /// +infinity (nothing positive) down to the smallest; its points trace both
+    /// the ROC and the precision-recall curve.
+    pub curve: Vec<ConfusionMatrix>,
+    /// Precision averaged over the recall gained at each threshold.
+    pub average_precision: f64,
+    pub log_loss: f64,
+    /// Mean squared difference between probability and outcome.
+    pub brier_score: f64,
+    /// Non-empty bins only.
+    pub calibration: Vec<CalibrationBin>,
+}
+
+impl Evaluation {
+    /// Evaluates `probabilities` against 0/1 outcomes `y`.
+    pub fn new(probabilities: &[f64], y: &[f64]) -> Result<Evaluation, Box<dyn Error>> {
+        if probabilities.len() != y.len() {
+            return Err("probabilities and outcomes must be of equal length".into());
+        }
+        if let Some(p) = probabilities.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            return Err(format!("predicted probability {} is outside [0, 1]", p).into());
+        }
+        if y.iter().any(|&v| v != 0.0 && v != 1.0) {
+            return Err("outcomes must take only the values 0 and 1".into());
+        }
+        let n = y.len();
+        let positives = y.iter().filter(|&&v| v == 1.0).count();
+        let negatives = n - positives;
+        if positives == 0 || negatives == 0 {
+            return Err("evaluation needs both positive and negative outcomes".into());
+        }
+        let nf = n as f64;
+
+        // Threshold sweep from the highest probability down
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| probabilities[b].total_cmp(&probabilities[a]));
+        let mut curve = vec![ConfusionMatrix {
+            threshold: f64::INFINITY,
+            true_positive: 0,
+            false_positive: 0,
+            true_negative: negatives,
+            false_negative: positives,
+        }];
+        let mut average_precision = 0.0;
+        let mut start = 0;
+        while start < n {
+            let threshold = probabilities[order[start]];
+            let mut end = start;
+            let mut last = *curve.last().unwrap();
+            while end < n && probabilities[order[end]] == threshold {
+                if y[order[end]] == 1.0 {
+                    last.true_positive += 1;
+                    last.false_negative -= 1;
+                } else {
+                    last.false_positive += 1;
+                    last.true_negative -= 1;
+                }
+                end += 1;
+            }
+            last.threshold = threshold;
+            let gained = last.true_positive - curve.last().unwrap().true_positive;
+            average_precision += gained as f64 / positives as f64 * last.precision();
+            curve.push(last);
+            start = end;
+        }
+
+        // DeLong placements: each positive's share of negatives scored below it, and vice versa
+        let scores_of = |class: f64| {
+            let mut scores: Vec<f64> = probabilities.iter().zip(y).filter(|(_, &v)| v == class).map(|(&p, _)| p).collect();
+            scores.sort_by(f64::total_cmp);
+            scores
+        };
+        let (positive_scores, negative_scores) = (scores_of(1.0), scores_of(0.0));
+        let placement = |score: f64, sorted: &[f64]| {
+            let below = sorted.partition_point(|&s| s < score);
+            let tied = sorted.partition_point(|&s| s <= score) - below;
+            (below as f64 + tied as f64 / 2.0) / sorted.len() as f64
+        };
+        let v10: Vec<f64> = positive_scores.iter().map(|&s| placement(s, &negative_scores)).collect();
+        let v01: Vec<f64> = negative_scores.iter().map(|&s| 1.0 - placement(s, &positive_scores)).collect();
+        let auc = v10.iter().sum::<f64>() / positives as f64;
+        let variance = |values: &[f64]| {
+            if values.len() < 2 {
+                return 0.0;
+            }
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+        };
+        let auc_std_error = (variance(&v10) / positives as f64 + variance(&v01) / negatives as f64).sqrt();
+        let margin = Normal.quantile(0.5 + CONFIDENCE_LEVEL / 2.0) * auc_std_error;
+
+        let log_loss = -probabilities
+            .iter()
+            .zip(y)
+            .map(|(&p, &v)| {
+                let p = p.clamp(LOG_LOSS_EPSILON, 1.0 - LOG_LOSS_EPSILON);
+                v * p.ln() + (1.0 - v) * (1.0 - p).ln()
+            })
+            .sum::<f64>()
+            / nf;
+        let brier_score = probabilities.iter().zip(y).map(|(p, v)| (p - v).powi(2)).sum::<f64>() / nf;
+
+        let mut bins = vec![(0usize, 0.0, 0.0); CALIBRATION_BINS];
+        for (&p, &v) in probabilities.iter().zip(y) {
+            let bin = &mut bins[((p * CALIBRATION_BINS as f64) as usize).min(CALIBRATION_BINS - 1)];
+            bin.0 += 1;
+            bin.1 += p;
+            bin.2 += v;
+        }
+        let width = 1.0 / CALIBRATION_BINS as f64;
+        let calibration = bins
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (count, _, _))| *count > 0)
+            .map(|(k, (count, predicted, observed))| CalibrationBin {
+                lower: k as f64 * width,
+                upper: (k + 1) as f64 * width,
+                n: count,
+                mean_predicted: predicted / count as f64,
+                observed_rate: observed / count as f64,
+            })
+            .collect();
+
+        Ok(Evaluation {
+            n,
+            positives,
+            auc,
+            auc_std_error,
+            auc_ci: ((auc - margin).max(0.0), (auc + margin).min(1.0)),
+            curve,
+            average_precision,
+            log_loss,
+            brier_score,
+            calibration,
+        })
+    }
+
+    /// Evaluates probabilities keyed by `Individual::id` against each
+    /// individual's `target` field, so any scorer can be judged.
+    pub fn for_individuals(
+        predictions: &[(usize, f64)],
+        individuals: &[Individual],
+        target: Field,
+    ) -> Result<Evaluation, Box<dyn Error>> {
+        let outcomes: HashMap<usize, f64> = individuals.iter().filter_map(|i| Some((i.id, target.value(i)?))).collect();
+        let mut probabilities = Vec::with_capacity(predictions.len());
+        let mut y = Vec::with_capacity(predictions.len());
+        for &(id, p) in predictions {
+            let outcome = outcomes
+                .get(&id)
+                .ok_or_else(|| format!("individual {} has no observed {}", id, target.key()))?;
+            probabilities.push(p);
+            y.push(*outcome);
+        }
+        Evaluation::new(&probabilities, &y)
+    }
+
+    /// Absolute gap between predicted and observed rates, averaged over bins weighted by size.
+    pub fn expected_calibration_error(&self) -> f64 {
+        self.calibration
+            .iter()
+            .map(|b| b.n as f64 * (b.mean_predicted - b.observed_rate).abs())
+            .sum::<f64>()
+            / self.n as f64
+    }
+
+    /// The point of the curve maximizing `criterion`, the highest threshold among ties.
+    pub fn best_threshold(&self, criterion: ThresholdCriterion) -> ConfusionMatrix {
+        let mut best = self.curve[0];
+        let mut best_score = f64::NEG_INFINITY;
+        for matrix in &self.curve[1..] {
+            let score = criterion.score(matrix);
+            if score > best_score {
+                best = *matrix;
+                best_score = score;
+            }
+        }
+        best
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "Classification evaluation (n = {}, {} positive, prevalence {:.4})",
+            self.n,
+            self.positives,
+            self.positives as f64 / self.n as f64
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "ROC AUC: {:.4} (SE {:.4}, {:.0}% DeLong CI [{:.4}, {:.4}])",
+            self.auc,
+            self.auc_std_error,
+            CONFIDENCE_LEVEL * 100.0,
+            self.auc_ci.0,
+            self.auc_ci.1
+        )
+        .unwrap();
+        writeln!(out, "Average precision: {:.4}", self.average_precision).unwrap();
+        writeln!(out, "Log loss: {:.4}, Brier score: {:.4}", self.log_loss, self.brier_score).unwrap();
+        writeln!(
+            out,
+            "Calibration ({} equal-width bins), expected calibration error {:.4}:",
+            CALIBRATION_BINS,
+            self.expected_calibration_error()
+        )
+        .unwrap();
+        writeln!(out, "{:<12} {:>6} {:>15} {:>14}", "Bin", "N", "Mean predicted", "Observed rate").unwrap();
+        for bin in &self.calibration {
+            writeln!(
+                out,
+                "{:<12} {:>6} {:>15.4} {:>14.4}",
+                format!("[{:.1}, {:.1})", bin.lower, bin.upper),
+                bin.n,
+                bin.mean_predicted,
+                bin.observed_rate
+            )
+            .unwrap();
+        }
+        for criterion in ThresholdCriterion::ALL {
+            let best = self.best_threshold(criterion);
+            writeln!(out, "Best {} = {:.4}", criterion, criterion.score(&best)).unwrap();
+            write!(out, "{}", best.render()).unwrap();
+        }
+        out
+    }
+
+    /// Column names matching [`Evaluation::write_rows`].
+    pub fn csv_header() -> [&'static str; 9] {
+        [
+            "threshold",
+            "true_positive",
+            "false_positive",
+            "true_negative",
+            "false_negative",
+            "true_positive_rate",
+            "false_positive_rate",
+            "precision",
+            "recall",
+        ]
+    }
+
+    /// One row per point of the ROC and precision-recall curves, highest threshold first.
+    pub fn write_rows<W: std::io::Write>(&self, wtr: &mut csv::Writer<W>) -> Result<(), Box<dyn Error>> {
+        for m in &self.curve {
+            wtr.write_record([
+                m.threshold.to_string(),
+                m.true_positive.to_string(),
+                m.false_positive.to_string(),
+                m.true_negative.to_string(),
+                m.false_negative.to_string(),
+                m.sensitivity().to_string(),
+                (1.0 - m.specificity()).to_string(),
+                m.precision().to_string(),
+                m.sensitivity().to_string(),
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    /// Five positives then six negatives, tied at 0.4.
+    const PROBABILITIES: [f64; 11] = [0.9, 0.8, 0.7, 0.55, 0.4, 0.6, 0.5, 0.4, 0.3, 0.2, 0.1];
+    const OUTCOMES: [f64; 11] = [1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+
+    #[test]
+    fn auc_and_delong_variance_match_pairwise_definitions() {
+        // 53 of 60 pairs ordered, the tie counting half; pROC::var(method = "delong")
+        let evaluation = Evaluation::new(&PROBABILITIES, &OUTCOMES).unwrap();
+        close(evaluation.auc, 53.0 / 60.0, 1e-12);
+        close(evaluation.auc_std_error, 0.1046156988431681, 1e-12);
+        close(evaluation.auc_ci.0, 53.0 / 60.0 - 1.959963984540054 * 0.1046156988431681, 1e-9);
+        assert_eq!(evaluation.auc_ci.1, 1.0);
+    }
+
+    #[test]
+    fn scoring_rules_and_average_precision() {
+        let evaluation = Evaluation::new(&PROBABILITIES, &OUTCOMES).unwrap();
+        close(evaluation.log_loss, 0.4549772082410366, 1e-12);
+        close(evaluation.brier_score, 0.1465909090909091, 1e-12);
+        close(evaluation.average_precision, 0.885, 1e-12);
+        // Ten distinct probabilities after the empty threshold
+        assert_eq!(evaluation.curve.len(), 11);
+        assert_eq!(evaluation.curve.last().unwrap().true_positive, 5);
+    }
+
+    #[test]
+    fn best_threshold_maximizes_youden() {
+        let evaluation = Evaluation::new(&PROBABILITIES, &OUTCOMES).unwrap();
+        let best = evaluation.best_threshold(ThresholdCriterion::Youden);
+        assert_eq!(best.threshold, 0.55);
+        assert_eq!((best.true_positive, best.false_positive), (4, 1));
+        close(ThresholdCriterion::Youden.score(&best), 0.8 + 5.0 / 6.0 - 1.0, 1e-12);
+    }
+
+    #[test]
+    fn confusion_matrix_counts_at_threshold() {
+        let matrix = ConfusionMatrix::new(&PROBABILITIES, &OUTCOMES, DEFAULT_THRESHOLD);
+        assert_eq!(
+            (matrix.true_positive, matrix.false_negative, matrix.false_positive, matrix.true_negative),
+            (4, 1, 2, 4)
+        );
+        close(matrix.accuracy(), 8.0 / 11.0, 1e-12);
+        close(matrix.precision(), 4.0 / 6.0, 1e-12);
+        close(matrix.f1(), 2.0 * (4.0 / 6.0) * 0.8 / (4.0 / 6.0 + 0.8), 1e-12);
+        assert!(ConfusionMatrix::new(&[0.1], &[0.0], 0.5).sensitivity().is_nan());
+    }
+
+    #[test]
+    fn rejects_invalid_inputs() {
+        assert!(Evaluation::new(&[0.2, 0.8], &[1.0, 1.0]).is_err());
+        assert!(Evaluation::new(&[0.2, 1.5], &[0.0, 1.0]).is_err());
+        assert!(Evaluation::new(&[0.2, 0.8], &[0.0, 2.0]).is_err());
+        assert!(Evaluation::new(&[0.2], &[0.0, 1.0]).is_err());
+    }
+}
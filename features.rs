@@ -0,0 +1,332 @@
+//! Numeric feature matrices built from individuals: formula terms plus encoded
+//! categories, optionally scaled. Encoders and scaling are learned on the
+//! individuals a matrix is built from and reapplied unchanged to others, so
+//! held-out data never informs its own features.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::encoders::{FrequencyEncoder, OneHotEncoder, TargetEncoder};
+use crate::formula::{Formula, Term};
+use crate::individual::{Category, Field, Individual};
+use crate::linalg::Matrix;
+use crate::schema::Encoding;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling {
+    None,
+    /// Subtract the mean and divide by the standard deviation.
+    Standardize,
+    /// Map the observed range onto [0, 1].
+    MinMax,
+}
+
+impl Scaling {
+    pub fn parse(name: &str) -> Result<Scaling, Box<dyn Error>> {
+        match name.to_lowercase().replace(['-', '_'], "").as_str() {
+            "none" => Ok(Scaling::None),
+            "standardize" | "zscore" => Ok(Scaling::Standardize),
+            "minmax" => Ok(Scaling::MinMax),
+            _ => Err(format!("unknown scaling '{}' (expected none, standardize or minmax)", name).into()),
+        }
+    }
+
+    /// (center, scale) that map `values` as this scaling does; constant columns keep scale 1.
+    fn learn(self, values: &[f64]) -> (f64, f64) {
+        let n = values.len() as f64;
+        let (center, spread) = match self {
+            Scaling::None => return (0.0, 1.0),
+            Scaling::Standardize => {
+                let mean = values.iter().sum::<f64>() / n;
+                let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+                (mean, variance.sqrt())
+            }
+            Scaling::MinMax => {
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (min, max - min)
+            }
+        };
+        (center, if spread > 0.0 { spread } else { 1.0 })
+    }
+}
+
+impl fmt::Display for Scaling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Scaling::None => "unscaled",
+            Scaling::Standardize => "standardized",
+            Scaling::MinMax => "min-max scaled",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A category encoder learned from the individuals a matrix was built on.
+#[derive(Debug, Clone)]
+enum FittedEncoder {
+    OneHot(OneHotEncoder),
+    Target(TargetEncoder),
+    Frequency(FrequencyEncoder),
+}
+
+impl FittedEncoder {
+    /// One-hot encoding without a reference drops the first level, so the
+    /// indicators are not collinear with the intercept.
+    fn fit(encoding: &Encoding, values: &[Option<String>], target: &[Option<f64>]) -> Result<Self, Box<dyn Error>> {
+        Ok(match encoding {
+            Encoding::OneHot { reference } => {
+                let first = values.iter().flatten().min().cloned();
+                let reference = reference.clone().or(first);
+                FittedEncoder::OneHot(OneHotEncoder::fit(values, reference.as_deref())?)
+            }
+            Encoding::Target { smoothing, .. } => FittedEncoder::Target(TargetEncoder::fit(values, target, *smoothing)?),
+            Encoding::Frequency => FittedEncoder::Frequency(FrequencyEncoder::fit(values)),
+        })
+    }
+
+    fn column_names(&self, category: Category) -> Vec<String> {
+        match self {
+            FittedEncoder::OneHot(encoder) => encoder.column_names(category.key()),
+            FittedEncoder::Target(_) => vec![format!("{} (target-encoded)", category.key())],
+            FittedEncoder::Frequency(_) => vec![format!("{} (frequency)", category.key())],
+        }
+    }
+
+    fn transform(&self, values: &[Option<String>]) -> Vec<Vec<Option<f64>>> {
+        match self {
+            FittedEncoder::OneHot(encoder) => encoder.transform(values),
+            FittedEncoder::Target(encoder) => vec![encoder.transform(values)],
+            FittedEncoder::Frequency(encoder) => vec![encoder.transform(values)],
+        }
+    }
+}
+
+/// Which columns a `FeatureMatrix` has and how they are encoded and scaled.
+///
+/// Target encoding always encodes against `target`, whatever target the schema names.
+#[derive(Debug, Clone)]
+pub struct FeatureMatrixBuilder {
+    pub target: Field,
+    pub terms: Vec<Term>,
+    pub categories: Vec<(Category, Encoding)>,
+    pub scaling: Scaling,
+}
+
+impl FeatureMatrixBuilder {
+    /// The response and terms of `formula`, unscaled, with no categories.
+    pub fn new(formula: &Formula) -> Self {
+        FeatureMatrixBuilder {
+            target: formula.response,
+            terms: formula.terms.clone(),
+            categories: Vec::new(),
+            scaling: Scaling::None,
+        }
+    }
+
+    pub fn category(mut self, category: Category, encoding: Encoding) -> Self {
+        self.categories.push((category, encoding));
+        self
+    }
+
+    pub fn scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    /// True when `individual` has the target, every term and every category.
+    pub fn is_complete(&self, individual: &Individual) -> bool {
+        self.target.value(individual).is_some()
+            && self.terms.iter().all(|t| t.value(individual).is_some())
+            && self.categories.iter().all(|(c, _)| c.value(individual).is_some())
+    }
+
+    /// Learns the encoders and scaling from the complete cases of `individuals` and encodes them.
+    pub fn build(&self, individuals: &[Individual]) -> Result<FeatureMatrix, Box<dyn Error>> {
+        let complete: Vec<Individual> = individuals.iter().filter(|i| self.is_complete(i)).cloned().collect();
+        if complete.is_empty() {
+            return Err(format!("no individual has {} and every feature", self.target.key()).into());
+        }
+        let target = self.target.values(&complete);
+        let encoders = self
+            .categories
+            .iter()
+            .map(|(category, encoding)| {
+                FittedEncoder::fit(encoding, &category_values(*category, &complete), &target)
+                    .map_err(|e| format!("{}: {}", category.key(), e).into())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        let unscaled = FeatureMatrix::assemble(self.clone(), encoders, Vec::new(), &complete);
+        let scales = (0..unscaled.x.cols).map(|j| self.scaling.learn(&unscaled.x.column(j))).collect();
+        Ok(FeatureMatrix::assemble(unscaled.builder, unscaled.encoders, scales, &complete))
+    }
+}
+
+impl fmt::Display for FeatureMatrixBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.terms.iter().map(Term::to_string).collect();
+        parts.extend(self.categories.iter().map(|(category, encoding)| {
+            let encoding = match encoding {
+                Encoding::OneHot { .. } => "one-hot",
+                Encoding::Target { .. } => "target-encoded",
+                Encoding::Frequency => "frequency",
+            };
+            format!("{} ({})", category.key(), encoding)
+        }));
+        write!(f, "{} ~ {}", self.target.key(), parts.join(" + "))?;
+        if self.scaling != Scaling::None {
+            write!(f, ", {}", self.scaling)?;
+        }
+        Ok(())
+    }
+}
+
+fn category_values(category: Category, individuals: &[Individual]) -> Vec<Option<String>> {
+    individuals.iter().map(|i| category.value(i).map(str::to_string)).collect()
+}
+
+/// Encoded, scaled features of the complete cases of some individuals, with
+/// the encoders and scaling that produced them.
+#[derive(Debug, Clone)]
+pub struct FeatureMatrix {
+    /// Column names: terms, then encoded category columns.
+    pub names: Vec<String>,
+    /// One row per individual, one column per name; no intercept column.
+    pub x: Matrix,
+    pub y: Vec<f64>,
+    builder: FeatureMatrixBuilder,
+    encoders: Vec<FittedEncoder>,
+    /// (center, scale) per column; empty while unscaled.
+    scales: Vec<(f64, f64)>,
+}
+
+impl FeatureMatrix {
+    fn assemble(
+        builder: FeatureMatrixBuilder,
+        encoders: Vec<FittedEncoder>,
+        scales: Vec<(f64, f64)>,
+        individuals: &[Individual],
+    ) -> FeatureMatrix {
+        let mut names: Vec<String> = builder.terms.iter().map(Term::to_string).collect();
+        let mut columns: Vec<Vec<Option<f64>>> = builder
+            .terms
+            .iter()
+            .map(|t| individuals.iter().map(|i| t.value(i)).collect())
+            .collect();
+        for ((category, _), encoder) in builder.categories.iter().zip(&encoders) {
+            names.extend(encoder.column_names(*category));
+            columns.extend(encoder.transform(&category_values(*category, individuals)));
+        }
+
+        let (mut rows, mut y) = (Vec::new(), Vec::new());
+        for (r, individual) in individuals.iter().enumerate() {
+            let row: Option<Vec<f64>> = columns
+                .iter()
+                .enumerate()
+                .map(|(j, column)| {
+                    let (center, scale) = scales.get(j).copied().unwrap_or((0.0, 1.0));
+                    column[r].map(|v| (v - center) / scale)
+                })
+                .collect();
+            if let (Some(row), Some(target)) = (row, builder.target.value(individual)) {
+                rows.push(row);
+                y.push(target);
+            }
+        }
+        let x = if rows.is_empty() { Matrix::zeros(0, names.len()) } else { Matrix::from_rows(&rows) };
+        FeatureMatrix { names, x, y, builder, encoders, scales }
+    }
+
+    pub fn target(&self) -> Field {
+        self.builder.target
+    }
+
+    /// Encodes the complete cases of other `individuals` with this matrix's
+    /// encoders and scaling; unseen categories get the encoders' fallbacks.
+    pub fn transform(&self, individuals: &[Individual]) -> FeatureMatrix {
+        let complete: Vec<Individual> = individuals.iter().filter(|i| self.builder.is_complete(i)).cloned().collect();
+        FeatureMatrix::assemble(self.builder.clone(), self.encoders.clone(), self.scales.clone(), &complete)
+    }
+
+    /// `x` with an intercept column of ones first.
+    pub fn design(&self) -> Matrix {
+        let mut design = Matrix::zeros(self.x.rows, self.x.cols + 1);
+        for i in 0..self.x.rows {
+            design[(i, 0)] = 1.0;
+            for j in 0..self.x.cols {
+                design[(i, j + 1)] = self.x[(i, j)];
+            }
+        }
+        design
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    fn person(id: usize, age: f64, gender: &str, field: &str) -> Individual {
+        Individual {
+            id,
+            age: Some(age),
+            salary: Some(1000.0 * age),
+            gender: Some(gender.to_string()),
+            field_of_study: Some(field.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn builder(scaling: Scaling) -> FeatureMatrixBuilder {
+        FeatureMatrixBuilder::new(&Formula::parse("salary ~ age").unwrap())
+            .category(Category::Gender, Encoding::OneHot { reference: None })
+            .category(Category::FieldOfStudy, Encoding::Frequency)
+            .scaling(scaling)
+    }
+
+    fn train() -> Vec<Individual> {
+        vec![person(0, 20.0, "Male", "Law"), person(1, 30.0, "Female", "Law"), person(2, 40.0, "Male", "Art")]
+    }
+
+    #[test]
+    fn transform_reapplies_the_training_encoders_and_scaling() {
+        let matrix = builder(Scaling::Standardize).build(&train()).unwrap();
+        // One-hot drops the first level, Female.
+        assert_eq!(matrix.names, ["age", "gender=Male", "field_of_study (frequency)"]);
+        assert_eq!(matrix.transform(&train()).x, matrix.x);
+
+        // Training columns: age 20/30/40 (mean 30, sd 10); Male 1/0/1 (mean 2/3,
+        // sd √(1/3)); frequency 2/3, 2/3, 1/3 (mean 5/9, sd √3/9).
+        let mut incomplete = person(5, 30.0, "Male", "Law");
+        incomplete.gender = None;
+        let test = [person(3, 50.0, "Other", "Art"), person(4, 30.0, "Male", "Music"), incomplete];
+        let held_out = matrix.transform(&test);
+        assert_eq!((held_out.x.rows, held_out.y.clone()), (2, vec![50000.0, 30000.0]));
+        let male = |v: f64| (v - 2.0 / 3.0) / (1.0_f64 / 3.0).sqrt();
+        let frequency = |v: f64| (v - 5.0 / 9.0) / (3.0_f64.sqrt() / 9.0);
+        let expected = [[2.0, male(0.0), frequency(1.0 / 3.0)], [0.0, male(1.0), frequency(0.0)]];
+        for (i, row) in expected.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                close(held_out.x[(i, j)], value, 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn min_max_scaling_keeps_the_training_range() {
+        let matrix = builder(Scaling::MinMax).build(&train()).unwrap();
+        assert_eq!(matrix.x.column(0), [0.0, 0.5, 1.0]);
+        let held_out = matrix.transform(&[person(3, 50.0, "Male", "Law"), person(4, 10.0, "Male", "Law")]);
+        assert_eq!(held_out.x.column(0), [1.5, -0.5]);
+    }
+
+    #[test]
+    fn building_needs_a_complete_case() {
+        let mut individuals = train();
+        for individual in &mut individuals {
+            individual.salary = None;
+        }
+        let error = builder(Scaling::None).build(&individuals).unwrap_err();
+        assert_eq!(error.to_string(), "no individual has salary and every feature");
+    }
+}
@@ -0,0 +1,205 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::individual::{Field, Individual};
+use crate::schema::Role;
+use crate::table::Table;
+
+/// A product of powers of fields, e.g. `age`, `age^2` or `age:job_satisfaction`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub factors: Vec<(Field, u32)>,
+}
+
+impl Term {
+    pub fn single(field: Field) -> Self {
+        Term { factors: vec![(field, 1)] }
+    }
+
+    /// Value of the term for one individual, `None` if any factor is missing.
+    pub fn value(&self, individual: &Individual) -> Option<f64> {
+        self.factors
+            .iter()
+            .try_fold(1.0, |acc, &(field, power)| Some(acc * field.value(individual)?.powi(power as i32)))
+    }
+
+    /// Combines two terms into their interaction, merging repeated fields.
+    fn interact(&self, other: &Term) -> Term {
+        let mut factors = self.factors.clone();
+        for &(field, power) in &other.factors {
+            match factors.iter_mut().find(|(f, _)| *f == field) {
+                Some((_, p)) => *p += power,
+                None => factors.push((field, power)),
+            }
+        }
+        factors.sort();
+        Term { factors }
+    }
+
+    fn parse(text: &str) -> Result<Term, Box<dyn Error>> {
+        let mut term = Term { factors: Vec::new() };
+        for factor in text.split(':') {
+            let (name, power) = match factor.split_once('^') {
+                Some((name, power)) => {
+                    let power: u32 = power
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid power in term '{}'", text.trim()))?;
+                    if power == 0 {
+                        return Err(format!("power must be positive in term '{}'", text.trim()).into());
+                    }
+                    (name, power)
+                }
+                None => (factor, 1),
+            };
+            term = term.interact(&Term { factors: vec![(Field::parse(name.trim())?, power)] });
+        }
+        Ok(term)
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .factors
+            .iter()
+            .map(|&(field, power)| {
+                if power == 1 {
+                    field.key().to_string()
+                } else {
+                    format!("{}^{}", field.key(), power)
+                }
+            })
+            .collect();
+        write!(f, "{}", parts.join(":"))
+    }
+}
+
+/// A model specification `response ~ term + term + ...`.
+///
+/// Terms are fields (`age`), powers (`age^2`), interactions (`age:job_satisfaction`),
+/// or crossings (`age*job_satisfaction`, shorthand for `age + job_satisfaction + age:job_satisfaction`).
+#[derive(Debug, Clone)]
+pub struct Formula {
+    pub response: Field,
+    pub terms: Vec<Term>,
+}
+
+impl Formula {
+    pub fn parse(text: &str) -> Result<Formula, Box<dyn Error>> {
+        let (lhs, rhs) = text
+            .split_once('~')
+            .ok_or_else(|| format!("formula '{}' needs the form 'response ~ terms'", text))?;
+        let response = Field::parse(lhs.trim())?;
+
+        let mut terms: Vec<Term> = Vec::new();
+        for part in rhs.split('+').map(str::trim) {
+            if part.is_empty() {
+                return Err(format!("empty term in formula '{}'", text).into());
+            }
+            // a*b*c expands to every non-empty interaction of its factors
+            let crossed: Vec<Term> = part.split('*').map(Term::parse).collect::<Result<_, _>>()?;
+            let mut expanded: Vec<Term> = Vec::new();
+            for factor in &crossed {
+                let with_factor: Vec<Term> = expanded.iter().map(|t| t.interact(factor)).collect();
+                expanded.push(factor.clone());
+                expanded.extend(with_factor);
+            }
+            expanded.sort_by_key(|t| t.factors.len());
+            for term in expanded {
+                if !terms.contains(&term) {
+                    terms.push(term);
+                }
+            }
+        }
+        if terms.iter().any(|t| t.factors.iter().any(|&(f, _)| f == response)) {
+            return Err(format!("response '{}' also appears among the predictors", response.key()).into());
+        }
+        Ok(Formula { response, terms })
+    }
+
+    /// A design matrix row for one individual: 1 for the intercept, then each
+    /// term's value; `None` if any term is missing.
+    pub fn row(&self, individual: &Individual) -> Option<Vec<f64>> {
+        let mut row = Vec::with_capacity(self.terms.len() + 1);
+        row.push(1.0);
+        for term in &self.terms {
+            row.push(term.value(individual)?);
+        }
+        Some(row)
+    }
+
+    /// Response ~ every other field the table loads as a feature, main effects
+    /// only. Columns with role `target` are left out, so one response never
+    /// predicts another. Fails when the table does not load the response.
+    pub fn all_features(response: Field, table: &Table) -> Result<Formula, Box<dyn Error>> {
+        if table.column(response.column()).is_none() {
+            return Err(format!("the schema does not load '{}'", response.column()).into());
+        }
+        let terms = Field::ALL
+            .into_iter()
+            .filter(|&f| f != response && table.column(f.column()).is_some_and(|c| c.spec.role == Role::Feature))
+            .map(Term::single)
+            .collect();
+        Ok(Formula { response, terms })
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self.terms.iter().map(Term::to_string).collect();
+        write!(f, "{} ~ {}", self.response.key(), terms.join(" + "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::load;
+
+    const SCHEMA: &str = r#"
+[[column]]
+name = "Age"
+type = "numeric"
+
+[[column]]
+name = "Years of Experience"
+type = "numeric"
+
+[[column]]
+name = "Job Satisfaction"
+type = "numeric"
+role = "ignore"
+
+[[column]]
+name = "Salary"
+type = "numeric"
+role = "target"
+
+[[column]]
+name = "Likely to Change Occupation"
+type = "boolean"
+role = "target"
+"#;
+
+    const CSV: &str = "Age,Years of Experience,Job Satisfaction,Salary,Likely to Change Occupation\n30,5,7,50000,1\n";
+
+    #[test]
+    fn default_formula_uses_loaded_features_only() {
+        let (table, _) = load("formula", SCHEMA, CSV).unwrap();
+        let salary = Formula::all_features(Field::Salary, &table).unwrap();
+        assert_eq!(salary.to_string(), "salary ~ age + years_of_experience");
+        let change = Formula::all_features(Field::LikelyToChangeOccupation, &table).unwrap();
+        assert_eq!(change.to_string(), "likely_to_change_occupation ~ age + years_of_experience");
+        assert!(Formula::all_features(Field::JobSatisfaction, &table).is_err());
+    }
+
+    #[test]
+    fn crossings_expand_to_main_effects_and_interactions() {
+        let formula = Formula::parse("salary ~ age*job_satisfaction + age^2").unwrap();
+        assert_eq!(formula.to_string(), "salary ~ age + job_satisfaction + age:job_satisfaction + age^2");
+        assert!(Formula::parse("salary ~ age + salary").is_err());
+        assert!(Formula::parse("salary ~ age + ").is_err());
+        assert!(Formula::parse("salary ~ age^0").is_err());
+    }
+}
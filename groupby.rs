@@ -0,0 +1,425 @@
+//! Splitting individuals by a loaded column and comparing statistics across the groups.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::anova::Sample;
+use crate::correction::Correction;
+use crate::correlation::{stars, CorrelationMatrix, CorrelationMethod};
+use crate::formula::Formula;
+use crate::individual::{Field, Individual};
+use crate::ols::MultipleRegression;
+use crate::summary::Summary;
+use crate::table::Table;
+
+/// Key of the group holding individuals with no value in the grouping column.
+pub const MISSING_GROUP: &str = "(missing)";
+/// Column label for the statistics over every individual.
+const ALL_GROUPS: &str = "All";
+
+/// A statistic shown per group in [`GroupBy::summary_pivot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupStat {
+    Count,
+    Mean,
+    Median,
+    StdDev,
+    Min,
+    Max,
+}
+
+impl GroupStat {
+    pub fn parse(name: &str) -> Result<GroupStat, Box<dyn Error>> {
+        match name.trim().to_lowercase().as_str() {
+            "n" | "count" => Ok(GroupStat::Count),
+            "mean" => Ok(GroupStat::Mean),
+            "median" => Ok(GroupStat::Median),
+            "sd" | "std_dev" => Ok(GroupStat::StdDev),
+            "min" => Ok(GroupStat::Min),
+            "max" => Ok(GroupStat::Max),
+            other => Err(format!("unknown statistic '{}' (expected n, mean, median, sd, min or max)", other).into()),
+        }
+    }
+
+    fn of(self, summary: &Summary) -> String {
+        match self {
+            GroupStat::Count => summary.count.to_string(),
+            GroupStat::Mean => format!("{:.2}", summary.mean),
+            GroupStat::Median => format!("{:.2}", summary.median),
+            GroupStat::StdDev => format!("{:.2}", summary.std_dev),
+            GroupStat::Min => format!("{:.2}", summary.min),
+            GroupStat::Max => format!("{:.2}", summary.max),
+        }
+    }
+}
+
+impl fmt::Display for GroupStat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            GroupStat::Count => "n",
+            GroupStat::Mean => "mean",
+            GroupStat::Median => "median",
+            GroupStat::StdDev => "sd",
+            GroupStat::Min => "min",
+            GroupStat::Max => "max",
+        })
+    }
+}
+
+/// A labelled grid of preformatted cells, one column per group.
+#[derive(Debug, Clone)]
+pub struct PivotTable {
+    pub title: String,
+    pub corner: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<(String, Vec<String>)>,
+    /// Footnotes such as skipped fits or the significance legend.
+    pub notes: Vec<String>,
+}
+
+impl PivotTable {
+    pub fn render(&self) -> String {
+        let header: Vec<&str> = std::iter::once(self.corner.as_str())
+            .chain(self.columns.iter().map(String::as_str))
+            .collect();
+        let lines: Vec<Vec<&str>> = std::iter::once(header)
+            .chain(self.rows.iter().map(|(label, cells)| {
+                std::iter::once(label.as_str())
+                    .chain(cells.iter().map(String::as_str))
+                    .collect()
+            }))
+            .collect();
+        let widths: Vec<usize> = (0..=self.columns.len())
+            .map(|c| lines.iter().map(|line| line[c].len()).max().unwrap_or(0))
+            .collect();
+
+        let mut out = format!("{}\n", self.title);
+        for line in &lines {
+            let cells: Vec<String> = line
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(c, (cell, &w))| if c == 0 { format!("{:<w$}", cell) } else { format!("{:>w$}", cell) })
+                .collect();
+            out.push_str(cells.join("  ").trim_end());
+            out.push('\n');
+        }
+        for note in &self.notes {
+            out.push_str(note);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Appends the header and every row to `wtr`; notes are left out.
+    pub fn write_rows<W: std::io::Write>(&self, wtr: &mut csv::Writer<W>) -> Result<(), Box<dyn Error>> {
+        let mut header = vec![self.corner.clone()];
+        header.extend(self.columns.iter().cloned());
+        wtr.write_record(&header)?;
+        for (label, cells) in &self.rows {
+            let mut row = vec![label.clone()];
+            row.extend(cells.iter().cloned());
+            wtr.write_record(&row)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub key: String,
+    pub members: Vec<Individual>,
+}
+
+/// Individuals partitioned by the value of one loaded column.
+#[derive(Debug, Clone)]
+pub struct GroupBy {
+    pub by: String,
+    /// Ordered by level code for ordinal and numeric columns, by name otherwise,
+    /// with the missing group last.
+    pub groups: Vec<Group>,
+    all: Vec<Individual>,
+}
+
+impl GroupBy {
+    /// Groups `individuals` (built from `table`, in any order or subset) by
+    /// column `by`, which can be any loaded column, categorical or not. Each
+    /// individual is matched to its table row by `Individual::id`.
+    pub fn from_table(table: &Table, individuals: &[Individual], by: &str) -> Result<GroupBy, Box<dyn Error>> {
+        let keys = table
+            .group_keys(by)
+            .ok_or_else(|| format!("no loaded column named '{}'", by))?;
+        let order = table.numeric(by);
+        let rows: HashMap<usize, usize> = table.row_ids.iter().enumerate().map(|(r, &id)| (id, r)).collect();
+
+        let mut groups: Vec<(Option<String>, Option<f64>, Vec<Individual>)> = Vec::new();
+        for individual in individuals {
+            let r = *rows
+                .get(&individual.id)
+                .ok_or_else(|| format!("individual {} is not a row of the table", individual.id))?;
+            let key = &keys[r];
+            match groups.iter_mut().find(|(k, _, _)| k == key) {
+                Some((_, _, members)) => members.push(individual.clone()),
+                None => groups.push((key.clone(), order.as_ref().and_then(|o| o[r]), vec![individual.clone()])),
+            }
+        }
+        groups.sort_by(|(ka, oa, _), (kb, ob, _)| {
+            ka.is_none()
+                .cmp(&kb.is_none())
+                .then_with(|| match (oa, ob) {
+                    (Some(a), Some(b)) => a.total_cmp(b),
+                    _ => ka.cmp(kb),
+                })
+        });
+
+        let by = table.column(by).map_or(by.to_string(), |c| c.spec.name.clone());
+        Ok(GroupBy {
+            by,
+            groups: groups
+                .into_iter()
+                .map(|(key, _, members)| Group {
+                    key: key.unwrap_or_else(|| MISSING_GROUP.to_string()),
+                    members,
+                })
+                .collect(),
+            all: individuals.to_vec(),
+        })
+    }
+
+    /// Observed values of `field` in each group, leaving out the missing group.
+    pub fn samples(&self, field: Field) -> Vec<Sample> {
+        self.groups
+            .iter()
+            .filter(|g| g.key != MISSING_GROUP)
+            .map(|g| (g.key.clone(), g.members.iter().filter_map(|ind| field.value(ind)).collect()))
+            .collect()
+    }
+
+    /// Group keys followed by the all-individuals column.
+    fn column_labels(&self) -> Vec<String> {
+        self.groups
+            .iter()
+            .map(|g| g.key.clone())
+            .chain(std::iter::once(ALL_GROUPS.to_string()))
+            .collect()
+    }
+
+    fn member_sets(&self) -> impl Iterator<Item = &[Individual]> {
+        self.groups
+            .iter()
+            .map(|g| g.members.as_slice())
+            .chain(std::iter::once(self.all.as_slice()))
+    }
+
+    /// One row per field and statistic, after a row of group sizes.
+    pub fn summary_pivot(&self, fields: &[Field], stats: &[GroupStat]) -> PivotTable {
+        let mut rows = vec![(
+            "individuals".to_string(),
+            self.member_sets().map(|m| m.len().to_string()).collect(),
+        )];
+        for &field in fields {
+            let summaries: Vec<Option<Summary>> = self
+                .member_sets()
+                .map(|members| Summary::from_column(field.label(), &field.values(members)))
+                .collect();
+            for &stat in stats {
+                let label = if stats.len() == 1 {
+                    field.label().to_string()
+                } else {
+                    format!("{} ({})", field.label(), stat)
+                };
+                let cells = summaries
+                    .iter()
+                    .map(|s| s.as_ref().map_or("-".to_string(), |s| stat.of(s)))
+                    .collect();
+                rows.push((label, cells));
+            }
+        }
+        let stat_names: Vec<String> = stats.iter().map(|s| s.to_string()).collect();
+        PivotTable {
+            title: format!("{} of each field by {}", stat_names.join(", "), self.by),
+            corner: self.by.clone(),
+            columns: self.column_labels(),
+            rows,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Correlation of every pair of `fields` within each group, with
+    /// significance adjusted by `correction` across the pairs of that group.
+    pub fn correlation_pivot(&self, fields: &[Field], method: CorrelationMethod, correction: Correction) -> PivotTable {
+        let matrices: Vec<(CorrelationMatrix, Vec<Vec<Option<f64>>>)> = self
+            .member_sets()
+            .map(|members| {
+                let columns: Vec<_> = fields
+                    .iter()
+                    .map(|f| (f.label().to_string(), f.values(members)))
+                    .collect();
+                let matrix = CorrelationMatrix::compute(&columns, method);
+                let adjusted = matrix.adjusted_p_values(correction);
+                (matrix, adjusted)
+            })
+            .collect();
+
+        let mut rows = Vec::new();
+        for i in 0..fields.len() {
+            for j in i + 1..fields.len() {
+                let cells = matrices
+                    .iter()
+                    .map(|(matrix, adjusted)| match (matrix.tests[i][j], adjusted[i][j]) {
+                        (Some(test), Some(p)) => format!("{:.3}{}", test.estimate, stars(p)),
+                        _ => "-".to_string(),
+                    })
+                    .collect();
+                rows.push((format!("{} x {}", fields[i].label(), fields[j].label()), cells));
+            }
+        }
+        PivotTable {
+            title: format!("{} correlations by {} ({} adjusted within each group)", method, self.by, correction),
+            corner: "pair".to_string(),
+            columns: self.column_labels(),
+            rows,
+            notes: vec!["* p < 0.05, ** p < 0.01, *** p < 0.001".to_string()],
+        }
+    }
+
+    /// Fits `formula` separately within each group: one row per coefficient,
+    /// then the number of complete cases and R².
+    pub fn regression_pivot(&self, formula: &Formula) -> PivotTable {
+        let columns = self.column_labels();
+        let fits: Vec<Result<MultipleRegression, Box<dyn Error>>> = self
+            .member_sets()
+            .map(|members| MultipleRegression::fit(members, formula))
+            .collect();
+
+        let mut notes = Vec::new();
+        let mut term_names: Vec<String> = Vec::new();
+        for (label, fit) in columns.iter().zip(&fits) {
+            match fit {
+                Ok(fit) => {
+                    for term in &fit.terms {
+                        if !term_names.contains(&term.name) {
+                            term_names.push(term.name.clone());
+                        }
+                    }
+                }
+                Err(e) => notes.push(format!("{}: skipped ({})", label, e)),
+            }
+        }
+
+        let cell = |fit: &Result<MultipleRegression, _>, f: &dyn Fn(&MultipleRegression) -> Option<String>| {
+            fit.as_ref().ok().and_then(f).unwrap_or_else(|| "-".to_string())
+        };
+        let mut rows: Vec<(String, Vec<String>)> = term_names
+            .iter()
+            .map(|name| {
+                let cells = fits
+                    .iter()
+                    .map(|fit| {
+                        cell(fit, &|fit| {
+                            let term = fit.terms.iter().find(|t| &t.name == name)?;
+                            let c = &term.coefficient;
+                            Some(format!("{:.4}{}", c.estimate, stars(c.p_value)))
+                        })
+                    })
+                    .collect();
+                (name.clone(), cells)
+            })
+            .collect();
+        rows.push((
+            "Observations".to_string(),
+            fits.iter().map(|fit| cell(fit, &|fit| Some(fit.n.to_string()))).collect(),
+        ));
+        rows.push((
+            "R-squared".to_string(),
+            fits.iter()
+                .map(|fit| cell(fit, &|fit| Some(format!("{:.4}", fit.r_squared))))
+                .collect(),
+        ));
+        notes.push("* p < 0.05, ** p < 0.01, *** p < 0.001".to_string());
+
+        PivotTable {
+            title: format!("{} fitted within each group of {}", formula, self.by),
+            corner: "term".to_string(),
+            columns,
+            rows,
+            notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::load;
+
+    const SCHEMA: &str = r#"
+[[column]]
+name = "Level"
+type = "ordinal"
+levels = { Low = 1, High = 2 }
+missing = "keep"
+
+[[column]]
+name = "Team"
+type = "categorical"
+"#;
+
+    // Record 2 is dropped, so table rows and record indices diverge after it.
+    const CSV: &str = "Level,Team\nHigh,a\nLow,a\nExtreme,b\n,b\nHigh,c\nLow,c\n";
+
+    /// The loaded individuals in reverse order, so grouping by position would mix them up.
+    fn grouped() -> GroupBy {
+        let (table, _) = load("groupby", SCHEMA, CSV).unwrap();
+        assert_eq!(table.row_ids, [0, 1, 3, 4, 5]);
+        let individuals: Vec<Individual> = [(5, 60.0), (4, 50.0), (3, 30.0), (1, 20.0), (0, 10.0)]
+            .into_iter()
+            .map(|(id, salary)| Individual { id, salary: Some(salary), ..Default::default() })
+            .collect();
+        GroupBy::from_table(&table, &individuals, "level").unwrap()
+    }
+
+    #[test]
+    fn individuals_are_grouped_by_id_in_level_order() {
+        let groups = grouped();
+        assert_eq!(groups.by, "Level");
+        let members: Vec<(&str, Vec<usize>)> = groups
+            .groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.members.iter().map(|m| m.id).collect()))
+            .collect();
+        assert_eq!(members, [("Low", vec![5, 1]), ("High", vec![4, 0]), (MISSING_GROUP, vec![3])]);
+        assert_eq!(
+            groups.samples(Field::Salary),
+            [("Low".to_string(), vec![60.0, 20.0]), ("High".to_string(), vec![50.0, 10.0])]
+        );
+    }
+
+    #[test]
+    fn individuals_outside_the_table_are_rejected() {
+        let (table, _) = load("groupby-unknown", SCHEMA, CSV).unwrap();
+        let stray = [Individual { id: 2, ..Default::default() }];
+        let error = GroupBy::from_table(&table, &stray, "Level").unwrap_err();
+        assert_eq!(error.to_string(), "individual 2 is not a row of the table");
+        assert!(GroupBy::from_table(&table, &stray, "Gender").is_err());
+    }
+
+    #[test]
+    fn summary_pivot_has_a_column_per_group_and_overall() {
+        let pivot = grouped().summary_pivot(&[Field::Salary], &[GroupStat::Count, GroupStat::Mean]);
+        assert_eq!(pivot.columns, ["Low", "High", MISSING_GROUP, ALL_GROUPS]);
+        let salary = Field::Salary.label();
+        let expected = [
+            ("individuals".to_string(), ["2", "2", "1", "5"]),
+            (format!("{} (n)", salary), ["2", "2", "1", "5"]),
+            (format!("{} (mean)", salary), ["40.00", "30.00", "30.00", "34.00"]),
+        ];
+        assert_eq!(pivot.rows.len(), expected.len());
+        for ((label, cells), (expected_label, expected_cells)) in pivot.rows.iter().zip(&expected) {
+            assert_eq!(label, expected_label);
+            assert_eq!(cells, expected_cells);
+        }
+        assert!(pivot.render().starts_with("n, mean of each field by Level\n"));
+    }
+}
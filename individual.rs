@@ -0,0 +1,523 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::columns::{
+    normalize_header, AGE, CAREER_CHANGE_EVENTS, CAREER_CHANGE_INTEREST, CERTIFICATIONS, CURRENT_OCCUPATION,
+    EDUCATION_LEVEL, FAMILY_INFLUENCE, FIELD_OF_STUDY, FREELANCING_EXPERIENCE, GENDER, GEOGRAPHIC_MOBILITY,
+    INDUSTRY_GROWTH_RATE, JOB_OPPORTUNITIES, JOB_SATISFACTION, JOB_SECURITY, LIKELY_TO_CHANGE_OCCUPATION,
+    MENTORSHIP_AVAILABLE, PROFESSIONAL_NETWORKS, SALARY, SKILLS_GAP, TECHNOLOGY_ADOPTION, WORK_LIFE_BALANCE,
+    YEARS_OF_EXPERIENCE,
+};
+use crate::table::{NamedSeries, Table};
+
+#[derive(Debug, Clone, Default)]
+pub struct Individual {
+    pub id: usize,
+    // `None` where the cell was blank and the column's missing policy is `keep`,
+    // or the schema does not load the column (only the first six are required)
+    pub age: Option<f64>,
+    pub years_of_experience: Option<f64>,
+    pub job_satisfaction: Option<f64>,
+    pub professional_network_size: Option<f64>,
+    pub family_influence: Option<f64>, // Ordinal code from the schema's levels (default None → 0, Low → 1, Medium → 2, High → 3)
+    pub salary: Option<f64>,
+    pub field_of_study: Option<String>,
+    pub current_occupation: Option<String>,
+    pub gender: Option<String>,
+    pub education_level: Option<f64>, // Ordinal code (default High School → 1, Bachelor's → 2, Master's → 3, PhD → 4)
+    pub industry_growth_rate: Option<f64>, // Ordinal code (default Low → 1, Medium → 2, High → 3)
+    pub work_life_balance: Option<f64>,
+    pub job_opportunities: Option<f64>,
+    pub job_security: Option<f64>,
+    pub career_change_interest: Option<bool>,
+    pub skills_gap: Option<f64>,
+    pub mentorship_available: Option<bool>,
+    pub certifications: Option<bool>,
+    pub freelancing_experience: Option<bool>,
+    pub geographic_mobility: Option<bool>,
+    pub career_change_events: Option<f64>,
+    pub technology_adoption: Option<f64>,
+    pub likely_to_change_occupation: Option<bool>,
+}
+
+/// A numeric field of `Individual`, for analyses that select columns by name.
+/// Boolean fields read as 0/1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Field {
+    Age,
+    YearsOfExperience,
+    JobSatisfaction,
+    ProfessionalNetworkSize,
+    FamilyInfluence,
+    Salary,
+    EducationLevel,
+    IndustryGrowthRate,
+    WorkLifeBalance,
+    JobOpportunities,
+    JobSecurity,
+    CareerChangeInterest,
+    SkillsGap,
+    MentorshipAvailable,
+    Certifications,
+    FreelancingExperience,
+    GeographicMobility,
+    CareerChangeEvents,
+    TechnologyAdoption,
+    LikelyToChangeOccupation,
+}
+
+impl Field {
+    /// The six fields the report's pairwise analyses are built around.
+    pub const CORE: [Field; 6] = [
+        Field::Age,
+        Field::YearsOfExperience,
+        Field::JobSatisfaction,
+        Field::ProfessionalNetworkSize,
+        Field::FamilyInfluence,
+        Field::Salary,
+    ];
+
+    pub const ALL: [Field; 20] = [
+        Field::Age,
+        Field::YearsOfExperience,
+        Field::JobSatisfaction,
+        Field::ProfessionalNetworkSize,
+        Field::FamilyInfluence,
+        Field::Salary,
+        Field::EducationLevel,
+        Field::IndustryGrowthRate,
+        Field::WorkLifeBalance,
+        Field::JobOpportunities,
+        Field::JobSecurity,
+        Field::CareerChangeInterest,
+        Field::SkillsGap,
+        Field::MentorshipAvailable,
+        Field::Certifications,
+        Field::FreelancingExperience,
+        Field::GeographicMobility,
+        Field::CareerChangeEvents,
+        Field::TechnologyAdoption,
+        Field::LikelyToChangeOccupation,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Field::Age => "Age",
+            Field::YearsOfExperience => "Years of Experience",
+            Field::JobSatisfaction => "Job Satisfaction",
+            Field::ProfessionalNetworkSize => "Professional Network Size",
+            Field::FamilyInfluence => "Family Influence",
+            Field::Salary => "Salary",
+            Field::EducationLevel => EDUCATION_LEVEL,
+            Field::IndustryGrowthRate => INDUSTRY_GROWTH_RATE,
+            Field::WorkLifeBalance => WORK_LIFE_BALANCE,
+            Field::JobOpportunities => JOB_OPPORTUNITIES,
+            Field::JobSecurity => JOB_SECURITY,
+            Field::CareerChangeInterest => CAREER_CHANGE_INTEREST,
+            Field::SkillsGap => SKILLS_GAP,
+            Field::MentorshipAvailable => MENTORSHIP_AVAILABLE,
+            Field::Certifications => CERTIFICATIONS,
+            Field::FreelancingExperience => FREELANCING_EXPERIENCE,
+            Field::GeographicMobility => GEOGRAPHIC_MOBILITY,
+            Field::CareerChangeEvents => CAREER_CHANGE_EVENTS,
+            Field::TechnologyAdoption => TECHNOLOGY_ADOPTION,
+            Field::LikelyToChangeOccupation => LIKELY_TO_CHANGE_OCCUPATION,
+        }
+    }
+
+    /// Name of the schema column the field is loaded from.
+    pub fn column(self) -> &'static str {
+        match self {
+            Field::Age => AGE,
+            Field::YearsOfExperience => YEARS_OF_EXPERIENCE,
+            Field::JobSatisfaction => JOB_SATISFACTION,
+            Field::ProfessionalNetworkSize => PROFESSIONAL_NETWORKS,
+            Field::FamilyInfluence => FAMILY_INFLUENCE,
+            Field::Salary => SALARY,
+            Field::EducationLevel => EDUCATION_LEVEL,
+            Field::IndustryGrowthRate => INDUSTRY_GROWTH_RATE,
+            Field::WorkLifeBalance => WORK_LIFE_BALANCE,
+            Field::JobOpportunities => JOB_OPPORTUNITIES,
+            Field::JobSecurity => JOB_SECURITY,
+            Field::CareerChangeInterest => CAREER_CHANGE_INTEREST,
+            Field::SkillsGap => SKILLS_GAP,
+            Field::MentorshipAvailable => MENTORSHIP_AVAILABLE,
+            Field::Certifications => CERTIFICATIONS,
+            Field::FreelancingExperience => FREELANCING_EXPERIENCE,
+            Field::GeographicMobility => GEOGRAPHIC_MOBILITY,
+            Field::CareerChangeEvents => CAREER_CHANGE_EVENTS,
+            Field::TechnologyAdoption => TECHNOLOGY_ADOPTION,
+            Field::LikelyToChangeOccupation => LIKELY_TO_CHANGE_OCCUPATION,
+        }
+    }
+
+    /// The snake_case identifier used in formulas and on the command line.
+    pub fn key(self) -> &'static str {
+        match self {
+            Field::Age => "age",
+            Field::YearsOfExperience => "years_of_experience",
+            Field::JobSatisfaction => "job_satisfaction",
+            Field::ProfessionalNetworkSize => "professional_network_size",
+            Field::FamilyInfluence => "family_influence",
+            Field::Salary => "salary",
+            Field::EducationLevel => "education_level",
+            Field::IndustryGrowthRate => "industry_growth_rate",
+            Field::WorkLifeBalance => "work_life_balance",
+            Field::JobOpportunities => "job_opportunities",
+            Field::JobSecurity => "job_security",
+            Field::CareerChangeInterest => "career_change_interest",
+            Field::SkillsGap => "skills_gap",
+            Field::MentorshipAvailable => "mentorship_available",
+            Field::Certifications => "certifications",
+            Field::FreelancingExperience => "freelancing_experience",
+            Field::GeographicMobility => "geographic_mobility",
+            Field::CareerChangeEvents => "career_change_events",
+            Field::TechnologyAdoption => "technology_adoption",
+            Field::LikelyToChangeOccupation => "likely_to_change_occupation",
+        }
+    }
+
+    /// Ordered codes or Likert-type scores, for which rank methods are more appropriate.
+    pub fn is_ordinal(self) -> bool {
+        matches!(
+            self,
+            Field::FamilyInfluence
+                | Field::JobSatisfaction
+                | Field::EducationLevel
+                | Field::IndustryGrowthRate
+                | Field::WorkLifeBalance
+                | Field::JobSecurity
+                | Field::SkillsGap
+                | Field::TechnologyAdoption
+        )
+    }
+
+    /// Yes/no answers, read as 0/1.
+    pub fn is_boolean(self) -> bool {
+        matches!(
+            self,
+            Field::CareerChangeInterest
+                | Field::MentorshipAvailable
+                | Field::Certifications
+                | Field::FreelancingExperience
+                | Field::GeographicMobility
+                | Field::LikelyToChangeOccupation
+        )
+    }
+
+    /// Accepts the key or the label, ignoring case, spaces and underscores.
+    pub fn parse(name: &str) -> Result<Field, Box<dyn Error>> {
+        let wanted = normalize_header(&name.replace('_', " "));
+        Field::ALL
+            .into_iter()
+            .find(|f| normalize_header(f.label()) == wanted || normalize_header(&f.key().replace('_', " ")) == wanted)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Field::ALL.iter().map(|f| f.key()).collect();
+                format!("unknown field '{}' (expected one of: {})", name, known.join(", ")).into()
+            })
+    }
+
+    pub fn values(self, individuals: &[Individual]) -> Vec<Option<f64>> {
+        individuals.iter().map(|ind| self.value(ind)).collect()
+    }
+
+    pub fn value(self, individual: &Individual) -> Option<f64> {
+        let flag = |b: Option<bool>| b.map(|b| if b { 1.0 } else { 0.0 });
+        match self {
+            Field::Age => individual.age,
+            Field::YearsOfExperience => individual.years_of_experience,
+            Field::JobSatisfaction => individual.job_satisfaction,
+            Field::ProfessionalNetworkSize => individual.professional_network_size,
+            Field::FamilyInfluence => individual.family_influence,
+            Field::Salary => individual.salary,
+            Field::EducationLevel => individual.education_level,
+            Field::IndustryGrowthRate => individual.industry_growth_rate,
+            Field::WorkLifeBalance => individual.work_life_balance,
+            Field::JobOpportunities => individual.job_opportunities,
+            Field::JobSecurity => individual.job_security,
+            Field::CareerChangeInterest => flag(individual.career_change_interest),
+            Field::SkillsGap => individual.skills_gap,
+            Field::MentorshipAvailable => flag(individual.mentorship_available),
+            Field::Certifications => flag(individual.certifications),
+            Field::FreelancingExperience => flag(individual.freelancing_experience),
+            Field::GeographicMobility => flag(individual.geographic_mobility),
+            Field::CareerChangeEvents => individual.career_change_events,
+            Field::TechnologyAdoption => individual.technology_adoption,
+            Field::LikelyToChangeOccupation => flag(individual.likely_to_change_occupation),
+        }
+    }
+
+    /// Overwrites the field; boolean fields store whether `value` is nonzero.
+    pub fn set(self, individual: &mut Individual, value: Option<f64>) {
+        let flag = value.map(|v| v != 0.0);
+        match self {
+            Field::Age => individual.age = value,
+            Field::YearsOfExperience => individual.years_of_experience = value,
+            Field::JobSatisfaction => individual.job_satisfaction = value,
+            Field::ProfessionalNetworkSize => individual.professional_network_size = value,
+            Field::FamilyInfluence => individual.family_influence = value,
+            Field::Salary => individual.salary = value,
+            Field::EducationLevel => individual.education_level = value,
+            Field::IndustryGrowthRate => individual.industry_growth_rate = value,
+            Field::WorkLifeBalance => individual.work_life_balance = value,
+            Field::JobOpportunities => individual.job_opportunities = value,
+            Field::JobSecurity => individual.job_security = value,
+            Field::CareerChangeInterest => individual.career_change_interest = flag,
+            Field::SkillsGap => individual.skills_gap = value,
+            Field::MentorshipAvailable => individual.mentorship_available = flag,
+            Field::Certifications => individual.certifications = flag,
+            Field::FreelancingExperience => individual.freelancing_experience = flag,
+            Field::GeographicMobility => individual.geographic_mobility = flag,
+            Field::CareerChangeEvents => individual.career_change_events = value,
+            Field::TechnologyAdoption => individual.technology_adoption = value,
+            Field::LikelyToChangeOccupation => individual.likely_to_change_occupation = flag,
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// A categorical field of `Individual`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Category {
+    FieldOfStudy,
+    CurrentOccupation,
+    Gender,
+}
+
+impl Category {
+    pub const ALL: [Category; 3] = [Category::FieldOfStudy, Category::CurrentOccupation, Category::Gender];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::FieldOfStudy => FIELD_OF_STUDY,
+            Category::CurrentOccupation => CURRENT_OCCUPATION,
+            Category::Gender => GENDER,
+        }
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Category::FieldOfStudy => "field_of_study",
+            Category::CurrentOccupation => "current_occupation",
+            Category::Gender => "gender",
+        }
+    }
+
+    /// Accepts the key or the label, ignoring case, spaces and underscores.
+    pub fn parse(name: &str) -> Result<Category, Box<dyn Error>> {
+        let wanted = normalize_header(&name.replace('_', " "));
+        Category::ALL
+            .into_iter()
+            .find(|c| normalize_header(c.label()) == wanted || normalize_header(&c.key().replace('_', " ")) == wanted)
+            .ok_or_else(|| {
+                let known: Vec<&str> = Category::ALL.iter().map(|c| c.key()).collect();
+                format!("unknown category '{}' (expected one of: {})", name, known.join(", ")).into()
+            })
+    }
+
+    pub fn value(self, individual: &Individual) -> Option<&str> {
+        match self {
+            Category::FieldOfStudy => individual.field_of_study.as_deref(),
+            Category::CurrentOccupation => individual.current_occupation.as_deref(),
+            Category::Gender => individual.gender.as_deref(),
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// The core numeric fields as labelled series, in `Field::CORE` order.
+pub fn numeric_columns(individuals: &[Individual]) -> Vec<NamedSeries> {
+    Field::CORE
+        .iter()
+        .map(|f| (f.label().to_string(), f.values(individuals)))
+        .collect()
+}
+
+/// Builds `Individual`s from a table whose schema loads the six core columns
+/// as numbers. The other survey columns are optional, but when loaded they
+/// must have the expected type.
+pub fn individuals_from_table(table: &Table) -> Result<Vec<Individual>, Box<dyn Error>> {
+    let column = |name: &str| {
+        table
+            .numeric(name)
+            .ok_or_else(|| format!("Schema must load '{}' as a numeric or ordinal column", name))
+    };
+    let number = |name: &str| optional_column(table, name, "numeric or ordinal", table.numeric(name));
+    let flag = |name: &str| optional_column(table, name, "boolean", table.boolean(name));
+    let text = |name: &str| optional_column(table, name, "categorical", table.categorical(name));
+
+    let age = column(AGE)?;
+    let years_of_experience = column(YEARS_OF_EXPERIENCE)?;
+    let job_satisfaction = column(JOB_SATISFACTION)?;
+    let professional_network_size = column(PROFESSIONAL_NETWORKS)?;
+    let family_influence = column(FAMILY_INFLUENCE)?;
+    let salary = column(SALARY)?;
+    let field_of_study = text(FIELD_OF_STUDY)?;
+    let current_occupation = text(CURRENT_OCCUPATION)?;
+    let gender = text(GENDER)?;
+    let education_level = number(EDUCATION_LEVEL)?;
+    let industry_growth_rate = number(INDUSTRY_GROWTH_RATE)?;
+    let work_life_balance = number(WORK_LIFE_BALANCE)?;
+    let job_opportunities = number(JOB_OPPORTUNITIES)?;
+    let job_security = number(JOB_SECURITY)?;
+    let career_change_interest = flag(CAREER_CHANGE_INTEREST)?;
+    let skills_gap = number(SKILLS_GAP)?;
+    let mentorship_available = flag(MENTORSHIP_AVAILABLE)?;
+    let certifications = flag(CERTIFICATIONS)?;
+    let freelancing_experience = flag(FREELANCING_EXPERIENCE)?;
+    let geographic_mobility = flag(GEOGRAPHIC_MOBILITY)?;
+    let career_change_events = number(CAREER_CHANGE_EVENTS)?;
+    let technology_adoption = number(TECHNOLOGY_ADOPTION)?;
+    let likely_to_change_occupation = flag(LIKELY_TO_CHANGE_OCCUPATION)?;
+
+    Ok((0..table.len())
+        .map(|r| Individual {
+            id: table.row_ids[r],
+            age: age[r],
+            years_of_experience: years_of_experience[r],
+            job_satisfaction: job_satisfaction[r],
+            professional_network_size: professional_network_size[r],
+            family_influence: family_influence[r],
+            salary: salary[r],
+            field_of_study: field_of_study[r].clone(),
+            current_occupation: current_occupation[r].clone(),
+            gender: gender[r].clone(),
+            education_level: education_level[r],
+            industry_growth_rate: industry_growth_rate[r],
+            work_life_balance: work_life_balance[r],
+            job_opportunities: job_opportunities[r],
+            job_security: job_security[r],
+            career_change_interest: career_change_interest[r],
+            skills_gap: skills_gap[r],
+            mentorship_available: mentorship_available[r],
+            certifications: certifications[r],
+            freelancing_experience: freelancing_experience[r],
+            geographic_mobility: geographic_mobility[r],
+            career_change_events: career_change_events[r],
+            technology_adoption: technology_adoption[r],
+            likely_to_change_occupation: likely_to_change_occupation[r],
+        })
+        .collect())
+}
+
+/// `values` of an optional column: all missing when the schema does not load
+/// it, an error when it loads it with another type (`values` is then `None`).
+fn optional_column<T: Clone>(
+    table: &Table,
+    name: &str,
+    kind: &str,
+    values: Option<Vec<Option<T>>>,
+) -> Result<Vec<Option<T>>, Box<dyn Error>> {
+    match (table.column(name), values) {
+        (None, _) => Ok(vec![None; table.len()]),
+        (Some(_), Some(values)) => Ok(values),
+        (Some(_), None) => Err(format!("Schema must load '{}' as a {} column", name, kind).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::load;
+
+    const CORE: &str = r#"
+[[column]]
+name = "Age"
+type = "numeric"
+
+[[column]]
+name = "Years of Experience"
+type = "numeric"
+
+[[column]]
+name = "Job Satisfaction"
+type = "numeric"
+
+[[column]]
+name = "Professional Networks"
+type = "numeric"
+
+[[column]]
+name = "Family Influence"
+type = "ordinal"
+levels = { None = 0, Low = 1, Medium = 2, High = 3 }
+
+[[column]]
+name = "Salary"
+type = "numeric"
+role = "target"
+"#;
+
+    const OPTIONAL: &str = r#"
+[[column]]
+name = "Gender"
+type = "categorical"
+missing = "keep"
+
+[[column]]
+name = "Mentorship Available"
+type = "boolean"
+missing = "keep"
+
+[[column]]
+name = "Education Level"
+type = "ordinal"
+levels = { "High School" = 1, PhD = 4 }
+"#;
+
+    // Record 1 is dropped for its unknown education level.
+    const CSV: &str = "Age,Years of Experience,Job Satisfaction,Professional Networks,Family Influence,Salary,\
+        Gender,Mentorship Available,Education Level,Certifications\n\
+        30,5,7,3,High,50000,Female,yes,PhD,1\n\
+        41,9,6,2,Low,61000,Male,no,College,0\n\
+        52,20,4,8,None,72000,,0,High School,1\n";
+
+    #[test]
+    fn loaded_columns_fill_typed_fields_and_absent_ones_stay_none() {
+        let (table, _) = load("individuals", &format!("{}{}", CORE, OPTIONAL), CSV).unwrap();
+        let individuals = individuals_from_table(&table).unwrap();
+        assert_eq!(individuals.len(), 2);
+
+        let first = &individuals[0];
+        assert_eq!((first.id, first.age, first.family_influence, first.salary), (0, Some(30.0), Some(3.0), Some(50000.0)));
+        assert_eq!(first.gender.as_deref(), Some("Female"));
+        assert_eq!(first.mentorship_available, Some(true));
+        assert_eq!(first.education_level, Some(4.0));
+
+        let second = &individuals[1];
+        assert_eq!(second.id, 2);
+        assert_eq!((second.gender.clone(), second.mentorship_available), (None, Some(false)));
+        assert_eq!(second.family_influence, Some(0.0));
+
+        // Certifications is in the file but not in the schema.
+        for individual in &individuals {
+            assert_eq!(individual.certifications, None);
+            assert_eq!(individual.field_of_study, None);
+            assert_eq!(individual.likely_to_change_occupation, None);
+            assert_eq!(individual.skills_gap, None);
+        }
+    }
+
+    #[test]
+    fn columns_of_the_wrong_type_are_rejected() {
+        let numeric_flag = format!("{}[[column]]\nname = \"Certifications\"\ntype = \"numeric\"\n", CORE);
+        let (table, _) = load("individuals-flag", &numeric_flag, CSV).unwrap();
+        let error = individuals_from_table(&table).unwrap_err();
+        assert_eq!(error.to_string(), "Schema must load 'Certifications' as a boolean column");
+
+        let categorical_age = CORE.replacen("type = \"numeric\"", "type = \"categorical\"", 1);
+        let (table, _) = load("individuals-age", &categorical_age, CSV).unwrap();
+        let error = individuals_from_table(&table).unwrap_err();
+        assert_eq!(error.to_string(), "Schema must load 'Age' as a numeric or ordinal column");
+    }
+}
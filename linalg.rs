@@ -0,0 +1,233 @@
+//! Small dense linear algebra: just enough for least squares without an external BLAS.
+
+/// Dense row-major matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    pub rows: usize,
+    pub cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix { rows, cols, data: vec![0.0; rows * cols] }
+    }
+
+    /// Builds a matrix from equally long rows.
+    pub fn from_rows(rows: &[Vec<f64>]) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            assert_eq!(row.len(), cols, "all rows must have the same length");
+            data.extend_from_slice(row);
+        }
+        Matrix { rows: rows.len(), cols, data }
+    }
+
+    /// Builds a matrix whose columns are the given equally long vectors.
+    pub fn from_columns(columns: &[Vec<f64>]) -> Self {
+        let rows = columns.first().map_or(0, Vec::len);
+        let mut m = Matrix::zeros(rows, columns.len());
+        for (j, column) in columns.iter().enumerate() {
+            assert_eq!(column.len(), rows, "all columns must have the same length");
+            for (i, &v) in column.iter().enumerate() {
+                m[(i, j)] = v;
+            }
+        }
+        m
+    }
+
+    pub fn column(&self, j: usize) -> Vec<f64> {
+        (0..self.rows).map(|i| self[(i, j)]).collect()
+    }
+
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// The matrix without column `j`.
+    pub fn without_column(&self, j: usize) -> Matrix {
+        let columns: Vec<Vec<f64>> = (0..self.cols).filter(|&c| c != j).map(|c| self.column(c)).collect();
+        Matrix::from_columns(&columns)
+    }
+
+    /// Matrix-vector product.
+    pub fn mul_vec(&self, v: &[f64]) -> Vec<f64> {
+        assert_eq!(v.len(), self.cols, "dimension mismatch");
+        (0..self.rows)
+            .map(|i| self.row(i).iter().zip(v).map(|(a, b)| a * b).sum())
+            .collect()
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+/// Householder QR decomposition of an m x n matrix with m >= n.
+#[derive(Debug, Clone)]
+pub struct Qr {
+    /// Householder vectors below the diagonal, R above it.
+    qr: Matrix,
+    r_diag: Vec<f64>,
+}
+
+impl Qr {
+    pub fn new(a: &Matrix) -> Qr {
+        let (m, n) = (a.rows, a.cols);
+        assert!(m >= n, "QR least squares needs at least as many rows as columns");
+        let mut qr = a.clone();
+        let mut r_diag = vec![0.0; n];
+
+        for k in 0..n {
+            let norm = (k..m).map(|i| qr[(i, k)] * qr[(i, k)]).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let norm = if qr[(k, k)] < 0.0 { -norm } else { norm };
+            for i in k..m {
+                qr[(i, k)] /= norm;
+            }
+            qr[(k, k)] += 1.0;
+
+            for j in k + 1..n {
+                let s = -(k..m).map(|i| qr[(i, k)] * qr[(i, j)]).sum::<f64>() / qr[(k, k)];
+                for i in k..m {
+                    let delta = s * qr[(i, k)];
+                    qr[(i, j)] += delta;
+                }
+            }
+            r_diag[k] = -norm;
+        }
+
+        Qr { qr, r_diag }
+    }
+
+    /// True when no diagonal element of R is negligible relative to the largest.
+    pub fn is_full_rank(&self) -> bool {
+        let largest = self.r_diag.iter().fold(0.0_f64, |a, b| a.max(b.abs()));
+        let tolerance = largest * 1e-10 * self.qr.rows as f64;
+        largest > 0.0 && self.r_diag.iter().all(|d| d.abs() > tolerance)
+    }
+
+    /// Q'y; its first n entries are the sequential "effects" of each column.
+    pub fn qt_mul(&self, y: &[f64]) -> Vec<f64> {
+        let (m, n) = (self.qr.rows, self.qr.cols);
+        let mut out = y.to_vec();
+        for k in 0..n {
+            if self.qr[(k, k)] == 0.0 {
+                continue;
+            }
+            let s = -(k..m).map(|i| self.qr[(i, k)] * out[i]).sum::<f64>() / self.qr[(k, k)];
+            for (i, value) in out.iter_mut().enumerate().take(m).skip(k) {
+                *value += s * self.qr[(i, k)];
+            }
+        }
+        out
+    }
+
+    fn r(&self, i: usize, j: usize) -> f64 {
+        if i == j {
+            self.r_diag[i]
+        } else if i < j {
+            self.qr[(i, j)]
+        } else {
+            0.0
+        }
+    }
+
+    /// Least squares solution of A x = y. Requires full rank.
+    pub fn solve(&self, y: &[f64]) -> Vec<f64> {
+        let n = self.qr.cols;
+        let qty = self.qt_mul(y);
+        let mut x = vec![0.0; n];
+        for k in (0..n).rev() {
+            let tail: f64 = (k + 1..n).map(|j| self.r(k, j) * x[j]).sum();
+            x[k] = (qty[k] - tail) / self.r(k, k);
+        }
+        x
+    }
+
+    /// (A'A)^-1 = R^-1 R^-T, the unscaled covariance of the least squares coefficients.
+    pub fn xtx_inverse(&self) -> Matrix {
+        let n = self.qr.cols;
+        // Invert the upper-triangular R column by column.
+        let mut r_inv = Matrix::zeros(n, n);
+        for j in 0..n {
+            r_inv[(j, j)] = 1.0 / self.r(j, j);
+            for i in (0..j).rev() {
+                let s: f64 = (i + 1..=j).map(|k| self.r(i, k) * r_inv[(k, j)]).sum();
+                r_inv[(i, j)] = -s / self.r(i, i);
+            }
+        }
+        let mut out = Matrix::zeros(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                out[(i, j)] = (i.max(j)..n).map(|k| r_inv[(i, k)] * r_inv[(j, k)]).sum();
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{close, STACKLOSS};
+
+    fn stackloss() -> (Matrix, Vec<f64>) {
+        let rows: Vec<Vec<f64>> = STACKLOSS.iter().map(|r| vec![1.0, r[0], r[1], r[2]]).collect();
+        (Matrix::from_rows(&rows), STACKLOSS.iter().map(|r| r[3]).collect())
+    }
+
+    #[test]
+    fn solve_matches_lm_on_stackloss() {
+        // coef(lm(stack.loss ~ ., stackloss))
+        let (x, y) = stackloss();
+        let beta = Qr::new(&x).solve(&y);
+        for (estimate, expected) in beta.iter().zip([-39.91967442, 0.71564020, 1.29528612, -0.15212252]) {
+            close(*estimate, expected, 1e-7);
+        }
+    }
+
+    #[test]
+    fn xtx_inverse_matches_solve_of_the_normal_equations() {
+        // solve(crossprod(model.matrix(lm(stack.loss ~ ., stackloss))))
+        let (x, _) = stackloss();
+        let inverse = Qr::new(&x).xtx_inverse();
+        let expected = [
+            [13.452726695, 0.027338712, -0.061961118, -0.159355028],
+            [0.027338712, 0.001728874, -0.003470791, -0.000679080],
+            [-0.061961118, -0.003470791, 0.012875424, 0.000000996],
+            [-0.159355028, -0.000679080, 0.000000996, 0.002322167],
+        ];
+        for (i, row) in expected.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                close(inverse[(i, j)], value, 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn collinear_columns_are_not_full_rank() {
+        let (x, _) = stackloss();
+        assert!(Qr::new(&x).is_full_rank());
+
+        // Water temperature in Fahrenheit is a linear function of the intercept and Celsius.
+        let rows: Vec<Vec<f64>> = STACKLOSS.iter().map(|r| vec![1.0, r[1], 32.0 + 1.8 * r[1]]).collect();
+        assert!(!Qr::new(&Matrix::from_rows(&rows)).is_full_rank());
+
+        let constant: Vec<Vec<f64>> = STACKLOSS.iter().map(|r| vec![1.0, r[0], 0.0]).collect();
+        assert!(!Qr::new(&Matrix::from_rows(&constant)).is_full_rank());
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// How many individual issues `print_summary` shows as examples.
+const SAMPLE_ISSUES: usize = 5;
+
+/// Why a cell could not be turned into a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueReason {
+    Missing,
+    NotANumber,
+    UnknownLevel,
+    NotABoolean,
+}
+
+impl fmt::Display for IssueReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            IssueReason::Missing => "missing value",
+            IssueReason::NotANumber => "not a number",
+            IssueReason::UnknownLevel => "unknown level",
+            IssueReason::NotABoolean => "not a boolean",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// One cell that failed to parse.
+#[derive(Debug, Clone)]
+pub struct ParseIssue {
+    /// Zero-based index of the data record (the header is not counted, so this
+    /// is not a file line number), matching `Individual::id`.
+    pub row: usize,
+    pub column: String,
+    pub raw_value: String,
+    pub reason: IssueReason,
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "record index {}, column '{}': {} ('{}')",
+            self.row, self.column, self.reason, self.raw_value
+        )
+    }
+}
+
+/// Outcome of loading a dataset: which rows were kept and why the others were dropped.
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    pub rows_read: usize,
+    pub rows_loaded: usize,
+    pub issues: Vec<ParseIssue>,
+    /// Cells filled by impute or sentinel policies, by column name.
+    pub imputed: BTreeMap<String, usize>,
+    headers: Vec<String>,
+    rejected: Vec<(usize, Vec<String>)>,
+}
+
+impl LoadReport {
+    pub fn new(headers: Vec<String>) -> Self {
+        LoadReport { headers, ..LoadReport::default() }
+    }
+
+    /// Records a dropped row along with every issue found in it.
+    pub fn reject(&mut self, row: usize, record: &csv::StringRecord, issues: Vec<ParseIssue>) {
+        self.rejected.push((row, record.iter().map(str::to_string).collect()));
+        self.issues.extend(issues);
+    }
+
+    pub fn rows_dropped(&self) -> usize {
+        self.rejected.len()
+    }
+
+    /// Number of dropped rows in which each column failed, by column name.
+    pub fn dropped_by_column(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.column.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn print_summary(&self) {
+        eprintln!(
+            "Loaded {} of {} records ({} dropped)",
+            self.rows_loaded,
+            self.rows_read,
+            self.rows_dropped()
+        );
+        for (column, count) in self.dropped_by_column() {
+            eprintln!("  {}: {} row(s)", column, count);
+        }
+        for issue in self.issues.iter().take(SAMPLE_ISSUES) {
+            eprintln!("  e.g. {}", issue);
+        }
+        for (column, count) in &self.imputed {
+            eprintln!("Filled {} missing value(s) in {}", count, column);
+        }
+    }
+
+    /// Writes the dropped rows, unchanged, plus `record_index` (as in `ParseIssue::row`)
+    /// and `issues` columns for cleanup.
+    pub fn write_rejects(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_path(path)?;
+
+        let mut header = vec!["record_index".to_string()];
+        header.extend(self.headers.iter().cloned());
+        header.push("issues".to_string());
+        wtr.write_record(&header)?;
+
+        for (row, fields) in &self.rejected {
+            let issues: Vec<String> = self
+                .issues
+                .iter()
+                .filter(|issue| issue.row == *row)
+                .map(|issue| format!("{}: {}", issue.column, issue.reason))
+                .collect();
+
+            let mut out = vec![row.to_string()];
+            out.extend(fields.iter().cloned());
+            out.push(issues.join("; "));
+            wtr.write_record(&out)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::testing::load;
+
+    const SCHEMA: &str = r#"
+[[column]]
+name = "Age"
+type = "numeric"
+
+[[column]]
+name = "Mentor"
+type = "boolean"
+"#;
+
+    // Records 1 and 3 (file lines 3 and 5) are dropped.
+    const CSV: &str = "Age,Mentor\n30,yes\nold,maybe\n41,no\n,no\n";
+
+    #[test]
+    fn issues_name_zero_based_record_indices() {
+        let (_, report) = load("load-report", SCHEMA, CSV).unwrap();
+        assert_eq!((report.rows_read, report.rows_loaded, report.rows_dropped()), (4, 2, 2));
+        let issues: Vec<String> = report.issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            issues,
+            [
+                "record index 1, column 'Age': not a number ('old')",
+                "record index 1, column 'Mentor': not a boolean ('maybe')",
+                "record index 3, column 'Age': missing value ('')",
+            ]
+        );
+        assert_eq!(report.dropped_by_column().into_iter().collect::<Vec<_>>(), [("Age", 2), ("Mentor", 1)]);
+    }
+
+    #[test]
+    fn rejects_keep_the_original_cells() {
+        let (_, report) = load("load-report-rejects", SCHEMA, CSV).unwrap();
+        let path = std::env::temp_dir().join(format!("finalproject-{}-rejects.csv", std::process::id()));
+        report.write_rejects(&path.to_string_lossy()).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines[0], "record_index,Age,Mentor,issues");
+        assert_eq!(lines[1], "1,old,maybe,Age: not a number; Mentor: not a boolean");
+        assert_eq!(lines[2], "3,,no,Age: missing value");
+        assert_eq!(lines.len(), 3);
+    }
+}
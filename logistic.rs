@@ -0,0 +1,550 @@
+//! Logistic regression of a boolean field, fitted by iteratively reweighted
+//! least squares (Newton's method) with an optional L2 penalty.
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::distributions::{ChiSquared, Continuous, Normal};
+use crate::evaluation::{ConfusionMatrix, DEFAULT_THRESHOLD};
+use crate::formula::Formula;
+use crate::individual::{Field, Individual};
+use crate::linalg::{Matrix, Qr};
+use crate::ols::{Design, CONFIDENCE_LEVEL};
+use crate::tests::TestResult;
+
+const MAX_ITERATIONS: usize = 100;
+/// Relative change in deviance at which IRLS stops, as in R's `glm`.
+const TOLERANCE: f64 = 1e-10;
+/// Lower bound on the IRLS weights p (1 - p), so fitted probabilities of 0 or 1 stay solvable.
+const MIN_WEIGHT: f64 = 1e-10;
+
+/// The logistic function, mapping log-odds to a probability.
+pub fn sigmoid(eta: f64) -> f64 {
+    1.0 / (1.0 + (-eta).exp())
+}
+
+/// ln(1 + e^eta) without overflow.
+fn softplus(eta: f64) -> f64 {
+    eta.max(0.0) + (-eta.abs()).exp().ln_1p()
+}
+
+/// Bernoulli log-likelihood of `y` given linear predictors `eta`.
+fn log_likelihood(eta: &[f64], y: &[f64]) -> f64 {
+    eta.iter().zip(y).map(|(e, v)| v * e - softplus(*e)).sum()
+}
+
+/// Result of one IRLS run.
+#[derive(Debug, Clone)]
+pub struct LogisticFit {
+    pub coefficients: Vec<f64>,
+    /// Inverse of the (penalized) information matrix at the estimate.
+    pub covariance: Matrix,
+    pub log_likelihood: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// QR of sqrt(W) X stacked on sqrt(penalty) times the identity without its
+/// intercept row, so least squares on it is one penalized Newton step.
+fn penalized_qr(x: &Matrix, weights: &[f64], penalty: f64) -> Qr {
+    let mut rows: Vec<Vec<f64>> = (0..x.rows)
+        .map(|i| x.row(i).iter().map(|v| v * weights[i].sqrt()).collect())
+        .collect();
+    if penalty > 0.0 {
+        for j in 1..x.cols {
+            let mut row = vec![0.0; x.cols];
+            row[j] = penalty.sqrt();
+            rows.push(row);
+        }
+    }
+    Qr::new(&Matrix::from_rows(&rows))
+}
+
+/// Maximizes the log-likelihood minus `penalty / 2` times the squared
+/// coefficients other than the intercept.
+fn irls(x: &Matrix, y: &[f64], penalty: f64) -> Result<LogisticFit, Box<dyn Error>> {
+    let mut coefficients = vec![0.0; x.cols];
+    let mut deviance = f64::INFINITY;
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < MAX_ITERATIONS {
+        iterations += 1;
+        let eta = x.mul_vec(&coefficients);
+        let mut weights = Vec::with_capacity(y.len());
+        let mut working = Vec::with_capacity(y.len() + x.cols);
+        for (e, v) in eta.iter().zip(y) {
+            let p = sigmoid(*e);
+            let w = (p * (1.0 - p)).max(MIN_WEIGHT);
+            weights.push(w);
+            working.push(w.sqrt() * (e + (v - p) / w));
+        }
+        if penalty > 0.0 {
+            working.extend(std::iter::repeat_n(0.0, x.cols - 1));
+        }
+        let qr = penalized_qr(x, &weights, penalty);
+        if !qr.is_full_rank() {
+            return Err("design matrix is rank deficient (collinear or constant terms)".into());
+        }
+        coefficients = qr.solve(&working);
+
+        let eta = x.mul_vec(&coefficients);
+        let penalized = -2.0 * log_likelihood(&eta, y)
+            + penalty * coefficients[1..].iter().map(|b| b * b).sum::<f64>();
+        if (penalized - deviance).abs() <= TOLERANCE * (penalized.abs() + 0.1) {
+            converged = true;
+            break;
+        }
+        deviance = penalized;
+    }
+
+    let eta = x.mul_vec(&coefficients);
+    let weights: Vec<f64> = eta
+        .iter()
+        .map(|e| {
+            let p = sigmoid(*e);
+            (p * (1.0 - p)).max(MIN_WEIGHT)
+        })
+        .collect();
+    Ok(LogisticFit {
+        covariance: penalized_qr(x, &weights, penalty).xtx_inverse(),
+        log_likelihood: log_likelihood(&eta, y),
+        coefficients,
+        iterations,
+        converged,
+    })
+}
+
+/// Number of 1s in `y`, failing unless `y` holds both 0s and 1s and nothing else.
+fn count_positives(response: Field, y: &[f64]) -> Result<usize, Box<dyn Error>> {
+    if y.iter().any(|&v| v != 0.0 && v != 1.0) {
+        return Err(format!("response '{}' must take only the values 0 and 1", response.key()).into());
+    }
+    let positives = y.iter().filter(|&&v| v == 1.0).count();
+    if positives == 0 || positives == y.len() {
+        return Err(format!("response '{}' has a single class among the complete cases", response.key()).into());
+    }
+    Ok(positives)
+}
+
+/// Fits the 0/1 `response` values `y` on the columns of `x` (intercept first),
+/// without the tests of [`LogisticRegression::fit`].
+pub fn fit_design(x: &Matrix, y: &[f64], response: Field, penalty: f64) -> Result<LogisticFit, Box<dyn Error>> {
+    if y.len() <= x.cols {
+        return Err(format!("{} observations are too few for {} coefficients", y.len(), x.cols).into());
+    }
+    count_positives(response, y)?;
+    irls(x, y, penalty)
+}
+
+/// A logistic regression coefficient with its Wald and likelihood-ratio inference.
+#[derive(Debug, Clone)]
+pub struct LogisticTerm {
+    pub name: String,
+    /// Change in log-odds per unit of the term.
+    pub estimate: f64,
+    pub std_error: f64,
+    pub z_value: f64,
+    /// Two-sided Wald p-value.
+    pub p_value: f64,
+    /// Wald `CONFIDENCE_LEVEL` interval of the odds ratio exp(estimate).
+    pub odds_ratio_ci: (f64, f64),
+    /// Likelihood-ratio test against the model without this term; `None` for the intercept.
+    pub likelihood_ratio: Option<TestResult>,
+}
+
+impl LogisticTerm {
+    /// Wald inference for `estimate` with standard error `std_error`.
+    pub fn new(name: String, estimate: f64, std_error: f64, likelihood_ratio: Option<TestResult>) -> Self {
+        let normal = Normal;
+        let z_crit = normal.quantile(0.5 + CONFIDENCE_LEVEL / 2.0);
+        let z_value = estimate / std_error;
+        LogisticTerm {
+            name,
+            estimate,
+            std_error,
+            z_value,
+            p_value: normal.two_sided_p(z_value),
+            odds_ratio_ci: ((estimate - z_crit * std_error).exp(), (estimate + z_crit * std_error).exp()),
+            likelihood_ratio,
+        }
+    }
+
+    pub fn odds_ratio(&self) -> f64 {
+        self.estimate.exp()
+    }
+}
+
+/// Coefficient table of a logistic model with Wald inference and odds ratios;
+/// the likelihood-ratio columns are blank where a term has no test.
+pub fn render_terms(terms: &[LogisticTerm]) -> String {
+    let mut out = String::new();
+    let width = terms.iter().map(|t| t.name.len()).max().unwrap_or(0).max(9);
+    let ci = format!("{:.0}% CI", CONFIDENCE_LEVEL * 100.0);
+    writeln!(
+        out,
+        "{:<width$} {:>12} {:>11} {:>8} {:>9} {:>11} {:>23} {:>9} {:>10}",
+        "Term", "Estimate", "Std. Error", "z value", "Pr(>|z|)", "Odds ratio", ci, "LR chisq", "Pr(>Chi)"
+    )
+    .unwrap();
+    for term in terms {
+        let (lr, lr_p) = term
+            .likelihood_ratio
+            .as_ref()
+            .map_or((String::new(), String::new()), |t| {
+                (format!("{:.3}", t.statistic), format!("{:.4}", t.p_value))
+            });
+        writeln!(
+            out,
+            "{:<width$} {:>12.6} {:>11.6} {:>8.3} {:>9.4} {:>11.4} {:>23} {:>9} {:>10}",
+            term.name,
+            term.estimate,
+            term.std_error,
+            term.z_value,
+            term.p_value,
+            term.odds_ratio(),
+            format!("[{:.4}, {:.4}]", term.odds_ratio_ci.0, term.odds_ratio_ci.1),
+            lr,
+            lr_p
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Maximum likelihood fit of P(response = 1) = 1 / (1 + exp(-x'b)) for a
+/// `Formula` whose response takes the values 0 and 1.
+///
+/// With an L2 `penalty`, standard errors come from the penalized information
+/// matrix and the likelihood-based statistics use the unpenalized
+/// log-likelihood at the penalized estimate. The penalty is not scale-free,
+/// so terms on large scales (salary) are barely shrunk.
+#[derive(Debug, Clone)]
+pub struct LogisticRegression {
+    pub formula: Formula,
+    pub penalty: f64,
+    /// Complete cases used.
+    pub n: usize,
+    /// Cases with response 1.
+    pub positives: usize,
+    /// Intercept first, then the formula terms in order.
+    pub terms: Vec<LogisticTerm>,
+    pub iterations: usize,
+    /// False when IRLS hit its iteration limit, usually because the classes are separable.
+    pub converged: bool,
+    pub log_likelihood: f64,
+    /// Log-likelihood of the intercept-only model.
+    pub null_log_likelihood: f64,
+    /// Likelihood-ratio test of every term jointly.
+    pub likelihood_ratio: TestResult,
+    /// Wald test of every term jointly.
+    pub wald: TestResult,
+    /// `Individual::id` of each complete case.
+    pub ids: Vec<usize>,
+    /// Observed 0/1 responses of the complete cases.
+    pub y: Vec<f64>,
+    /// Fitted probabilities of the complete cases.
+    pub fitted: Vec<f64>,
+}
+
+impl LogisticRegression {
+    pub fn fit(individuals: &[Individual], formula: &Formula, penalty: f64) -> Result<LogisticRegression, Box<dyn Error>> {
+        if !(penalty >= 0.0 && penalty.is_finite()) {
+            return Err(format!("L2 penalty must be a non-negative number, got {}", penalty).into());
+        }
+        let Design { ids, x, y } = Design::new(individuals, formula)?;
+        let n = y.len();
+        let positives = count_positives(formula.response, &y)?;
+
+        let full = irls(&x, &y, penalty)?;
+        let rate = positives as f64 / n as f64;
+        let null_log_likelihood = n as f64 * (rate * rate.ln() + (1.0 - rate) * (1.0 - rate).ln());
+
+        let mut terms = Vec::with_capacity(x.cols);
+        for (j, &estimate) in full.coefficients.iter().enumerate() {
+            let (name, likelihood_ratio) = if j == 0 {
+                ("(Intercept)".to_string(), None)
+            } else {
+                let reduced = irls(&x.without_column(j), &y, penalty)?;
+                let statistic = (2.0 * (full.log_likelihood - reduced.log_likelihood)).max(0.0);
+                let test = TestResult {
+                    test: "Likelihood ratio",
+                    statistic,
+                    df: Some(1.0),
+                    p_value: ChiSquared::new(1.0).sf(statistic),
+                    effect_size: None,
+                    n,
+                };
+                (formula.terms[j - 1].to_string(), Some(test))
+            };
+            terms.push(LogisticTerm::new(name, estimate, full.covariance[(j, j)].sqrt(), likelihood_ratio));
+        }
+
+        let df = (x.cols - 1) as f64;
+        let lr = (2.0 * (full.log_likelihood - null_log_likelihood)).max(0.0);
+        let likelihood_ratio = TestResult {
+            test: "Likelihood ratio",
+            statistic: lr,
+            df: Some(df),
+            p_value: ChiSquared::new(df).sf(lr),
+            effect_size: None,
+            n,
+        };
+
+        // b' V^-1 b over the slopes, solving V z = b with the square QR
+        let slopes = &full.coefficients[1..];
+        let block: Vec<Vec<f64>> = (1..x.cols)
+            .map(|i| (1..x.cols).map(|j| full.covariance[(i, j)]).collect())
+            .collect();
+        let solved = Qr::new(&Matrix::from_rows(&block)).solve(slopes);
+        let w = slopes.iter().zip(&solved).map(|(a, b)| a * b).sum::<f64>();
+        let wald = TestResult {
+            test: "Wald",
+            statistic: w,
+            df: Some(df),
+            p_value: ChiSquared::new(df).sf(w),
+            effect_size: None,
+            n,
+        };
+
+        let fitted = x.mul_vec(&full.coefficients).into_iter().map(sigmoid).collect();
+        Ok(LogisticRegression {
+            formula: formula.clone(),
+            penalty,
+            n,
+            positives,
+            terms,
+            iterations: full.iterations,
+            converged: full.converged,
+            log_likelihood: full.log_likelihood,
+            null_log_likelihood,
+            likelihood_ratio,
+            wald,
+            ids,
+            y,
+            fitted,
+        })
+    }
+
+    pub fn deviance(&self) -> f64 {
+        -2.0 * self.log_likelihood
+    }
+
+    pub fn null_deviance(&self) -> f64 {
+        -2.0 * self.null_log_likelihood
+    }
+
+    pub fn aic(&self) -> f64 {
+        self.deviance() + 2.0 * self.terms.len() as f64
+    }
+
+    /// 1 - lnL / lnL0.
+    pub fn mcfadden_r_squared(&self) -> f64 {
+        1.0 - self.log_likelihood / self.null_log_likelihood
+    }
+
+    /// 1 - (L0 / L)^(2/n).
+    pub fn cox_snell_r_squared(&self) -> f64 {
+        1.0 - (2.0 * (self.null_log_likelihood - self.log_likelihood) / self.n as f64).exp()
+    }
+
+    /// Cox-Snell rescaled by its maximum, 1 - L0^(2/n), to reach 1.
+    pub fn nagelkerke_r_squared(&self) -> f64 {
+        self.cox_snell_r_squared() / (1.0 - (2.0 * self.null_log_likelihood / self.n as f64).exp())
+    }
+
+    /// Fitted probability per `Individual::id`, for [`crate::evaluation::Evaluation`].
+    pub fn predictions(&self) -> Vec<(usize, f64)> {
+        self.ids.iter().copied().zip(self.fitted.iter().copied()).collect()
+    }
+
+    pub fn confusion(&self, threshold: f64) -> ConfusionMatrix {
+        ConfusionMatrix::new(&self.fitted, &self.y, threshold)
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "Logistic model: {}", self.formula).unwrap();
+        writeln!(
+            out,
+            "Observations: {} ({} with {} = 1), L2 penalty: {}",
+            self.n,
+            self.positives,
+            self.formula.response.key(),
+            self.penalty
+        )
+        .unwrap();
+        if !self.converged {
+            writeln!(
+                out,
+                "Warning: IRLS did not converge in {} iterations; the classes may be separable",
+                self.iterations
+            )
+            .unwrap();
+        }
+        write!(out, "{}", render_terms(&self.terms)).unwrap();
+        writeln!(
+            out,
+            "Null deviance: {:.3}, Residual deviance: {:.3} on {} DF, AIC: {:.3}, IRLS iterations: {}",
+            self.null_deviance(),
+            self.deviance(),
+            self.n - self.terms.len(),
+            self.aic(),
+            self.iterations
+        )
+        .unwrap();
+        writeln!(out, "{}", self.likelihood_ratio).unwrap();
+        writeln!(out, "{}", self.wald).unwrap();
+        writeln!(
+            out,
+            "Pseudo R-squared: McFadden {:.4}, Cox-Snell {:.4}, Nagelkerke {:.4}",
+            self.mcfadden_r_squared(),
+            self.cox_snell_r_squared(),
+            self.nagelkerke_r_squared()
+        )
+        .unwrap();
+        write!(out, "{}", self.confusion(DEFAULT_THRESHOLD).render()).unwrap();
+        out
+    }
+
+    /// Column names matching [`LogisticRegression::write_rows`].
+    pub fn csv_header() -> [&'static str; 10] {
+        [
+            "term",
+            "estimate",
+            "std_error",
+            "z_value",
+            "p_value",
+            "odds_ratio",
+            "odds_ratio_lower",
+            "odds_ratio_upper",
+            "lr_statistic",
+            "lr_p_value",
+        ]
+    }
+
+    /// One row per coefficient, intercept first.
+    pub fn write_rows<W: std::io::Write>(&self, wtr: &mut csv::Writer<W>) -> Result<(), Box<dyn Error>> {
+        for term in &self.terms {
+            let (lr, lr_p) = term
+                .likelihood_ratio
+                .as_ref()
+                .map_or((String::new(), String::new()), |t| (t.statistic.to_string(), t.p_value.to_string()));
+            wtr.write_record([
+                term.name.clone(),
+                term.estimate.to_string(),
+                term.std_error.to_string(),
+                term.z_value.to_string(),
+                term.p_value.to_string(),
+                term.odds_ratio().to_string(),
+                term.odds_ratio_ci.0.to_string(),
+                term.odds_ratio_ci.1.to_string(),
+                lr,
+                lr_p,
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    /// Horsepower, weight and manual transmission of R's `mtcars`.
+    const MTCARS: [(f64, f64, bool); 32] = [
+        (110.0, 2.620, true),
+        (110.0, 2.875, true),
+        (93.0, 2.320, true),
+        (110.0, 3.215, false),
+        (175.0, 3.440, false),
+        (105.0, 3.460, false),
+        (245.0, 3.570, false),
+        (62.0, 3.190, false),
+        (95.0, 3.150, false),
+        (123.0, 3.440, false),
+        (123.0, 3.440, false),
+        (180.0, 4.070, false),
+        (180.0, 3.730, false),
+        (180.0, 3.780, false),
+        (205.0, 5.250, false),
+        (215.0, 5.424, false),
+        (230.0, 5.345, false),
+        (66.0, 2.200, true),
+        (52.0, 1.615, true),
+        (65.0, 1.835, true),
+        (97.0, 2.465, false),
+        (150.0, 3.520, false),
+        (150.0, 3.435, false),
+        (245.0, 3.840, false),
+        (175.0, 3.845, false),
+        (66.0, 1.935, true),
+        (91.0, 2.140, true),
+        (113.0, 1.513, true),
+        (264.0, 3.170, true),
+        (175.0, 2.770, true),
+        (335.0, 3.570, true),
+        (109.0, 2.780, true),
+    ];
+
+    /// `am ~ hp + wt`, with horsepower as age and weight as years of experience.
+    fn fit(penalty: f64) -> Result<LogisticRegression, Box<dyn Error>> {
+        let individuals: Vec<Individual> = MTCARS
+            .iter()
+            .enumerate()
+            .map(|(id, &(hp, wt, am))| Individual {
+                id,
+                age: Some(hp),
+                years_of_experience: Some(wt),
+                likely_to_change_occupation: Some(am),
+                ..Default::default()
+            })
+            .collect();
+        let formula = Formula::parse("likely_to_change_occupation ~ age + years_of_experience").unwrap();
+        LogisticRegression::fit(&individuals, &formula, penalty)
+    }
+
+    #[test]
+    fn irls_matches_glm() {
+        // glm(am ~ hp + wt, binomial, mtcars)
+        let model = fit(0.0).unwrap();
+        assert!(model.converged);
+        let expected = [(18.86630, 7.44356), (0.03626, 0.01773), (-8.08348, 3.06868)];
+        for (term, (estimate, std_error)) in model.terms.iter().zip(expected) {
+            close(term.estimate, estimate, 1e-5);
+            close(term.std_error, std_error, 1e-5);
+        }
+        close(model.null_deviance(), 43.22973, 1e-5);
+        close(model.deviance(), 10.05911, 1e-5);
+        close(model.aic(), 16.05911, 1e-5);
+        assert_eq!((model.n, model.positives), (32, 13));
+    }
+
+    #[test]
+    fn likelihood_ratio_tests_match_nested_glm_fits() {
+        // Deviances of am ~ wt (19.17608) and am ~ hp (41.22757) less that of am ~ hp + wt
+        let model = fit(0.0).unwrap();
+        close(model.terms[1].likelihood_ratio.as_ref().unwrap().statistic, 19.17608 - 10.05911, 1e-4);
+        close(model.terms[2].likelihood_ratio.as_ref().unwrap().statistic, 41.22757 - 10.05911, 1e-4);
+        close(model.likelihood_ratio.statistic, 43.22973 - 10.05911, 1e-4);
+        assert!(model.terms[0].likelihood_ratio.is_none());
+        close(model.mcfadden_r_squared(), 1.0 - 10.05911 / 43.22973, 1e-6);
+    }
+
+    #[test]
+    fn l2_penalty_shrinks_the_slopes() {
+        let plain = fit(0.0).unwrap();
+        let shrunk = fit(1.0).unwrap();
+        assert!(shrunk.terms[2].estimate.abs() < plain.terms[2].estimate.abs());
+        assert!(shrunk.log_likelihood < plain.log_likelihood);
+        assert!(fit(-1.0).is_err());
+    }
+
+    #[test]
+    fn sigmoid_is_stable_in_both_tails() {
+        assert_eq!(sigmoid(0.0), 0.5);
+        assert!(sigmoid(-800.0) >= 0.0 && sigmoid(800.0) == 1.0);
+        close(softplus(800.0), 800.0, 1e-12);
+        close(softplus(-800.0), 0.0, 1e-12);
+    }
+}
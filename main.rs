@@ -1,196 +1,1028 @@
-use csv;
+mod anova;
+mod bootstrap;
+mod cli;
+mod columns;
+mod correction;
+mod correlation;
+mod crossval;
+mod diagnostics;
+mod distributions;
+mod encoders;
+mod estimator;
+mod evaluation;
+mod features;
+mod formula;
+mod groupby;
+mod individual;
+mod linalg;
+mod load_report;
+mod logistic;
+mod missing;
+mod ols;
+mod outliers;
+mod permutation;
+mod rng;
+mod robust;
+mod schema;
+mod summary;
+mod table;
+mod tests;
+#[cfg(test)]
+mod testing;
+
 use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+use anova::GroupComparison;
+use bootstrap::{coefficient_intervals, correlation_intervals, Bootstrap, BootstrapEstimate, DEFAULT_RESAMPLES};
+use cli::{Command, Options, OutputFormat, Pivot, HELP};
+use columns::{FAMILY_INFLUENCE, FIELD_OF_STUDY, GENDER};
+use correction::Correction;
+use correlation::{all_methods, CorrelationMatrix, CorrelationMethod};
+use crossval::{CrossValidation, FitPredict, Scheme, Task, DEFAULT_FOLDS};
+use diagnostics::Diagnostics;
+use estimator::{Learner, Pipeline};
+use evaluation::Evaluation;
+use features::{FeatureMatrixBuilder, Scaling};
+use formula::Formula;
+use groupby::{GroupBy, GroupStat, PivotTable, MISSING_GROUP};
+use individual::{individuals_from_table, numeric_columns, Category, Field, Individual};
+use logistic::LogisticRegression;
+use missing::pairwise_complete;
+use ols::{MultipleRegression, SimpleRegression};
+use outliers::{OutlierMethod, OutlierReport, Treatment};
+use permutation::{correlation_tests, Permutation, DEFAULT_PERMUTATIONS};
+use rng::DEFAULT_SEED;
+use robust::{fit_line, render_comparison, RegressionMethod};
+use schema::{Encoding, Role, Schema};
+use summary::{Summary, DEFAULT_PERCENTILES};
+use table::{load_table, NamedSeries, Table};
+use tests::{
+    chi_square_independence, fisher_exact, mann_whitney_u, paired_t_test, student_t_test, welch_t_test, ContingencyTable,
+    TestResult,
+};
+
+const SCHEMA_PATH: &str = "career_schema.toml";
+
+/// Loads `--schema`, else `SCHEMA_PATH` if present, else the built-in career
+/// schema, then applies any `--alias` options.
+fn load_schema(options: &Options) -> Result<Schema, Box<dyn Error>> {
+    let mut schema = match &options.schema {
+        Some(path) => Schema::from_path(path)?,
+        None if Path::new(SCHEMA_PATH).exists() => Schema::from_path(SCHEMA_PATH)?,
+        None => Schema::default(),
+    };
+    for (name, alias) in &options.aliases {
+        schema.add_alias(name, alias)?;
+    }
+    Ok(schema)
+}
+
+/// Flags unusual values of the continuous fields; the codes and scores of the
+/// ordinal fields are bounded, so they are left out.
+fn perform_outlier_screen(individuals: &[Individual]) -> Result<(), Box<dyn Error>> {
+    println!("\n--- Outliers ---");
+    let fields = [Field::Age, Field::YearsOfExperience, Field::ProfessionalNetworkSize, Field::Salary];
+    let report = OutlierReport::detect(individuals, &fields, &OutlierMethod::ALL)?;
+    print!("{}", report.render(Some(10)));
+    Ok(())
+}
+
+/// The individuals the correlation analyses see: as loaded, or with the
+/// `--outliers` flags excluded or winsorized when `--treat` is given.
+fn screen_outliers(individuals: &[Individual], options: &Options) -> Result<Vec<Individual>, Box<dyn Error>> {
+    let Some(treatment) = options.treatment else {
+        return Ok(individuals.to_vec());
+    };
+    let report = OutlierReport::detect(individuals, &options.columns, &options.outlier_methods)?;
+    let screened = report.apply(treatment, individuals);
+    let action = match treatment {
+        Treatment::Exclude => "excluded",
+        Treatment::Winsorize => "winsorized",
+    };
+    eprintln!(
+        "Outliers: {} of {} individuals flagged and {} before the correlation analysis",
+        report.by_id().len(),
+        individuals.len(),
+        action
+    );
+    Ok(screened)
+}
+
+fn perform_correlation_analysis(individuals: &[Individual]) -> Result<(), Box<dyn Error>> {
+    let columns = numeric_columns(individuals);
+    let mut analyses = Vec::new();
+    for (i, &x_field) in Field::CORE.iter().enumerate() {
+        for &y_field in &Field::CORE[i + 1..] {
+            analyses.push((x_field, y_field));
+        }
+    }
+
+    println!("\n--- Correlation Analyses ---");
+    let permutation = Permutation::new(DEFAULT_PERMUTATIONS, DEFAULT_SEED);
 
-#[derive(Debug, Clone)]
-struct Individual {
-    id: usize,
-    age: f64,
-    years_of_experience: f64,
-    job_satisfaction: f64,
-    professional_network_size: f64,
-    family_influence: f64, // Ordinal encoding: Low → 1, Medium → 2, High → 3
-    salary: f64,
-}
-
-fn read_dataset(file_path: &str) -> Result<Vec<Individual>, Box<dyn Error>> {
-    let mut individuals = Vec::new();
-    let mut rdr = csv::Reader::from_path(file_path)?;
-
-    for (i, result) in rdr.records().enumerate() {
-        let record = result?;
-
-        // Ordinal encoding for Family Influence
-        let family_influence = match record[14].trim() { // Assuming index 15 for Family Influence
-            "Low" => Ok(1.0),
-            "Medium" => Ok(2.0),
-            "High" => Ok(3.0),
-            _ => Err("Invalid Family Influence value"),
+    for (x_field, y_field) in analyses {
+        let (x, y) = pairwise_complete(&x_field.values(individuals), &y_field.values(individuals));
+        println!("\n{} vs {}:", x_field, y_field);
+        let fit = match SimpleRegression::fit(&x, &y) {
+            Ok(fit) => fit,
+            Err(e) => {
+                println!("Skipped: {}", e);
+                continue;
+            }
         };
+        fit.print();
+
+        let correlation = fit.correlation;
+        if correlation.abs() < 0.3 {
+            println!("Weak correlation");
+        } else if correlation.abs() < 0.7 {
+            println!("Moderate correlation");
+        } else {
+            println!("Strong correlation");
+        }
+
+        let tests: Vec<String> = all_methods(&x, &y)
+            .into_iter()
+            .map(|(method, test)| match test {
+                Some(t) => format!("{} = {:.4} (p = {:.4})", method.statistic_name(), t.estimate, t.p_value),
+                None => format!("{} = n/a", method.statistic_name()),
+            })
+            .collect();
+        println!("{}", tests.join(", "));
+        if let Some(result) = permutation.correlation(CorrelationMethod::Pearson, &x, &y) {
+            println!("Permutation test of r: p = {:.4} ({})", result.p_value, result.describe());
+        }
+        for field in [x_field, y_field].into_iter().filter(|f| f.is_ordinal()) {
+            println!("Note: {} is ordinal; prefer the rank correlations", field);
+        }
+    }
+
+    println!("\n--- Correlation Matrix ---");
+    let matrix = CorrelationMatrix::compute(&columns, CorrelationMethod::Pearson);
+    print!("{}", matrix.render(Correction::Holm));
+
+    // Salary and network size are skewed, so check the analytic p-values against resampling
+    let bootstrap = Bootstrap::new(DEFAULT_RESAMPLES, DEFAULT_SEED);
+    let intervals = correlation_intervals(individuals, &Field::CORE, CorrelationMethod::Pearson, &bootstrap)?;
+    print!("{}", bootstrap::render("\nBootstrap Pearson correlations", &intervals, &bootstrap));
+
+    println!("\n--- Descriptive Statistics ---");
+    for (name, values) in selected_columns(individuals, &Field::ALL) {
+        if let Some(summary) = Summary::from_column(&name, &values) {
+            print!("{}", summary.render(&DEFAULT_PERCENTILES));
+        }
+    }
 
-        // Parse other fields
-        match (
-            record[2].trim().parse::<f64>(),     // Age (index 2)
-            record[4].trim().parse::<f64>(),     // Years of Experience (index 4)
-            record[7].trim().parse::<f64>(),     // Job Satisfaction (index 7)
-            record[19].trim().parse::<f64>(),    // Professional Network Size (index 19)
-            family_influence,
-            record[10].trim().parse::<f64>()     // Salary (index 11)
-        ) {
-            (
-                Ok(age),
-                Ok(years_of_experience),
-                Ok(job_satisfaction),
-                Ok(professional_network_size),
-                Ok(family_influence),
-                Ok(salary),
-            ) => {
-                individuals.push(Individual {
-                    id: i,
-                    age,
-                    years_of_experience,
-                    job_satisfaction,
-                    professional_network_size,
-                    family_influence,
-                    salary,
-                });
+    Ok(())
+}
+
+/// Fits salary on every loaded feature jointly, then a model with
+/// a curved experience effect and an age/satisfaction interaction, and checks
+/// each fit's residuals.
+fn perform_multiple_regression(table: &Table, individuals: &[Individual]) {
+    println!("\n--- Multiple Regression ---");
+    let formulas = [
+        Formula::all_features(Field::Salary, table),
+        Formula::parse("salary ~ years_of_experience + years_of_experience^2 + age*job_satisfaction"),
+    ];
+    for formula in formulas {
+        println!();
+        let formula = match formula {
+            Ok(formula) => formula,
+            Err(e) => {
+                println!("Skipped: {}", e);
+                continue;
             }
-            _ => {
-                eprintln!("Warning: Could not parse data for record {}", i);
+        };
+        match MultipleRegression::fit(individuals, &formula) {
+            Ok(fit) => print!("{}", fit.render()),
+            Err(e) => {
+                println!("Skipped: {}", e);
+                continue;
             }
         }
+        println!();
+        match Diagnostics::compute(individuals, &formula) {
+            Ok(diagnostics) => print!("{}", diagnostics.render()),
+            Err(e) => println!("Diagnostics skipped: {}", e),
+        }
+    }
+}
+
+/// A few extreme salaries pull the least squares line, so refit salary on
+/// experience with every robust estimator.
+fn perform_robust_regression(individuals: &[Individual]) {
+    println!("\n--- Robust Regression ---\n");
+    let (x, y) = pairwise_complete(
+        &Field::YearsOfExperience.values(individuals),
+        &Field::Salary.values(individuals),
+    );
+    let mut fits = Vec::new();
+    for method in RegressionMethod::ALL {
+        match fit_line(method, &x, &y, DEFAULT_SEED) {
+            Ok(fit) => fits.push(fit),
+            Err(e) => println!("{} skipped: {}", method, e),
+        }
+    }
+    print!("{}", render_comparison("Salary vs Years of Experience", &fits));
+}
+
+/// Who expects to change occupation, from every loaded feature.
+fn perform_logistic_regression(table: &Table, individuals: &[Individual]) {
+    println!("\n--- Logistic Regression ---\n");
+    let fit = match Formula::all_features(Field::LikelyToChangeOccupation, table)
+        .and_then(|formula| LogisticRegression::fit(individuals, &formula, 0.0))
+    {
+        Ok(fit) => fit,
+        Err(e) => {
+            println!("Skipped: {}", e);
+            return;
+        }
+    };
+    print!("{}", fit.render());
+    println!("\nIn-sample fitted probabilities:");
+    match Evaluation::for_individuals(&fit.predictions(), individuals, fit.formula.response) {
+        Ok(evaluation) => print!("{}", evaluation.render()),
+        Err(e) => println!("Evaluation skipped: {}", e),
     }
-    Ok(individuals)
 }
 
-fn calculate_linear_regression(x: &[f64], y: &[f64]) -> (f64, f64, f64, f64) {
-    assert_eq!(x.len(), y.len(), "Input vectors must be of equal length");
-    let n = x.len() as f64;
+/// Out-of-sample fit of the salary and career change models on every loaded
+/// feature, categorical ones encoded as the schema says.
+fn perform_cross_validation(table: &Table, individuals: &[Individual]) {
+    println!("\n--- Cross-Validation ---");
+    let runs = [
+        (Learner::Linear(RegressionMethod::Ols), Scheme::KFold(DEFAULT_FOLDS), Scaling::None),
+        (Learner::Logistic, Scheme::Stratified(DEFAULT_FOLDS), Scaling::Standardize),
+    ];
+    let categories: Vec<Category> = Category::ALL
+        .into_iter()
+        .filter(|c| table.column(c.label()).is_some_and(|c| c.spec.role == Role::Feature))
+        .collect();
+    for (learner, scheme, scaling) in runs {
+        println!();
+        let formula = match Formula::all_features(learner.default_response(), table) {
+            Ok(formula) => formula,
+            Err(e) => {
+                println!("{} skipped: {}", learner, e);
+                continue;
+            }
+        };
+        let features = feature_builder(table, &formula, &categories, scaling);
+        let mut pipeline = Pipeline::new(features, learner.build(DEFAULT_SEED, 0.0));
+        let cv = CrossValidation { scheme, repeats: 1, seed: DEFAULT_SEED };
+        match cv.run(&mut pipeline, individuals) {
+            Ok(result) => print!("{}", result.render(&pipeline.to_string())),
+            Err(e) => println!("{} skipped: {}", learner, e),
+        }
+    }
+}
 
-    let mean_x: f64 = x.iter().sum::<f64>() / n;
-    let mean_y: f64 = y.iter().sum::<f64>() / n;
+/// Features of `formula` plus `categories`, each encoded as the schema says
+/// (one-hot when it gives no encoding).
+fn feature_builder(table: &Table, formula: &Formula, categories: &[Category], scaling: Scaling) -> FeatureMatrixBuilder {
+    categories.iter().fold(FeatureMatrixBuilder::new(formula).scaling(scaling), |builder, &category| {
+        let encoding = table
+            .column(category.label())
+            .and_then(|c| c.spec.encoding.clone())
+            .unwrap_or(Encoding::OneHot { reference: None });
+        builder.category(category, encoding)
+    })
+}
 
-    let mut var_x = 0.0;
-    let mut cov_xy = 0.0;
+/// Compares salary, satisfaction and network size across family influence levels.
+fn perform_group_analysis(table: &Table, individuals: &[Individual]) -> Result<(), Box<dyn Error>> {
+    println!("\n--- Family Influence Groups ---");
+    let groups = GroupBy::from_table(table, individuals, FAMILY_INFLUENCE)?;
+    let fields = [Field::Salary, Field::JobSatisfaction, Field::ProfessionalNetworkSize];
+    print!("{}", groups.summary_pivot(&fields, &[GroupStat::Mean, GroupStat::Median, GroupStat::StdDev]).render());
+    println!();
+    print!("{}", groups.correlation_pivot(&fields, CorrelationMethod::Spearman, Correction::Holm).render());
+    println!();
+    print!("{}", groups.regression_pivot(&Formula::parse("salary ~ years_of_experience")?).render());
 
-    for i in 0..x.len() {
-        var_x += (x[i] - mean_x).powi(2);
-        cov_xy += (x[i] - mean_x) * (y[i] - mean_y);
+    // Family influence codes are ordinal, not equally spaced: compare the levels directly
+    let permutation = Permutation::new(DEFAULT_PERMUTATIONS, DEFAULT_SEED);
+    for field in fields {
+        println!();
+        match GroupComparison::compute(field.label(), &groups.by, &groups.samples(field), Correction::Holm) {
+            Ok(comparison) => print!("{}", comparison.render()),
+            Err(e) => println!("{}: skipped ({})", field, e),
+        }
+        if let Ok(result) = permutation.group_difference(&groups.samples(field)) {
+            println!("Permutation F test: p = {:.4} ({})", result.p_value, result.describe());
+        }
     }
 
-    var_x /= n;
+    Ok(())
+}
 
-    let slope = cov_xy / (var_x * (n - 1.0));
-    let intercept = mean_y - slope * mean_x;
+/// Salary by gender, paired satisfaction against network size, and the
+/// association between gender and field of study.
+fn perform_hypothesis_tests(table: &Table, individuals: &[Individual]) {
+    println!("\n--- Hypothesis Tests ---");
+    match GroupBy::from_table(table, individuals, GENDER) {
+        Ok(gender) => {
+            let samples = gender.samples(Field::Salary);
+            if let [(a_name, a), (b_name, b)] = samples.as_slice() {
+                println!("Salary, {} vs {}:", a_name, b_name);
+                for result in two_sample_tests(a, b) {
+                    print_test(result);
+                }
+            }
+        }
+        Err(e) => println!("Salary by gender skipped: {}", e),
+    }
 
-    let mut r_numerator = 0.0;
-    let mut r_denomx = 0.0;
-    let mut r_denomy = 0.0;
+    println!("\nJob Satisfaction vs Professional Network Size (same individuals):");
+    let (x, y) = pairwise_complete(
+        &Field::JobSatisfaction.values(individuals),
+        &Field::ProfessionalNetworkSize.values(individuals),
+    );
+    print_test(paired_t_test(&x, &y));
 
-    for i in 0..x.len() {
-        let dx = x[i] - mean_x;
-        let dy = y[i] - mean_y;
-        r_numerator += dx * dy;
-        r_denomx += dx.powi(2);
-        r_denomy += dy.powi(2);
+    if let Some(contingency) = contingency_table(table, GENDER, FIELD_OF_STUDY) {
+        println!();
+        print!("{}", contingency.render());
+        print_test(chi_square_independence(&contingency));
     }
+}
 
-    let correlation = r_numerator / (r_denomx * r_denomy).sqrt();
-    let r_squared = correlation.powi(2);
+fn two_sample_tests(a: &[f64], b: &[f64]) -> Vec<Result<TestResult, Box<dyn Error>>> {
+    vec![student_t_test(a, b), welch_t_test(a, b), mann_whitney_u(a, b)]
+}
 
-    (slope, intercept, correlation, r_squared)
+fn print_test(result: Result<TestResult, Box<dyn Error>>) {
+    match result {
+        Ok(result) => println!("  {}", result),
+        Err(e) => println!("  Skipped: {}", e),
+    }
 }
 
-fn perform_correlation_analysis(individuals: &[Individual]) -> Result<(), Box<dyn Error>> {
-    let analyses = vec![
-        ("Age vs Years of Experience", 
-         individuals.iter().map(|ind| ind.age).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.years_of_experience).collect::<Vec<f64>>()),
-        
-        ("Age vs Job Satisfaction", 
-         individuals.iter().map(|ind| ind.age).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.job_satisfaction).collect::<Vec<f64>>()),
-        
-        ("Age vs Professional Network Size", 
-         individuals.iter().map(|ind| ind.age).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.professional_network_size).collect::<Vec<f64>>()),
-        
-        ("Years of Experience vs Professional Network Size", 
-         individuals.iter().map(|ind| ind.years_of_experience).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.professional_network_size).collect::<Vec<f64>>()),
-        
-        ("Professional Network Size vs Job Satisfaction", 
-         individuals.iter().map(|ind| ind.professional_network_size).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.job_satisfaction).collect::<Vec<f64>>()),
-
-         ("Family Influence vs Salary", 
-         individuals.iter().map(|ind| ind.family_influence).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.salary).collect::<Vec<f64>>()),
-
-         ("Age vs Salary", 
-         individuals.iter().map(|ind| ind.age).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.salary).collect::<Vec<f64>>()),
-
-         ("Years of Experience vs Salary", 
-         individuals.iter().map(|ind| ind.years_of_experience).collect::<Vec<f64>>(), 
-         individuals.iter().map(|ind| ind.salary).collect::<Vec<f64>>()),
-    ];
+fn contingency_table(table: &Table, rows: &str, columns: &str) -> Option<ContingencyTable> {
+    let row_keys = table.group_keys(rows)?;
+    let column_keys = table.group_keys(columns)?;
+    Some(ContingencyTable::from_keys(
+        &table.column(rows)?.spec.name,
+        &row_keys,
+        &table.column(columns)?.spec.name,
+        &column_keys,
+    ))
+}
 
-    println!("\n--- Correlation Analyses ---");
-    
-    for (title, x, y) in analyses {
-        let (slope, intercept, correlation, r_squared) = 
-            calculate_linear_regression(&x, &y);
+/// Regresses every schema target on every numeric, ordinal, boolean or encoded
+/// categorical feature, so columns added to the schema file are analysed
+/// without code changes.
+fn perform_schema_analysis(table: &Table) -> Result<(), Box<dyn Error>> {
+    let targets: Vec<_> = table
+        .columns
+        .iter()
+        .filter(|c| c.spec.role == Role::Target)
+        .filter_map(|c| table.numeric(&c.spec.name).map(|values| (c.spec.name.as_str(), values)))
+        .collect();
+    let features = table.encoded_features()?;
 
-        println!("\n{}:", title);
-        println!("Correlation Coefficient: {:.4}", correlation);
-        println!("Regression Equation: Y = {:.4} * X + {:.4}", slope, intercept);
-        println!("R-squared: {:.4}", r_squared);
+    println!("\n--- Schema Target Analyses ---");
 
-        if correlation.abs() < 0.3 {
-            println!("Weak correlation");
-        } else if correlation.abs() < 0.7 {
-            println!("Moderate correlation");
+    for (target_name, y) in &targets {
+        for (feature_name, x) in &features {
+            let (x, y) = pairwise_complete(x, y);
+            println!("\n{} vs {}:", feature_name, target_name);
+            match SimpleRegression::fit(&x, &y) {
+                Ok(fit) => fit.print(),
+                Err(e) => println!("Skipped: {}", e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The selected fields as labelled series.
+fn selected_columns(individuals: &[Individual], fields: &[Field]) -> Vec<NamedSeries> {
+    fields
+        .iter()
+        .map(|f| (f.label().to_string(), f.values(individuals)))
+        .collect()
+}
+
+fn run_describe(individuals: &[Individual], options: &Options) -> Result<(), Box<dyn Error>> {
+    let summaries: Vec<Summary> = selected_columns(individuals, &options.columns)
+        .iter()
+        .filter_map(|(name, values)| Summary::from_column(name, values))
+        .collect();
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => {
+            for summary in &summaries {
+                write!(out, "{}", summary.render(&options.percentiles))?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(Summary::csv_header(&options.percentiles))?;
+            for summary in &summaries {
+                wtr.write_record(summary.csv_row(&options.percentiles))?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn run_correlate(individuals: &[Individual], options: &Options) -> Result<(), Box<dyn Error>> {
+    let matrix = CorrelationMatrix::compute(&selected_columns(individuals, &options.columns), options.method);
+    let intervals = match options.bootstrap {
+        Some(resamples) => {
+            let bootstrap = Bootstrap::new(resamples, options.seed);
+            Some((correlation_intervals(individuals, &options.columns, options.method, &bootstrap)?, bootstrap))
+        }
+        None => None,
+    };
+    let permutation_tests = options.permutations.map(|permutations| {
+        let permutation = Permutation::new(permutations, options.seed);
+        (correlation_tests(individuals, &options.columns, options.method, &permutation), permutation)
+    });
+    let mut out = options.writer()?;
+    match (options.format, intervals) {
+        (OutputFormat::Text, intervals) => {
+            write!(out, "{}", matrix.render(options.correction))?;
+            if let Some((estimates, bootstrap)) = intervals {
+                let title = format!("\nBootstrap {} correlations", options.method);
+                write!(out, "{}", bootstrap::render(&title, &estimates, &bootstrap))?;
+            }
+            if let Some((results, permutation)) = permutation_tests {
+                let title = format!("\nPermutation tests of {} correlations", options.method);
+                write!(out, "{}", permutation::render(&title, &results, &permutation))?;
+            }
+        }
+        (OutputFormat::Csv, Some((estimates, _))) => write_bootstrap_csv(out, &estimates)?,
+        (OutputFormat::Csv, None) => match permutation_tests {
+            Some((results, _)) => {
+                let mut wtr = csv::Writer::from_writer(out);
+                wtr.write_record(["pair", "estimate", "p_value", "permutations", "exact"])?;
+                for (name, result) in &results {
+                    let Some(r) = result else { continue };
+                    wtr.write_record([
+                        name.clone(),
+                        r.observed.to_string(),
+                        r.p_value.to_string(),
+                        r.permutations.to_string(),
+                        r.exact.to_string(),
+                    ])?;
+                }
+                wtr.flush()?;
+            }
+            None => {
+                let mut wtr = csv::Writer::from_writer(out);
+                wtr.write_record(correlation::csv_header())?;
+                matrix.write_rows(&mut wtr)?;
+                wtr.flush()?;
+            }
+        },
+    }
+    Ok(())
+}
+
+fn run_regress(individuals: &[Individual], table: &Table, options: &Options) -> Result<(), Box<dyn Error>> {
+    let formula = match &options.formula {
+        Some(text) => Formula::parse(text)?,
+        None => Formula::all_features(Field::Salary, table)?,
+    };
+    if !options.robust.is_empty() {
+        return run_robust_regress(individuals, &formula, options);
+    }
+    let fit = MultipleRegression::fit(individuals, &formula)?;
+    let intervals = match options.bootstrap {
+        Some(resamples) => {
+            let bootstrap = Bootstrap::new(resamples, options.seed);
+            Some((coefficient_intervals(individuals, &formula, &bootstrap)?, bootstrap))
+        }
+        None => None,
+    };
+    let diagnostics = options
+        .diagnostics
+        .then(|| Diagnostics::compute(individuals, &formula))
+        .transpose()?;
+    let mut out = options.writer()?;
+    match (options.format, intervals, diagnostics) {
+        (OutputFormat::Text, intervals, diagnostics) => {
+            write!(out, "{}", fit.render())?;
+            if let Some((estimates, bootstrap)) = intervals {
+                write!(out, "{}", bootstrap::render("\nBootstrap coefficients", &estimates, &bootstrap))?;
+            }
+            if let Some(diagnostics) = diagnostics {
+                write!(out, "\n{}", diagnostics.render())?;
+            }
+        }
+        (OutputFormat::Csv, _, Some(diagnostics)) => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(Diagnostics::csv_header())?;
+            diagnostics.write_rows(&mut wtr)?;
+            wtr.flush()?;
+        }
+        (OutputFormat::Csv, Some((estimates, _)), None) => write_bootstrap_csv(out, &estimates)?,
+        (OutputFormat::Csv, None, None) => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(["term", "estimate", "std_error", "t_value", "p_value", "ci_lower", "ci_upper", "vif"])?;
+            for term in &fit.terms {
+                let c = &term.coefficient;
+                wtr.write_record([
+                    term.name.clone(),
+                    c.estimate.to_string(),
+                    c.std_error.to_string(),
+                    c.t_value.to_string(),
+                    c.p_value.to_string(),
+                    c.ci.0.to_string(),
+                    c.ci.1.to_string(),
+                    term.vif.map_or(String::new(), |v| v.to_string()),
+                ])?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Least squares and the `--robust` estimators side by side, for a formula with one term.
+fn run_robust_regress(individuals: &[Individual], formula: &Formula, options: &Options) -> Result<(), Box<dyn Error>> {
+    let [term] = formula.terms.as_slice() else {
+        return Err("--robust needs a formula with exactly one term, e.g. salary ~ years_of_experience".into());
+    };
+    let (x, y): (Vec<f64>, Vec<f64>) = individuals
+        .iter()
+        .filter_map(|ind| Some((term.value(ind)?, formula.response.value(ind)?)))
+        .unzip();
+    let mut methods = vec![RegressionMethod::Ols];
+    methods.extend(options.robust.iter().filter(|&&m| m != RegressionMethod::Ols));
+    let fits = methods
+        .into_iter()
+        .map(|method| fit_line(method, &x, &y, options.seed))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => write!(out, "{}", render_comparison(&format!("Model: {}", formula), &fits))?,
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record([
+                "method", "term", "estimate", "std_error", "t_value", "p_value", "ci_lower", "ci_upper", "r_squared", "scale",
+            ])?;
+            for fit in &fits {
+                for (name, c) in [("(Intercept)".to_string(), &fit.intercept), (term.to_string(), &fit.slope)] {
+                    wtr.write_record([
+                        fit.method.to_string(),
+                        name,
+                        c.estimate.to_string(),
+                        c.std_error.to_string(),
+                        c.t_value.to_string(),
+                        c.p_value.to_string(),
+                        c.ci.0.to_string(),
+                        c.ci.1.to_string(),
+                        fit.r_squared.to_string(),
+                        fit.residual_std_error.to_string(),
+                    ])?;
+                }
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn run_logistic(individuals: &[Individual], table: &Table, options: &Options) -> Result<(), Box<dyn Error>> {
+    let formula = match &options.formula {
+        Some(text) => Formula::parse(text)?,
+        None => Formula::all_features(Field::LikelyToChangeOccupation, table)?,
+    };
+    let fit = LogisticRegression::fit(individuals, &formula, options.penalty)?;
+    let evaluation = options
+        .evaluate
+        .then(|| Evaluation::for_individuals(&fit.predictions(), individuals, formula.response))
+        .transpose()?;
+    let mut out = options.writer()?;
+    match (options.format, evaluation) {
+        (OutputFormat::Text, evaluation) => {
+            write!(out, "{}", fit.render())?;
+            if let Some(evaluation) = evaluation {
+                write!(out, "\nIn-sample fitted probabilities:\n{}", evaluation.render())?;
+            }
+        }
+        (OutputFormat::Csv, Some(evaluation)) => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(Evaluation::csv_header())?;
+            evaluation.write_rows(&mut wtr)?;
+            wtr.flush()?;
+        }
+        (OutputFormat::Csv, None) => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(LogisticRegression::csv_header())?;
+            fit.write_rows(&mut wtr)?;
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn run_cross_validation(individuals: &[Individual], table: &Table, options: &Options) -> Result<(), Box<dyn Error>> {
+    let formula = match &options.formula {
+        Some(text) => Formula::parse(text)?,
+        None => Formula::all_features(options.model.default_response(), table)?,
+    };
+    let features = feature_builder(table, &formula, &options.categories, options.scaling);
+    let scheme = match (options.leave_one_out, options.stratify) {
+        (true, _) => Scheme::LeaveOneOut,
+        (false, true) => Scheme::Stratified(options.folds),
+        (false, false) => Scheme::KFold(options.folds),
+    };
+    let mut pipeline = Pipeline::new(features, options.model.build(options.seed, options.penalty));
+    let cv = CrossValidation { scheme, repeats: options.repeats, seed: options.seed };
+    let result = cv.run(&mut pipeline, individuals)?;
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => {
+            write!(out, "{}", result.render(&pipeline.to_string()))?;
+            if pipeline.task() == Task::Classification {
+                let evaluation = Evaluation::for_individuals(&result.predictions, individuals, formula.response)?;
+                write!(out, "\nOut-of-fold probabilities:\n{}", evaluation.render())?;
+            }
+            let all = pipeline.features.build(individuals)?;
+            let estimator = pipeline.estimator.as_mut();
+            estimator.fit(&all)?;
+            let score = match estimator.task() {
+                Task::Regression => "R-squared",
+                Task::Classification => "ROC AUC",
+            };
+            write!(out, "\nFit on every complete case:\n{}", estimator.report())?;
+            writeln!(out, "In-sample {}: {:.4}", score, estimator.score(&all)?)?;
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(result.csv_header())?;
+            result.write_rows(&mut wtr)?;
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn run_outliers(individuals: &[Individual], options: &Options) -> Result<(), Box<dyn Error>> {
+    let report = OutlierReport::detect(individuals, &options.columns, &options.outlier_methods)?;
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => write!(out, "{}", report.render(None))?,
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            report.write_rows(&mut wtr)?;
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_bootstrap_csv(out: Box<dyn Write>, estimates: &[BootstrapEstimate]) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_writer(out);
+    wtr.write_record([
+        "name", "estimate", "bias", "std_error", "percentile_lower", "percentile_upper", "bca_lower", "bca_upper", "replicates",
+    ])?;
+    for e in estimates {
+        wtr.write_record([
+            e.name.clone(),
+            e.estimate.to_string(),
+            e.bias.to_string(),
+            e.std_error.to_string(),
+            e.percentile.0.to_string(),
+            e.percentile.1.to_string(),
+            e.bca.map_or(String::new(), |ci| ci.0.to_string()),
+            e.bca.map_or(String::new(), |ci| ci.1.to_string()),
+            e.replicates.to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Pivot tables of the selected fields across the groups of `--by`.
+fn run_groupby(individuals: &[Individual], table: &Table, options: &Options) -> Result<(), Box<dyn Error>> {
+    let by = options.by.as_deref().ok_or("groupby needs --by <COLUMN>")?;
+    let groups = GroupBy::from_table(table, individuals, by)?;
+    let formula = options.formula.as_deref().map(Formula::parse).transpose()?;
+
+    let pivots = match (options.pivot, options.format) {
+        (Some(pivot), _) => vec![pivot],
+        (None, OutputFormat::Csv) => vec![Pivot::Stats],
+        (None, OutputFormat::Text) => {
+            let mut all = vec![Pivot::Stats, Pivot::Correlations];
+            if formula.is_some() {
+                all.push(Pivot::Regression);
+            }
+            all
+        }
+    };
+    if options.format == OutputFormat::Csv && pivots.len() > 1 {
+        return Err("csv output holds one table; choose it with --pivot".into());
+    }
+
+    let mut tables = Vec::new();
+    for pivot in pivots {
+        tables.push(match pivot {
+            Pivot::Stats => groups.summary_pivot(&options.columns, &options.stats),
+            Pivot::Correlations => groups.correlation_pivot(&options.columns, options.method, options.correction),
+            Pivot::Regression => match &formula {
+                Some(formula) => groups.regression_pivot(formula),
+                None => groups.regression_pivot(&Formula::all_features(Field::Salary, table)?),
+            },
+        });
+    }
+
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => {
+            let rendered: Vec<String> = tables.iter().map(PivotTable::render).collect();
+            write!(out, "{}", rendered.join("\n"))?;
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            tables[0].write_rows(&mut wtr)?;
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Paired, contingency or two-sample tests, depending on the options given.
+fn run_test(individuals: &[Individual], table: &Table, options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut results: Vec<(String, TestResult)> = Vec::new();
+    let mut preamble = String::new();
+    if let Some((first, second)) = options.paired {
+        let (x, y) = pairwise_complete(&first.values(individuals), &second.values(individuals));
+        results.push((format!("{} - {}", first, second), paired_t_test(&x, &y)?));
+    } else {
+        let by = options.by.as_deref().ok_or("test needs --paired, or --by with --groups or --against")?;
+        if let Some(against) = &options.against {
+            let contingency = contingency_table(table, by, against)
+                .ok_or_else(|| format!("no loaded columns named '{}' and '{}'", by, against))?;
+            preamble = contingency.render();
+            let label = format!("{} x {}", contingency.row_name, contingency.column_name);
+            results.push((label.clone(), chi_square_independence(&contingency)?));
+            if contingency.row_labels.len() == 2 && contingency.column_labels.len() == 2 {
+                results.push((label, fisher_exact(&contingency)?));
+            }
         } else {
-            println!("Strong correlation");
+            let groups = GroupBy::from_table(table, individuals, by)?;
+            let keys: Vec<&str> = groups.groups.iter().map(|g| g.key.as_str()).filter(|&k| k != MISSING_GROUP).collect();
+            let (a_key, b_key) = match (&options.groups, keys.as_slice()) {
+                (Some((a, b)), _) => (a.as_str(), b.as_str()),
+                (None, [a, b]) => (*a, *b),
+                (None, _) => return Err(format!("{} has {} groups; pick two with --groups A,B", groups.by, keys.len()).into()),
+            };
+            for &field in &options.columns {
+                let samples = groups.samples(field);
+                let sample = |key: &str| {
+                    samples
+                        .iter()
+                        .find(|(k, _)| k == key)
+                        .map(|(_, values)| values.as_slice())
+                        .ok_or_else(|| format!("{} has no group '{}'", groups.by, key))
+                };
+                let (a, b) = (sample(a_key)?, sample(b_key)?);
+                for result in two_sample_tests(a, b) {
+                    results.push((format!("{}: {} vs {}", field, a_key, b_key), result?));
+                }
+            }
         }
     }
 
-    println!("\n--- Descriptive Statistics ---");
-    let calculate_descriptive_stats = |data: &[f64]| {
-        let n = data.len() as f64;
-        let mean = data.iter().sum::<f64>() / n;
-        let min = data.iter().cloned().fold(f64::INFINITY, |a, b| a.min(b));
-        let max = data.iter().cloned().fold(f64::NEG_INFINITY, |a, b| a.max(b));
-        
-        (mean, min, max)
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => {
+            write!(out, "{}", preamble)?;
+            let mut previous = None;
+            for (label, result) in &results {
+                if previous != Some(label) {
+                    writeln!(out, "{}", label)?;
+                    previous = Some(label);
+                }
+                writeln!(out, "  {}", result)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(["comparison", "test", "statistic", "df", "p_value", "n", "effect_size", "effect_value"])?;
+            for (label, result) in &results {
+                wtr.write_record([
+                    label.clone(),
+                    result.test.to_string(),
+                    result.statistic.to_string(),
+                    result.df.map_or(String::new(), |df| df.to_string()),
+                    result.p_value.to_string(),
+                    result.n.to_string(),
+                    result.effect_size.map_or(String::new(), |e| e.name().to_string()),
+                    result.effect_size.map_or(String::new(), |e| e.value().to_string()),
+                ])?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Compares each selected field across the groups of `--by`.
+fn run_compare(individuals: &[Individual], table: &Table, options: &Options) -> Result<(), Box<dyn Error>> {
+    let by = options.by.as_deref().ok_or("compare needs --by <COLUMN>")?;
+    let groups = GroupBy::from_table(table, individuals, by)?;
+    let fields: Vec<Field> = options
+        .columns
+        .iter()
+        .copied()
+        .filter(|&field| table.column(by).is_none_or(|c| c.spec.name != field.label()))
+        .collect();
+    let comparisons = fields
+        .iter()
+        .map(|&field| GroupComparison::compute(field.label(), &groups.by, &groups.samples(field), options.correction))
+        .collect::<Result<Vec<_>, _>>()?;
+    let permutation_tests = match options.permutations {
+        Some(permutations) => {
+            let permutation = Permutation::new(permutations, options.seed);
+            fields
+                .iter()
+                .map(|&field| permutation.group_difference(&groups.samples(field)).map(Some))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        None => vec![None; fields.len()],
     };
 
-    let age_stats = calculate_descriptive_stats(&individuals.iter().map(|ind| ind.age).collect::<Vec<f64>>());
-    let network_stats = calculate_descriptive_stats(&individuals.iter().map(|ind| ind.professional_network_size).collect::<Vec<f64>>());
-    let experience_stats = calculate_descriptive_stats(&individuals.iter().map(|ind| ind.years_of_experience).collect::<Vec<f64>>());
-    let job_satisfaction_stats = calculate_descriptive_stats(&individuals.iter().map(|ind| ind.job_satisfaction).collect::<Vec<f64>>());
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => {
+            let rendered: Vec<String> = comparisons
+                .iter()
+                .zip(&permutation_tests)
+                .map(|(comparison, permutation)| match permutation {
+                    Some(r) => format!(
+                        "{}Permutation F test: p = {:.4} ({})\n",
+                        comparison.render(),
+                        r.p_value,
+                        r.describe()
+                    ),
+                    None => comparison.render(),
+                })
+                .collect();
+            write!(out, "{}", rendered.join("\n"))?;
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(["outcome", "grouping", "test", "first", "second", "statistic", "df1", "df2", "estimate", "p_value", "adjusted_p"])?;
+            for (c, permutation) in comparisons.iter().zip(&permutation_tests) {
+                let mut row = |test: &str, pair: (&str, &str), statistic: f64, df: (String, String), estimate: Option<f64>, p: f64, adjusted: Option<f64>| {
+                    wtr.write_record([
+                        c.outcome.clone(),
+                        c.grouping.clone(),
+                        test.to_string(),
+                        pair.0.to_string(),
+                        pair.1.to_string(),
+                        statistic.to_string(),
+                        df.0,
+                        df.1,
+                        estimate.map_or(String::new(), |e| e.to_string()),
+                        p.to_string(),
+                        adjusted.map_or(String::new(), |p| p.to_string()),
+                    ])
+                };
+                let a = &c.anova;
+                row("anova", ("", ""), a.f_statistic, (a.df_between.to_string(), a.df_within.to_string()), Some(a.eta_squared), a.p_value, None)?;
+                if let Some(w) = &a.welch {
+                    row("welch", ("", ""), w.f_statistic, (w.df1.to_string(), w.df2.to_string()), None, w.p_value, None)?;
+                }
+                for t in &a.tukey {
+                    row("tukey", (&t.first, &t.second), t.q_statistic, (String::new(), a.df_within.to_string()), Some(t.difference), t.p_value, Some(t.p_value))?;
+                }
+                if let Some(r) = permutation {
+                    row("permutation", ("", ""), r.observed, (String::new(), String::new()), None, r.p_value, None)?;
+                }
+                let kw = &c.kruskal_wallis;
+                row("kruskal_wallis", ("", ""), kw.h_statistic, (kw.df.to_string(), String::new()), Some(kw.epsilon_squared), kw.p_value, None)?;
+                for d in &kw.dunn {
+                    row("dunn", (&d.first, &d.second), d.z, (String::new(), String::new()), Some(d.rank_difference), d.p_value, Some(d.adjusted_p))?;
+                }
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
 
-    println!("Age - Mean: {:.2}, Min: {:.2}, Max: {:.2}", age_stats.0, age_stats.1, age_stats.2);
-    println!("Professional Network Size - Mean: {:.2}, Min: {:.2}, Max: {:.2}", 
-             network_stats.0, network_stats.1, network_stats.2);
-    println!("Years of Experience - Mean: {:.2}, Min: {:.2}, Max: {:.2}", 
-             experience_stats.0, experience_stats.1, experience_stats.2);
-    println!("Job Satisfaction - Mean: {:.2}, Min: {:.2}, Max: {:.2}", 
-             job_satisfaction_stats.0, job_satisfaction_stats.1, job_satisfaction_stats.2);
+/// Writes `id` plus the selected fields of every loaded individual as CSV.
+fn run_export(individuals: &[Individual], options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.format != OutputFormat::Csv && options.output.is_none() {
+        eprintln!("Note: export always writes CSV");
+    }
+    let mut wtr = csv::Writer::from_writer(options.writer()?);
+    let mut header = vec!["id".to_string()];
+    header.extend(Category::ALL.iter().map(|c| c.key().to_string()));
+    header.extend(options.columns.iter().map(|f| f.key().to_string()));
+    wtr.write_record(&header)?;
+    for individual in individuals {
+        let mut row = vec![individual.id.to_string()];
+        row.extend(Category::ALL.iter().map(|c| c.value(individual).unwrap_or_default().to_string()));
+        row.extend(
+            options
+                .columns
+                .iter()
+                .map(|f| f.value(individual).map_or(String::new(), |v| v.to_string())),
+        );
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
 
+/// Evaluates a distribution at the `--x` points and `--p` probabilities.
+fn run_dist(options: &Options) -> Result<(), Box<dyn Error>> {
+    let name = options.distribution.as_deref().ok_or("dist needs --dist <NAME>")?;
+    let distribution = distributions::from_name(name, &options.params)?;
+    if options.points.is_empty() && options.probabilities.is_empty() {
+        return Err("dist needs --x and/or --p".into());
+    }
+    let mut out = options.writer()?;
+    match options.format {
+        OutputFormat::Text => {
+            for &x in &options.points {
+                writeln!(out, "x = {}: cdf = {}, sf = {}", x, distribution.cdf(x), distribution.sf(x))?;
+            }
+            for &p in &options.probabilities {
+                writeln!(out, "p = {}: quantile = {}", p, distribution.quantile(p))?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            wtr.write_record(["function", "argument", "value"])?;
+            for &x in &options.points {
+                wtr.write_record(["cdf".to_string(), x.to_string(), distribution.cdf(x).to_string()])?;
+                wtr.write_record(["sf".to_string(), x.to_string(), distribution.sf(x).to_string()])?;
+            }
+            for &p in &options.probabilities {
+                wtr.write_record(["quantile".to_string(), p.to_string(), distribution.quantile(p).to_string()])?;
+            }
+            wtr.flush()?;
+        }
+    }
     Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let file_path = "career_dataset.csv";
-    let individuals = read_dataset(file_path)?;
+    let options = Options::parse(std::env::args().skip(1))?;
+    match options.command {
+        Command::Help => {
+            print!("{}", HELP);
+            return Ok(());
+        }
+        // Needs no dataset
+        Command::Dist => return run_dist(&options),
+        _ => {}
+    }
+
+    let schema = load_schema(&options)?;
+    let (table, report) = load_table(&options.input, &schema)?;
+    report.print_summary();
+    if let Some(path) = &options.rejects {
+        report.write_rejects(path)?;
+        eprintln!("Dropped rows written to {}", path);
+    }
+    let individuals = individuals_from_table(&table)?;
 
     if individuals.is_empty() {
         eprintln!("No individuals loaded from the dataset!");
         return Ok(());
     }
 
-    perform_correlation_analysis(&individuals)?;
+    match options.command {
+        Command::Report => {
+            perform_outlier_screen(&individuals)?;
+            perform_correlation_analysis(&screen_outliers(&individuals, &options)?)?;
+            perform_multiple_regression(&table, &individuals);
+            perform_robust_regression(&individuals);
+            perform_logistic_regression(&table, &individuals);
+            perform_cross_validation(&table, &individuals);
+            perform_group_analysis(&table, &individuals)?;
+            perform_hypothesis_tests(&table, &individuals);
+            perform_schema_analysis(&table)?;
+        }
+        Command::Load => println!("Loaded {} individuals from {}", individuals.len(), options.input),
+        Command::Describe => run_describe(&individuals, &options)?,
+        Command::Correlate => run_correlate(&screen_outliers(&individuals, &options)?, &options)?,
+        Command::Regress => run_regress(&individuals, &table, &options)?,
+        Command::Logistic => run_logistic(&individuals, &table, &options)?,
+        Command::CrossValidate => run_cross_validation(&individuals, &table, &options)?,
+        Command::GroupBy => run_groupby(&individuals, &table, &options)?,
+        Command::Test => run_test(&individuals, &table, &options)?,
+        Command::Compare => run_compare(&individuals, &table, &options)?,
+        Command::Export => run_export(&individuals, &options)?,
+        Command::Outliers => run_outliers(&individuals, &options)?,
+        Command::Help | Command::Dist => unreachable!("handled before loading"),
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
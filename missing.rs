@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Statistic used to fill a missing cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImputeStat {
+    Mean,
+    Median,
+    Mode,
+}
+
+/// What the loader does with a blank cell in a column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MissingPolicy {
+    /// Reject the whole row (the historical behaviour).
+    Drop,
+    /// Fill with a statistic of the observed values, optionally computed within
+    /// the rows that share the same value of `group_by`.
+    Impute { stat: ImputeStat, group_by: Option<String> },
+    /// Fill with a fixed numeric code.
+    Sentinel(f64),
+    /// Keep the row and leave the cell as `None`.
+    Keep,
+}
+
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+/// Most frequent value; ties go to the smallest value.
+pub fn mode_f64(values: &[f64]) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mut best: Option<(f64, usize)> = None;
+    let mut i = 0;
+    while i < sorted.len() {
+        let run = sorted[i..].iter().take_while(|&&v| v == sorted[i]).count();
+        if best.is_none_or(|(_, count)| run > count) {
+            best = Some((sorted[i], run));
+        }
+        i += run;
+    }
+    best.map(|(value, _)| value)
+}
+
+/// Most frequent value; ties go to the smallest value.
+pub fn mode<'a, T: Ord + Clone + 'a>(values: impl IntoIterator<Item = &'a T>) -> Option<T> {
+    let mut counts: BTreeMap<&T, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    let max = counts.values().copied().max()?;
+    counts.into_iter().find(|&(_, c)| c == max).map(|(v, _)| v.clone())
+}
+
+fn numeric_stat(values: &[f64], stat: ImputeStat) -> Option<f64> {
+    match stat {
+        ImputeStat::Mean => mean(values),
+        ImputeStat::Median => median(values),
+        ImputeStat::Mode => mode_f64(values),
+    }
+}
+
+/// Fills `None` cells with `stat`, computed per group when `groups` is given
+/// (falling back to the overall statistic for missing or empty groups).
+/// Returns the number of cells filled.
+pub fn impute_numeric(values: &mut [Option<f64>], stat: ImputeStat, groups: Option<&[Option<String>]>) -> usize {
+    let observed: Vec<f64> = values.iter().flatten().copied().collect();
+    let Some(overall) = numeric_stat(&observed, stat) else {
+        return 0;
+    };
+
+    let mut by_group: HashMap<&str, f64> = HashMap::new();
+    if let Some(groups) = groups {
+        let mut members: HashMap<&str, Vec<f64>> = HashMap::new();
+        for (value, group) in values.iter().zip(groups) {
+            if let (Some(v), Some(g)) = (value, group) {
+                members.entry(g.as_str()).or_default().push(*v);
+            }
+        }
+        for (group, observed) in members {
+            if let Some(fill) = numeric_stat(&observed, stat) {
+                by_group.insert(group, fill);
+            }
+        }
+    }
+
+    let mut filled = 0;
+    for (r, value) in values.iter_mut().enumerate() {
+        if value.is_none() {
+            let group = groups.and_then(|g| g[r].as_deref());
+            *value = Some(group.and_then(|g| by_group.get(g)).copied().unwrap_or(overall));
+            filled += 1;
+        }
+    }
+    filled
+}
+
+/// Fills `None` cells with the most frequent value, per group when `groups` is given.
+pub fn impute_mode<T: Ord + Clone>(values: &mut [Option<T>], groups: Option<&[Option<String>]>) -> usize {
+    let Some(overall) = mode(values.iter().flatten()) else {
+        return 0;
+    };
+
+    let mut by_group: HashMap<&str, T> = HashMap::new();
+    if let Some(groups) = groups {
+        let mut members: HashMap<&str, Vec<&T>> = HashMap::new();
+        for (value, group) in values.iter().zip(groups) {
+            if let (Some(v), Some(g)) = (value, group) {
+                members.entry(g.as_str()).or_default().push(v);
+            }
+        }
+        for (group, observed) in members {
+            if let Some(fill) = mode(observed) {
+                by_group.insert(group, fill);
+            }
+        }
+    }
+
+    let mut filled = 0;
+    for (r, value) in values.iter_mut().enumerate() {
+        if value.is_none() {
+            let group = groups.and_then(|g| g[r].as_deref());
+            *value = Some(group.and_then(|g| by_group.get(g)).unwrap_or(&overall).clone());
+            filled += 1;
+        }
+    }
+    filled
+}
+
+/// The observations where both `x` and `y` are present.
+pub fn pairwise_complete(x: &[Option<f64>], y: &[Option<f64>]) -> (Vec<f64>, Vec<f64>) {
+    x.iter()
+        .zip(y)
+        .filter_map(|(a, b)| Some(((*a)?, (*b)?)))
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::load;
+
+    fn groups(keys: &[Option<&str>]) -> Vec<Option<String>> {
+        keys.iter().map(|k| k.map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn numeric_imputation_uses_each_group() {
+        let mut values = [Some(1.0), Some(3.0), None, Some(10.0), Some(20.0), Some(60.0), None];
+        let keys = groups(&[Some("a"), Some("a"), Some("a"), Some("b"), Some("b"), Some("b"), Some("b")]);
+        assert_eq!(impute_numeric(&mut values, ImputeStat::Mean, Some(&keys)), 2);
+        assert_eq!(values[2], Some(2.0));
+        assert_eq!(values[6], Some(30.0));
+
+        let mut values = [Some(1.0), Some(3.0), None, Some(10.0), Some(20.0), Some(60.0), None];
+        impute_numeric(&mut values, ImputeStat::Median, Some(&keys));
+        assert_eq!((values[2], values[6]), (Some(2.0), Some(20.0)));
+    }
+
+    #[test]
+    fn groups_without_observations_fall_back_to_the_overall_statistic() {
+        // Group "c" has no observed value and the last row has no group at all.
+        let mut values = [Some(1.0), Some(2.0), Some(2.0), Some(9.0), None, None, None];
+        let keys = groups(&[Some("a"), Some("a"), Some("b"), Some("b"), Some("c"), Some("c"), None]);
+        assert_eq!(impute_numeric(&mut values, ImputeStat::Mode, Some(&keys)), 3);
+        assert_eq!(values[4..], [Some(2.0), Some(2.0), Some(2.0)]);
+
+        let mut values = [Some(1.0), Some(2.0), Some(2.0), Some(9.0), None];
+        impute_numeric(&mut values, ImputeStat::Mean, None);
+        assert_eq!(values[4], Some(3.5));
+
+        let mut empty = [None, None];
+        assert_eq!(impute_numeric(&mut empty, ImputeStat::Mean, None), 0);
+        assert_eq!(empty, [None, None]);
+    }
+
+    #[test]
+    fn mode_imputation_breaks_ties_towards_the_smallest_value() {
+        let mut values = groups(&[Some("x"), Some("y"), Some("y"), None, Some("x"), None]);
+        let keys = groups(&[Some("a"), Some("a"), Some("b"), Some("b"), Some("c"), Some("d")]);
+        assert_eq!(impute_mode(&mut values, Some(&keys)), 2);
+        // "a" ties x and y, "b" is all y, and "d" has nothing observed: overall x/y tie goes to x.
+        assert_eq!(values[3].as_deref(), Some("y"));
+        assert_eq!(values[5].as_deref(), Some("x"));
+
+        let mut flags = [Some(true), Some(false), Some(true), None];
+        assert_eq!(impute_mode(&mut flags, None), 1);
+        assert_eq!(flags[3], Some(true));
+    }
+
+    #[test]
+    fn pairwise_complete_keeps_rows_with_both_values() {
+        let x = [Some(1.0), None, Some(3.0), Some(4.0)];
+        let y = [Some(10.0), Some(20.0), None, Some(40.0)];
+        assert_eq!(pairwise_complete(&x, &y), (vec![1.0, 4.0], vec![10.0, 40.0]));
+    }
+
+    #[test]
+    fn sentinel_and_keep_policies_apply_at_load() {
+        let schema = r#"
+[[column]]
+name = "Team"
+type = "categorical"
+
+[[column]]
+name = "Score"
+type = "numeric"
+missing = "mean"
+group_by = "Team"
+
+[[column]]
+name = "Rating"
+type = "numeric"
+missing = "sentinel"
+sentinel = -1
+
+[[column]]
+name = "Note"
+type = "numeric"
+missing = "keep"
+"#;
+        let csv = "Team,Score,Rating,Note\nred,1,5,\nred,,4,2\nblue,10,,3\nblue,,,\n";
+        let (table, report) = load("missing", schema, csv).unwrap();
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.numeric("Score").unwrap(), [Some(1.0), Some(1.0), Some(10.0), Some(10.0)]);
+        assert_eq!(table.numeric("Rating").unwrap(), [Some(5.0), Some(4.0), Some(-1.0), Some(-1.0)]);
+        assert_eq!(table.numeric("Note").unwrap(), [None, Some(2.0), Some(3.0), None]);
+        assert_eq!(report.imputed.get("Score"), Some(&2));
+        assert_eq!(report.imputed.get("Rating"), Some(&2));
+        assert_eq!(report.imputed.get("Note"), None);
+    }
+}
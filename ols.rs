@@ -0,0 +1,514 @@
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::distributions::{Continuous, FisherF, StudentT};
+use crate::formula::Formula;
+use crate::individual::Individual;
+use crate::linalg::{Matrix, Qr};
+use crate::robust::RegressionMethod;
+
+/// Confidence level used for coefficient intervals.
+pub const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// An estimated coefficient with its t-based inference.
+#[derive(Debug, Clone, Copy)]
+pub struct Coefficient {
+    pub estimate: f64,
+    pub std_error: f64,
+    pub t_value: f64,
+    pub p_value: f64,
+    /// `CONFIDENCE_LEVEL` confidence interval (lower, upper).
+    pub ci: (f64, f64),
+}
+
+impl Coefficient {
+    /// Builds the t statistic, two-sided p-value and interval from an estimate
+    /// and its standard error, using a t distribution with `df` degrees of freedom.
+    pub fn new(estimate: f64, std_error: f64, df: f64) -> Self {
+        let t = StudentT::new(df);
+        let t_value = estimate / std_error;
+        let margin = t.quantile(0.5 + CONFIDENCE_LEVEL / 2.0) * std_error;
+        Coefficient {
+            estimate,
+            std_error,
+            t_value,
+            p_value: t.two_sided_p(t_value),
+            ci: (estimate - margin, estimate + margin),
+        }
+    }
+}
+
+/// Fit of `y = intercept + slope * x`, by ordinary least squares unless
+/// `method` names a robust estimator (see `robust::fit_line`).
+#[derive(Debug, Clone)]
+pub struct SimpleRegression {
+    pub method: RegressionMethod,
+    pub n: usize,
+    pub slope: Coefficient,
+    pub intercept: Coefficient,
+    pub correlation: f64,
+    pub r_squared: f64,
+    pub adj_r_squared: f64,
+    pub residual_std_error: f64,
+    /// Residual degrees of freedom, n - 2.
+    pub df_residual: f64,
+    pub f_statistic: f64,
+    pub f_p_value: f64,
+}
+
+impl SimpleRegression {
+    pub fn fit(x: &[f64], y: &[f64]) -> Result<SimpleRegression, Box<dyn Error>> {
+        if x.len() != y.len() {
+            return Err("Input vectors must be of equal length".into());
+        }
+        let n = x.len();
+        if n < 3 {
+            return Err(format!("need at least 3 observations, got {}", n).into());
+        }
+        let nf = n as f64;
+
+        let mean_x = x.iter().sum::<f64>() / nf;
+        let mean_y = y.iter().sum::<f64>() / nf;
+
+        let mut sxx = 0.0;
+        let mut syy = 0.0;
+        let mut sxy = 0.0;
+        for (xi, yi) in x.iter().zip(y) {
+            let dx = xi - mean_x;
+            let dy = yi - mean_y;
+            sxx += dx * dx;
+            syy += dy * dy;
+            sxy += dx * dy;
+        }
+        if sxx == 0.0 {
+            return Err("predictor has zero variance".into());
+        }
+
+        let slope = sxy / sxx;
+        let intercept = mean_y - slope * mean_x;
+
+        let sse: f64 = x
+            .iter()
+            .zip(y)
+            .map(|(xi, yi)| (yi - intercept - slope * xi).powi(2))
+            .sum();
+        let df_residual = nf - 2.0;
+        let sigma2 = sse / df_residual;
+
+        let slope_se = (sigma2 / sxx).sqrt();
+        let intercept_se = (sigma2 * (1.0 / nf + mean_x * mean_x / sxx)).sqrt();
+
+        let correlation = if syy == 0.0 { 0.0 } else { sxy / (sxx * syy).sqrt() };
+        let r_squared = correlation * correlation;
+        let adj_r_squared = 1.0 - (1.0 - r_squared) * (nf - 1.0) / df_residual;
+
+        let ssr = syy - sse;
+        let f_statistic = ssr / sigma2;
+
+        Ok(SimpleRegression {
+            method: RegressionMethod::Ols,
+            n,
+            slope: Coefficient::new(slope, slope_se, df_residual),
+            intercept: Coefficient::new(intercept, intercept_se, df_residual),
+            correlation,
+            r_squared,
+            adj_r_squared,
+            residual_std_error: sigma2.sqrt(),
+            df_residual,
+            f_statistic,
+            f_p_value: FisherF::new(1.0, df_residual).sf(f_statistic),
+        })
+    }
+
+    /// Multi-line report in the style of the correlation analysis output.
+    pub fn print(&self) {
+        println!("Observations: {}", self.n);
+        println!("Correlation Coefficient: {:.4}", self.correlation);
+        println!(
+            "Regression Equation: Y = {:.4} * X + {:.4}",
+            self.slope.estimate, self.intercept.estimate
+        );
+        for (name, c) in [("Slope", &self.slope), ("Intercept", &self.intercept)] {
+            println!(
+                "{}: {:.4} (SE {:.4}, t = {:.3}, p = {:.4}, {:.0}% CI [{:.4}, {:.4}])",
+                name,
+                c.estimate,
+                c.std_error,
+                c.t_value,
+                c.p_value,
+                CONFIDENCE_LEVEL * 100.0,
+                c.ci.0,
+                c.ci.1
+            );
+        }
+        println!(
+            "R-squared: {:.4}, Adjusted R-squared: {:.4}",
+            self.r_squared, self.adj_r_squared
+        );
+        println!(
+            "Residual standard error: {:.4} on {} degrees of freedom",
+            self.residual_std_error, self.df_residual
+        );
+        println!(
+            "F-statistic: {:.4} on 1 and {} DF, p-value: {:.4}",
+            self.f_statistic, self.df_residual, self.f_p_value
+        );
+    }
+}
+
+/// One row of a sequential (type I) ANOVA table.
+#[derive(Debug, Clone)]
+pub struct AnovaRow {
+    pub source: String,
+    pub df: f64,
+    pub sum_sq: f64,
+    pub mean_sq: f64,
+    /// `None` for the residual row.
+    pub f_value: Option<f64>,
+    pub p_value: Option<f64>,
+}
+
+/// A predictor of a multiple regression with its variance inflation factor.
+#[derive(Debug, Clone)]
+pub struct TermEstimate {
+    pub name: String,
+    pub coefficient: Coefficient,
+    /// `None` for the intercept.
+    pub vif: Option<f64>,
+}
+
+/// Coefficient table of a linear model with t-based inference, as printed by
+/// every linear fit; the VIF column is blank where `vif` is `None`.
+pub fn render_coefficients(terms: &[TermEstimate]) -> String {
+    let mut out = String::new();
+    let width = terms.iter().map(|t| t.name.len()).max().unwrap_or(0).max(9);
+    let ci = format!("{:.0}% CI", CONFIDENCE_LEVEL * 100.0);
+    writeln!(
+        out,
+        "{:<width$} {:>14} {:>12} {:>9} {:>9} {:>31} {:>8}",
+        "Term", "Estimate", "Std. Error", "t value", "Pr(>|t|)", ci, "VIF"
+    )
+    .unwrap();
+    for term in terms {
+        let c = &term.coefficient;
+        let vif = term.vif.map_or(String::new(), |v| format!("{:.3}", v));
+        writeln!(
+            out,
+            "{:<width$} {:>14.4} {:>12.4} {:>9.3} {:>9.4} {:>31} {:>8}",
+            term.name,
+            c.estimate,
+            c.std_error,
+            c.t_value,
+            c.p_value,
+            format!("[{:.4}, {:.4}]", c.ci.0, c.ci.1),
+            vif
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Ordinary least squares fit of a `Formula`, solved by Householder QR.
+#[derive(Debug, Clone)]
+pub struct MultipleRegression {
+    pub formula: Formula,
+    /// Complete cases used, i.e. individuals with the response and every term present.
+    pub n: usize,
+    /// Intercept first, then the formula terms in order.
+    pub terms: Vec<TermEstimate>,
+    pub r_squared: f64,
+    pub adj_r_squared: f64,
+    pub residual_std_error: f64,
+    pub df_residual: f64,
+    pub f_statistic: f64,
+    pub f_p_value: f64,
+    pub anova: Vec<AnovaRow>,
+}
+
+/// R-squared of regressing `y` on the columns of `x` (which include an intercept).
+fn r_squared_of(x: &Matrix, y: &[f64]) -> f64 {
+    let beta = Qr::new(x).solve(y);
+    let fitted = x.mul_vec(&beta);
+    let mean = y.iter().sum::<f64>() / y.len() as f64;
+    let sse: f64 = y.iter().zip(&fitted).map(|(a, b)| (a - b).powi(2)).sum();
+    let sst: f64 = y.iter().map(|a| (a - mean).powi(2)).sum();
+    if sst == 0.0 {
+        1.0
+    } else {
+        1.0 - sse / sst
+    }
+}
+
+/// The complete cases of a formula: individuals with the response and every term present.
+#[derive(Debug, Clone)]
+pub struct Design {
+    /// `Individual::id` of each row.
+    pub ids: Vec<usize>,
+    /// Intercept column first, then one column per formula term.
+    pub x: Matrix,
+    pub y: Vec<f64>,
+}
+
+impl Design {
+    /// Builds the design, failing when there are no more complete cases than coefficients.
+    pub fn new(individuals: &[Individual], formula: &Formula) -> Result<Design, Box<dyn Error>> {
+        let mut ids = Vec::new();
+        let mut rows = Vec::new();
+        let mut y = Vec::new();
+        for individual in individuals {
+            if let (Some(response), Some(row)) = (formula.response.value(individual), formula.row(individual)) {
+                ids.push(individual.id);
+                rows.push(row);
+                y.push(response);
+            }
+        }
+
+        let (n, p) = (y.len(), formula.terms.len() + 1);
+        if n <= p {
+            return Err(format!("{} complete observations are too few for {} coefficients", n, p).into());
+        }
+        Ok(Design { ids, x: Matrix::from_rows(&rows), y })
+    }
+}
+
+impl MultipleRegression {
+    pub fn fit(individuals: &[Individual], formula: &Formula) -> Result<MultipleRegression, Box<dyn Error>> {
+        let Design { x, y, .. } = Design::new(individuals, formula)?;
+        let n = y.len();
+        let p = x.cols;
+        let qr = Qr::new(&x);
+        if !qr.is_full_rank() {
+            return Err("design matrix is rank deficient (collinear or constant terms)".into());
+        }
+
+        let beta = qr.solve(&y);
+        let fitted = x.mul_vec(&beta);
+        let nf = n as f64;
+        let df_residual = (n - p) as f64;
+        let mean_y = y.iter().sum::<f64>() / nf;
+        let sse: f64 = y.iter().zip(&fitted).map(|(a, b)| (a - b).powi(2)).sum();
+        let sst: f64 = y.iter().map(|a| (a - mean_y).powi(2)).sum();
+        let sigma2 = sse / df_residual;
+        let covariance = qr.xtx_inverse();
+
+        let mut terms = Vec::with_capacity(p);
+        for (j, &estimate) in beta.iter().enumerate() {
+            let (name, vif) = if j == 0 {
+                ("(Intercept)".to_string(), None)
+            } else {
+                let others = x.without_column(j);
+                let vif = 1.0 / (1.0 - r_squared_of(&others, &x.column(j)));
+                (formula.terms[j - 1].to_string(), Some(vif))
+            };
+            let std_error = (sigma2 * covariance[(j, j)]).sqrt();
+            terms.push(TermEstimate {
+                name,
+                coefficient: Coefficient::new(estimate, std_error, df_residual),
+                vif,
+            });
+        }
+
+        // Q'y splits the model sum of squares into one sequential piece per column.
+        let effects = qr.qt_mul(&y);
+        let mut anova: Vec<AnovaRow> = formula
+            .terms
+            .iter()
+            .zip(&effects[1..p])
+            .map(|(term, effect)| {
+                let sum_sq = effect * effect;
+                let f_value = sum_sq / sigma2;
+                AnovaRow {
+                    source: term.to_string(),
+                    df: 1.0,
+                    sum_sq,
+                    mean_sq: sum_sq,
+                    f_value: Some(f_value),
+                    p_value: Some(FisherF::new(1.0, df_residual).sf(f_value)),
+                }
+            })
+            .collect();
+        anova.push(AnovaRow {
+            source: "Residuals".to_string(),
+            df: df_residual,
+            sum_sq: sse,
+            mean_sq: sigma2,
+            f_value: None,
+            p_value: None,
+        });
+
+        let df_model = (p - 1) as f64;
+        let r_squared = if sst == 0.0 { 0.0 } else { 1.0 - sse / sst };
+        let f_statistic = ((sst - sse) / df_model) / sigma2;
+
+        Ok(MultipleRegression {
+            formula: formula.clone(),
+            n,
+            terms,
+            r_squared,
+            adj_r_squared: 1.0 - (1.0 - r_squared) * (nf - 1.0) / df_residual,
+            residual_std_error: sigma2.sqrt(),
+            df_residual,
+            f_statistic,
+            f_p_value: FisherF::new(df_model, df_residual).sf(f_statistic),
+            anova,
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "Model: {}", self.formula).unwrap();
+        writeln!(out, "Observations: {}", self.n).unwrap();
+        write!(out, "{}", render_coefficients(&self.terms)).unwrap();
+        writeln!(
+            out,
+            "R-squared: {:.4}, Adjusted R-squared: {:.4}",
+            self.r_squared, self.adj_r_squared
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "Residual standard error: {:.4} on {} degrees of freedom",
+            self.residual_std_error, self.df_residual
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "F-statistic: {:.4} on {} and {} DF, p-value: {:.4}",
+            self.f_statistic,
+            self.terms.len() - 1,
+            self.df_residual,
+            self.f_p_value
+        )
+        .unwrap();
+
+        writeln!(out, "\nAnalysis of Variance (sequential sums of squares):").unwrap();
+        let width = self.anova.iter().map(|r| r.source.len()).max().unwrap_or(0).max(9);
+        writeln!(
+            out,
+            "{:<width$} {:>6} {:>18} {:>18} {:>10} {:>9}",
+            "Source", "Df", "Sum Sq", "Mean Sq", "F value", "Pr(>F)"
+        )
+        .unwrap();
+        for row in &self.anova {
+            let f_value = row.f_value.map_or(String::new(), |f| format!("{:.3}", f));
+            let p_value = row.p_value.map_or(String::new(), |p| format!("{:.4}", p));
+            writeln!(
+                out,
+                "{:<width$} {:>6} {:>18.2} {:>18.2} {:>10} {:>9}",
+                row.source, row.df, row.sum_sq, row.mean_sq, f_value, p_value
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{close, relative, DIST, SPEED, STACKLOSS};
+
+    #[test]
+    fn simple_regression_matches_lm_on_cars() {
+        // summary(lm(dist ~ speed, cars)); confint(lm(dist ~ speed, cars))
+        let fit = SimpleRegression::fit(&SPEED, &DIST).unwrap();
+        assert_eq!((fit.n, fit.df_residual), (50, 48.0));
+        close(fit.intercept.estimate, -17.5790949, 1e-6);
+        close(fit.intercept.std_error, 6.7584402, 1e-6);
+        close(fit.intercept.p_value, 0.0123188, 1e-6);
+        close(fit.slope.estimate, 3.9324088, 1e-6);
+        close(fit.slope.std_error, 0.4155128, 1e-6);
+        close(fit.slope.ci.0, 3.096964, 1e-5);
+        close(fit.slope.ci.1, 4.767853, 1e-5);
+        close(fit.intercept.ci.0, -31.167850, 1e-5);
+        close(fit.residual_std_error, 15.3795867, 1e-6);
+        close(fit.r_squared, 0.6510794, 1e-6);
+        close(fit.adj_r_squared, 0.6438102, 1e-6);
+        close(fit.f_statistic, 89.5671065, 1e-6);
+        relative(fit.f_p_value, 1.4898365e-12, 1e-5);
+    }
+
+    #[test]
+    fn simple_regression_rejects_degenerate_input() {
+        assert!(SimpleRegression::fit(&[1.0, 2.0], &[1.0, 2.0]).is_err());
+        assert!(SimpleRegression::fit(&[1.0, 2.0, 3.0], &[1.0, 2.0]).is_err());
+        assert!(SimpleRegression::fit(&[2.0; 4], &[1.0, 2.0, 3.0, 4.0]).is_err());
+    }
+
+    /// Stackloss as individuals: air flow as age, water temperature as years of
+    /// experience, acid concentration as job satisfaction and stack loss as salary.
+    fn stackloss() -> Vec<Individual> {
+        STACKLOSS
+            .iter()
+            .enumerate()
+            .map(|(id, r)| Individual {
+                id,
+                age: Some(r[0]),
+                years_of_experience: Some(r[1]),
+                job_satisfaction: Some(r[2]),
+                salary: Some(r[3]),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn fit() -> MultipleRegression {
+        let formula = Formula::parse("salary ~ age + years_of_experience + job_satisfaction").unwrap();
+        MultipleRegression::fit(&stackloss(), &formula).unwrap()
+    }
+
+    #[test]
+    fn coefficients_match_lm_on_stackloss() {
+        // summary(lm(stack.loss ~ Air.Flow + Water.Temp + Acid.Conc., stackloss))
+        let fit = fit();
+        let expected = [
+            (-39.9196744, 11.8959969),
+            (0.7156402, 0.1348582),
+            (1.2952861, 0.3680243),
+            (-0.1521225, 0.1562940),
+        ];
+        assert_eq!(fit.n, 21);
+        for (term, (estimate, std_error)) in fit.terms.iter().zip(expected) {
+            close(term.coefficient.estimate, estimate, 1e-6);
+            close(term.coefficient.std_error, std_error, 1e-6);
+        }
+        close(fit.r_squared, 0.9135769, 1e-6);
+        close(fit.residual_std_error, 3.2433639, 1e-6);
+        close(fit.f_statistic, 59.9022259, 1e-5);
+        assert_eq!(fit.df_residual, 17.0);
+    }
+
+    #[test]
+    fn variance_inflation_matches_car_vif() {
+        // car::vif(lm(stack.loss ~ ., stackloss))
+        let fit = fit();
+        assert!(fit.terms[0].vif.is_none());
+        for (term, expected) in fit.terms[1..].iter().zip([2.9064836, 2.5726324, 1.3335875]) {
+            close(term.vif.unwrap(), expected, 1e-6);
+        }
+    }
+
+    #[test]
+    fn sequential_sums_of_squares_match_anova() {
+        // anova(lm(stack.loss ~ Air.Flow + Water.Temp + Acid.Conc., stackloss))
+        let fit = fit();
+        let expected = [(1.0, 1750.1219894), (1.0, 130.3207720), (1.0, 9.9653723), (17.0, 178.8299616)];
+        assert_eq!(fit.anova.len(), expected.len());
+        for (row, (df, sum_sq)) in fit.anova.iter().zip(expected) {
+            assert_eq!(row.df, df);
+            close(row.sum_sq, sum_sq, 1e-5);
+        }
+        close(fit.anova[0].f_value.unwrap(), 166.3707, 1e-3);
+        assert!(fit.anova[3].f_value.is_none());
+    }
+
+    #[test]
+    fn collinear_terms_are_rejected() {
+        let mut individuals = stackloss();
+        for individual in &mut individuals {
+            individual.professional_network_size = individual.age.map(|a| 2.0 * a);
+        }
+        let formula = Formula::parse("salary ~ age + professional_network_size").unwrap();
+        assert!(MultipleRegression::fit(&individuals, &formula).is_err());
+    }
+}
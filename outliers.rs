@@ -0,0 +1,436 @@
+//! Outlier screening of `Individual` fields: univariate z-score, modified
+//! z-score and IQR rules, and multivariate Mahalanobis distance.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+
+use crate::distributions::{ChiSquared, Continuous};
+use crate::individual::{Field, Individual};
+use crate::linalg::{Matrix, Qr};
+use crate::missing::median;
+use crate::summary::Summary;
+
+/// |z| above which a value is flagged by the z-score rule.
+pub const Z_LIMIT: f64 = 3.0;
+/// |modified z| above which a value is flagged (Iglewicz and Hoaglin).
+pub const MODIFIED_Z_LIMIT: f64 = 3.5;
+/// Tukey's fences lie this many IQRs beyond the quartiles.
+pub const IQR_MULTIPLIER: f64 = 1.5;
+/// Squared Mahalanobis distances above this chi-square quantile are flagged.
+pub const MAHALANOBIS_LEVEL: f64 = 0.999;
+/// Makes the MAD comparable to a standard deviation in the modified z-score.
+const MAD_FACTOR: f64 = 0.6745;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutlierMethod {
+    ZScore,
+    Mad,
+    Iqr,
+    Mahalanobis,
+}
+
+impl OutlierMethod {
+    pub const ALL: [OutlierMethod; 4] = [
+        OutlierMethod::ZScore,
+        OutlierMethod::Mad,
+        OutlierMethod::Iqr,
+        OutlierMethod::Mahalanobis,
+    ];
+
+    pub fn parse(name: &str) -> Result<OutlierMethod, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "z" | "zscore" | "z-score" => Ok(OutlierMethod::ZScore),
+            "mad" => Ok(OutlierMethod::Mad),
+            "iqr" => Ok(OutlierMethod::Iqr),
+            "mahalanobis" => Ok(OutlierMethod::Mahalanobis),
+            _ => Err(format!("unknown outlier method '{}' (expected z, mad, iqr or mahalanobis)", name).into()),
+        }
+    }
+
+    /// Flags one field at a time, so flagged values can be clamped to limits.
+    pub fn is_univariate(self) -> bool {
+        self != OutlierMethod::Mahalanobis
+    }
+}
+
+impl fmt::Display for OutlierMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutlierMethod::ZScore => "z-score",
+            OutlierMethod::Mad => "MAD",
+            OutlierMethod::Iqr => "IQR",
+            OutlierMethod::Mahalanobis => "Mahalanobis",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What to do with flagged values before an analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Treatment {
+    /// Drop every flagged individual.
+    Exclude,
+    /// Clamp values beyond a univariate rule's limits to the nearest limit.
+    Winsorize,
+}
+
+impl Treatment {
+    pub fn parse(name: &str) -> Result<Treatment, Box<dyn Error>> {
+        match name.to_lowercase().as_str() {
+            "exclude" => Ok(Treatment::Exclude),
+            "winsorize" => Ok(Treatment::Winsorize),
+            _ => Err(format!("unknown outlier treatment '{}' (expected exclude or winsorize)", name).into()),
+        }
+    }
+}
+
+/// One reason an individual was flagged.
+#[derive(Debug, Clone)]
+pub struct Flag {
+    pub id: usize,
+    pub method: OutlierMethod,
+    /// `None` for Mahalanobis distance, which looks at every field at once.
+    pub field: Option<Field>,
+    /// z, modified z, the value itself for IQR, or the squared distance.
+    pub statistic: f64,
+    pub reason: String,
+}
+
+/// Limits of a univariate rule on one field: values outside are flagged.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub field: Field,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutlierReport {
+    pub fields: Vec<Field>,
+    pub methods: Vec<OutlierMethod>,
+    pub limits: Vec<Limits>,
+    /// Squared-distance cutoff of the Mahalanobis rule, when it was run.
+    pub mahalanobis_cutoff: Option<f64>,
+    /// By method, then field, then individual.
+    pub flags: Vec<Flag>,
+}
+
+impl OutlierReport {
+    /// Screens `fields` of `individuals` with every method in `methods`.
+    /// Univariate rules use each field's observed values and skip boolean
+    /// fields; Mahalanobis distance uses the individuals with every field present.
+    pub fn detect(
+        individuals: &[Individual],
+        fields: &[Field],
+        methods: &[OutlierMethod],
+    ) -> Result<OutlierReport, Box<dyn Error>> {
+        let mut methods = methods.to_vec();
+        methods.sort();
+        methods.dedup();
+        let mut report = OutlierReport {
+            fields: fields.to_vec(),
+            methods: methods.clone(),
+            limits: Vec::new(),
+            mahalanobis_cutoff: None,
+            flags: Vec::new(),
+        };
+
+        for &method in methods.iter().filter(|m| m.is_univariate()) {
+            for &field in fields.iter().filter(|f| !f.is_boolean()) {
+                let values = field.values(individuals);
+                let Some(summary) = Summary::from_column(field.label(), &values) else { continue };
+                let observed: Vec<f64> = values.iter().flatten().copied().collect();
+                let Some(limits) = univariate_limits(method, field, &summary, &observed) else { continue };
+                report.limits.push(limits);
+                for individual in individuals {
+                    let Some(value) = field.value(individual) else { continue };
+                    if value >= limits.lower && value <= limits.upper {
+                        continue;
+                    }
+                    let (statistic, reason) = match method {
+                        OutlierMethod::ZScore => {
+                            let z = (value - summary.mean) / summary.std_dev;
+                            (z, format!("{} z = {:.2} (|z| > {})", field, z, Z_LIMIT))
+                        }
+                        OutlierMethod::Mad => {
+                            // The limits are median +- MODIFIED_Z_LIMIT * MAD / MAD_FACTOR
+                            let mad = (limits.upper - summary.median) * MAD_FACTOR / MODIFIED_Z_LIMIT;
+                            let z = MAD_FACTOR * (value - summary.median) / mad;
+                            (z, format!("{} modified z = {:.2} (|z| > {})", field, z, MODIFIED_Z_LIMIT))
+                        }
+                        _ => (
+                            value,
+                            format!(
+                                "{} = {} outside IQR fences [{:.2}, {:.2}]",
+                                field, value, limits.lower, limits.upper
+                            ),
+                        ),
+                    };
+                    report.flags.push(Flag {
+                        id: individual.id,
+                        method,
+                        field: Some(field),
+                        statistic,
+                        reason,
+                    });
+                }
+            }
+        }
+
+        if methods.contains(&OutlierMethod::Mahalanobis) {
+            report.mahalanobis(individuals)?;
+        }
+        Ok(report)
+    }
+
+    fn mahalanobis(&mut self, individuals: &[Individual]) -> Result<(), Box<dyn Error>> {
+        let k = self.fields.len();
+        let complete: Vec<(usize, Vec<f64>)> = individuals
+            .iter()
+            .filter_map(|ind| Some((ind.id, self.fields.iter().map(|f| f.value(ind)).collect::<Option<Vec<f64>>>()?)))
+            .collect();
+        let n = complete.len();
+        if k == 0 || n <= k {
+            return Err(format!("{} complete individuals are too few for a {}-field Mahalanobis distance", n, k).into());
+        }
+        let means: Vec<f64> = (0..k)
+            .map(|j| complete.iter().map(|(_, row)| row[j]).sum::<f64>() / n as f64)
+            .collect();
+        let centered: Vec<Vec<f64>> = complete
+            .iter()
+            .map(|(_, row)| row.iter().zip(&means).map(|(v, m)| v - m).collect())
+            .collect();
+        // With centered rows X, S = X'X / (n - 1), so S^-1 = (n - 1) (X'X)^-1
+        let qr = Qr::new(&Matrix::from_rows(&centered));
+        if !qr.is_full_rank() {
+            return Err("fields are collinear, so the Mahalanobis distance is undefined".into());
+        }
+        let inverse = qr.xtx_inverse();
+        let cutoff = ChiSquared::new(k as f64).quantile(MAHALANOBIS_LEVEL);
+        self.mahalanobis_cutoff = Some(cutoff);
+        for ((id, _), d) in complete.iter().zip(&centered) {
+            let distance: f64 = (0..k)
+                .map(|i| (0..k).map(|j| d[i] * inverse[(i, j)] * d[j]).sum::<f64>())
+                .sum::<f64>()
+                * (n - 1) as f64;
+            if distance > cutoff {
+                self.flags.push(Flag {
+                    id: *id,
+                    method: OutlierMethod::Mahalanobis,
+                    field: None,
+                    statistic: distance,
+                    reason: format!("Mahalanobis D^2 = {:.2} (> {:.2}, chi-square {} df)", distance, cutoff, k),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Reasons by flagged id, in id order.
+    pub fn by_id(&self) -> BTreeMap<usize, Vec<&Flag>> {
+        let mut flagged: BTreeMap<usize, Vec<&Flag>> = BTreeMap::new();
+        for flag in &self.flags {
+            flagged.entry(flag.id).or_default().push(flag);
+        }
+        flagged
+    }
+
+    /// Individuals not flagged by any method.
+    pub fn exclude(&self, individuals: &[Individual]) -> Vec<Individual> {
+        let flagged = self.by_id();
+        individuals
+            .iter()
+            .filter(|ind| !flagged.contains_key(&ind.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Copies of `individuals` with each screened field clamped to the
+    /// tightest univariate limits. Mahalanobis flags have no per-field limit
+    /// and are left as they are.
+    pub fn winsorize(&self, individuals: &[Individual]) -> Vec<Individual> {
+        let mut out = individuals.to_vec();
+        for &field in &self.fields {
+            let limits: Vec<&Limits> = self.limits.iter().filter(|l| l.field == field).collect();
+            if limits.is_empty() {
+                continue;
+            }
+            let lower = limits.iter().map(|l| l.lower).fold(f64::NEG_INFINITY, f64::max);
+            let upper = limits.iter().map(|l| l.upper).fold(f64::INFINITY, f64::min);
+            for individual in &mut out {
+                if let Some(value) = field.value(individual) {
+                    field.set(individual, Some(value.clamp(lower, upper)));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn apply(&self, treatment: Treatment, individuals: &[Individual]) -> Vec<Individual> {
+        match treatment {
+            Treatment::Exclude => self.exclude(individuals),
+            Treatment::Winsorize => self.winsorize(individuals),
+        }
+    }
+
+    /// Flag counts by method and field, then the flagged ids with their
+    /// reasons, most-flagged first; `limit` caps how many ids are listed.
+    pub fn render(&self, limit: Option<usize>) -> String {
+        let mut out = String::new();
+        let width = self.fields.iter().map(|f| f.label().len()).max().unwrap_or(0).max(11);
+        let methods: Vec<OutlierMethod> = self.methods.iter().copied().filter(|m| m.is_univariate()).collect();
+        if !methods.is_empty() {
+            write!(out, "{:<width$}", "Field").unwrap();
+            for method in &methods {
+                write!(out, " {:>8}", method.to_string()).unwrap();
+            }
+            writeln!(out).unwrap();
+            for &field in &self.fields {
+                write!(out, "{:<width$}", field.label()).unwrap();
+                for &method in &methods {
+                    let count = self
+                        .flags
+                        .iter()
+                        .filter(|f| f.method == method && f.field == Some(field))
+                        .count();
+                    write!(out, " {:>8}", count).unwrap();
+                }
+                writeln!(out).unwrap();
+            }
+        }
+        if let Some(cutoff) = self.mahalanobis_cutoff {
+            let count = self.flags.iter().filter(|f| f.method == OutlierMethod::Mahalanobis).count();
+            writeln!(
+                out,
+                "Mahalanobis distance over {} fields: {} flagged (D^2 > {:.2})",
+                self.fields.len(),
+                count,
+                cutoff
+            )
+            .unwrap();
+        }
+
+        let mut flagged: Vec<(usize, Vec<&Flag>)> = self.by_id().into_iter().collect();
+        flagged.sort_by_key(|(_, flags)| std::cmp::Reverse(flags.len()));
+        writeln!(out, "Flagged individuals: {}", flagged.len()).unwrap();
+        let shown = limit.unwrap_or(flagged.len()).min(flagged.len());
+        for (id, flags) in &flagged[..shown] {
+            let reasons: Vec<&str> = flags.iter().map(|f| f.reason.as_str()).collect();
+            writeln!(out, "  {:>6}: {}", id, reasons.join("; ")).unwrap();
+        }
+        if shown < flagged.len() {
+            writeln!(out, "  ... and {} more", flagged.len() - shown).unwrap();
+        }
+        out
+    }
+
+    /// One row per flag.
+    pub fn write_rows<W: std::io::Write>(&self, wtr: &mut csv::Writer<W>) -> Result<(), Box<dyn Error>> {
+        wtr.write_record(["id", "method", "field", "statistic", "reason"])?;
+        let mut flags: Vec<&Flag> = self.flags.iter().collect();
+        flags.sort_by_key(|f| f.id);
+        for flag in flags {
+            wtr.write_record([
+                flag.id.to_string(),
+                flag.method.to_string(),
+                flag.field.map_or(String::new(), |f| f.label().to_string()),
+                flag.statistic.to_string(),
+                flag.reason.clone(),
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+/// Limits of a univariate rule, or `None` when the field has no spread to
+/// scale by (every value, or more than half of them, equal).
+fn univariate_limits(method: OutlierMethod, field: Field, summary: &Summary, observed: &[f64]) -> Option<Limits> {
+    let (lower, upper) = match method {
+        OutlierMethod::ZScore => {
+            let margin = Z_LIMIT * summary.std_dev;
+            (summary.mean - margin, summary.mean + margin)
+        }
+        OutlierMethod::Mad => {
+            let deviations: Vec<f64> = observed.iter().map(|v| (v - summary.median).abs()).collect();
+            let mad = median(&deviations)?;
+            let margin = MODIFIED_Z_LIMIT * mad / MAD_FACTOR;
+            (summary.median - margin, summary.median + margin)
+        }
+        OutlierMethod::Iqr => {
+            let margin = IQR_MULTIPLIER * summary.iqr;
+            (summary.q1 - margin, summary.q3 + margin)
+        }
+        OutlierMethod::Mahalanobis => return None,
+    };
+    (upper > lower).then_some(Limits { field, lower, upper })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    /// Ages 1 to 20 and one of 100, with salary rising with age except for individual 5.
+    fn individuals() -> Vec<Individual> {
+        (0..21)
+            .map(|id| {
+                let age = if id == 20 { 100.0 } else { (id + 1) as f64 };
+                let salary = if id == 5 { 1000.0 } else { 100.0 * (id + 1) as f64 };
+                Individual { id, age: Some(age), salary: Some(salary), certifications: Some(id % 2 == 0), ..Default::default() }
+            })
+            .collect()
+    }
+
+    fn flagged(report: &OutlierReport, method: OutlierMethod) -> Vec<usize> {
+        report.flags.iter().filter(|f| f.method == method).map(|f| f.id).collect()
+    }
+
+    #[test]
+    fn univariate_rules_use_their_published_limits() {
+        let methods = [OutlierMethod::ZScore, OutlierMethod::Mad, OutlierMethod::Iqr];
+        let report = OutlierReport::detect(&individuals(), &[Field::Age], &methods).unwrap();
+        let limits: Vec<(f64, f64)> = report.limits.iter().map(|l| (l.lower, l.upper)).collect();
+        // Mean 14.762 and standard deviation 20.364; median 11 and MAD 5; quartiles 6 and 16
+        close(limits[0].1, 14.761904761904763 + 3.0 * 20.36395040728778, 1e-9);
+        close(limits[1].1, 11.0 + 3.5 * 5.0 / 0.6745, 1e-9);
+        assert_eq!(limits[2], (-9.0, 31.0));
+        for method in methods {
+            assert_eq!(flagged(&report, method), vec![20]);
+        }
+        let z = report.flags.iter().find(|f| f.method == OutlierMethod::ZScore).unwrap();
+        close(z.statistic, 4.1857347682201445, 1e-9);
+        let modified = report.flags.iter().find(|f| f.method == OutlierMethod::Mad).unwrap();
+        close(modified.statistic, 0.6745 * 89.0 / 5.0, 1e-9);
+    }
+
+    #[test]
+    fn boolean_fields_are_not_screened_one_at_a_time() {
+        let report = OutlierReport::detect(&individuals(), &[Field::Certifications], &[OutlierMethod::ZScore]).unwrap();
+        assert!(report.limits.is_empty());
+        assert!(report.flags.is_empty());
+    }
+
+    #[test]
+    fn mahalanobis_flags_individuals_off_the_joint_trend() {
+        let report =
+            OutlierReport::detect(&individuals(), &[Field::Age, Field::Salary], &[OutlierMethod::Mahalanobis]).unwrap();
+        close(report.mahalanobis_cutoff.unwrap(), ChiSquared::new(2.0).quantile(0.999), 1e-9);
+        assert!(flagged(&report, OutlierMethod::Mahalanobis).contains(&20));
+        assert!(OutlierReport::detect(&individuals()[..2], &[Field::Age, Field::Salary], &[OutlierMethod::Mahalanobis]).is_err());
+    }
+
+    #[test]
+    fn treatments_exclude_or_clamp_flagged_values() {
+        let people = individuals();
+        let report = OutlierReport::detect(&people, &[Field::Age], &[OutlierMethod::ZScore, OutlierMethod::Iqr]).unwrap();
+        let kept = report.apply(Treatment::Exclude, &people);
+        assert_eq!(kept.len(), 20);
+        assert!(kept.iter().all(|i| i.id != 20));
+        // Clamped to the tighter of the z-score and IQR upper limits
+        let clamped = report.apply(Treatment::Winsorize, &people);
+        assert_eq!(clamped[20].age, Some(31.0));
+        assert_eq!(clamped[3].age, Some(4.0));
+    }
+}
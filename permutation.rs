@@ -0,0 +1,290 @@
+//! Permutation tests: p-values from reshuffling the data instead of a
+//! reference distribution, for correlations and for differences between groups.
+
+use std::error::Error;
+use std::fmt::Write;
+
+use crate::anova::Sample;
+use crate::correlation::{correlation_test, stars, CorrelationMethod};
+use crate::distributions::ln_gamma;
+use crate::individual::{Field, Individual};
+use crate::missing::pairwise_complete;
+use crate::rng::Rng;
+
+pub const DEFAULT_PERMUTATIONS: usize = 9999;
+/// Relative slack when counting permuted statistics at least as extreme as the
+/// observed one, so that floating-point noise does not drop ties.
+const TOLERANCE: f64 = 1e-10;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PermutationResult {
+    /// The statistic on the data as observed.
+    pub observed: f64,
+    pub p_value: f64,
+    /// Arrangements evaluated: all of them when `exact`, else the random shuffles.
+    pub permutations: usize,
+    /// Every distinct arrangement was enumerated, so `p_value` is exact.
+    /// Otherwise it is the Monte Carlo estimate (count + 1) / (shuffles + 1).
+    pub exact: bool,
+}
+
+impl PermutationResult {
+    /// How the p-value was obtained, e.g. "exact over 120 arrangements".
+    pub fn describe(&self) -> String {
+        if self.exact {
+            format!("exact over {} arrangements", self.permutations)
+        } else {
+            format!("Monte Carlo, {} shuffles", self.permutations)
+        }
+    }
+}
+
+/// Permutation settings. Data with no more distinct arrangements than
+/// `permutations` are enumerated exactly.
+#[derive(Debug, Clone, Copy)]
+pub struct Permutation {
+    pub permutations: usize,
+    pub seed: u64,
+}
+
+impl Permutation {
+    pub fn new(permutations: usize, seed: u64) -> Self {
+        Permutation { permutations, seed }
+    }
+
+    fn result(&self, observed: f64, extreme: usize, total: usize, exact: bool) -> PermutationResult {
+        PermutationResult {
+            observed,
+            p_value: if exact {
+                extreme as f64 / total as f64
+            } else {
+                (extreme + 1) as f64 / (total + 1) as f64
+            },
+            permutations: total,
+            exact,
+        }
+    }
+
+    /// Two-sided test of no association: shuffles `y` against `x` and counts
+    /// correlations at least as large in absolute value as the observed one.
+    pub fn correlation(&self, method: CorrelationMethod, x: &[f64], y: &[f64]) -> Option<PermutationResult> {
+        let observed = correlation_test(method, x, y)?.estimate;
+        let threshold = observed.abs() * (1.0 - TOLERANCE);
+        let is_extreme = |y: &[f64]| correlation_test(method, x, y).is_some_and(|t| t.estimate.abs() >= threshold);
+        let n = x.len();
+        let mut y = y.to_vec();
+
+        if ln_factorial(n) <= (self.permutations as f64).ln() {
+            // Heap's algorithm visits all n! orderings of y
+            let mut extreme = usize::from(is_extreme(&y));
+            let mut total = 1;
+            let mut c = vec![0; n];
+            let mut i = 1;
+            while i < n {
+                if c[i] < i {
+                    y.swap(if i % 2 == 0 { 0 } else { c[i] }, i);
+                    extreme += usize::from(is_extreme(&y));
+                    total += 1;
+                    c[i] += 1;
+                    i = 1;
+                } else {
+                    c[i] = 0;
+                    i += 1;
+                }
+            }
+            return Some(self.result(observed, extreme, total, true));
+        }
+
+        let mut rng = Rng::new(self.seed);
+        let mut extreme = 0;
+        for _ in 0..self.permutations {
+            rng.shuffle(&mut y);
+            extreme += usize::from(is_extreme(&y));
+        }
+        Some(self.result(observed, extreme, self.permutations, false))
+    }
+
+    /// Test of no difference between groups: reassigns values to groups of the
+    /// same sizes and counts one-way ANOVA F statistics at least as large as
+    /// the observed one. With two groups this is the two-sided test of the
+    /// difference in means.
+    pub fn group_difference(&self, samples: &[Sample]) -> Result<PermutationResult, Box<dyn Error>> {
+        let sizes: Vec<usize> = samples.iter().map(|(_, v)| v.len()).filter(|&n| n > 0).collect();
+        let n: usize = sizes.iter().sum();
+        if sizes.len() < 2 || n <= sizes.len() {
+            return Err("Need at least two non-empty groups and more values than groups".into());
+        }
+        let values: Vec<f64> = samples.iter().flat_map(|(_, v)| v.iter().copied()).collect();
+        let mut labels: Vec<usize> = sizes.iter().enumerate().flat_map(|(g, &size)| vec![g; size]).collect();
+
+        let observed = f_statistic(&values, &labels, &sizes);
+        let threshold = observed * (1.0 - TOLERANCE);
+        let is_extreme = |labels: &[usize]| f_statistic(&values, labels, &sizes) >= threshold;
+
+        let ln_arrangements = ln_factorial(n) - sizes.iter().map(|&s| ln_factorial(s)).sum::<f64>();
+        if ln_arrangements <= (self.permutations as f64).ln() {
+            let (mut extreme, mut total) = (0, 0);
+            let mut remaining = sizes.clone();
+            enumerate_assignments(&mut labels, 0, &mut remaining, &mut |labels| {
+                extreme += usize::from(is_extreme(labels));
+                total += 1;
+            });
+            return Ok(self.result(observed, extreme, total, true));
+        }
+
+        let mut rng = Rng::new(self.seed);
+        let mut extreme = 0;
+        for _ in 0..self.permutations {
+            rng.shuffle(&mut labels);
+            extreme += usize::from(is_extreme(&labels));
+        }
+        Ok(self.result(observed, extreme, self.permutations, false))
+    }
+}
+
+fn ln_factorial(n: usize) -> f64 {
+    ln_gamma(n as f64 + 1.0)
+}
+
+/// One-way ANOVA F of `values` split by group `labels`.
+fn f_statistic(values: &[f64], labels: &[usize], sizes: &[usize]) -> f64 {
+    let mut sums = vec![0.0; sizes.len()];
+    for (&v, &g) in values.iter().zip(labels) {
+        sums[g] += v;
+    }
+    let n = values.len() as f64;
+    let grand_mean = sums.iter().sum::<f64>() / n;
+    let ss_total: f64 = values.iter().map(|v| (v - grand_mean).powi(2)).sum();
+    let ss_between: f64 = sums
+        .iter()
+        .zip(sizes)
+        .map(|(sum, &size)| size as f64 * (sum / size as f64 - grand_mean).powi(2))
+        .sum();
+    let k = sizes.len() as f64;
+    let ss_within = ss_total - ss_between;
+    if ss_within <= 0.0 {
+        return f64::INFINITY;
+    }
+    (ss_between / (k - 1.0)) / (ss_within / (n - k))
+}
+
+/// Calls `visit` with every distinct way of labelling positions `position..`
+/// using the `remaining` count of each label.
+fn enumerate_assignments(
+    labels: &mut [usize],
+    position: usize,
+    remaining: &mut [usize],
+    visit: &mut dyn FnMut(&[usize]),
+) {
+    if position == labels.len() {
+        visit(labels);
+        return;
+    }
+    for g in 0..remaining.len() {
+        if remaining[g] > 0 {
+            remaining[g] -= 1;
+            labels[position] = g;
+            enumerate_assignments(labels, position + 1, remaining, visit);
+            remaining[g] += 1;
+        }
+    }
+}
+
+/// Permutation tests of every pair of `fields`, on the individuals where both are present.
+pub fn correlation_tests(
+    individuals: &[Individual],
+    fields: &[Field],
+    method: CorrelationMethod,
+    permutation: &Permutation,
+) -> Vec<(String, Option<PermutationResult>)> {
+    let mut results = Vec::new();
+    for (i, &a) in fields.iter().enumerate() {
+        for &b in &fields[i + 1..] {
+            let (x, y) = pairwise_complete(&a.values(individuals), &b.values(individuals));
+            results.push((format!("{} x {}", a, b), permutation.correlation(method, &x, &y)));
+        }
+    }
+    results
+}
+
+/// Text table of permutation results; `None` marks a test that could not be run.
+pub fn render(title: &str, results: &[(String, Option<PermutationResult>)], permutation: &Permutation) -> String {
+    let name_width = results.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{} (up to {} permutations, seed {})",
+        title, permutation.permutations, permutation.seed
+    )
+    .unwrap();
+    for (name, result) in results {
+        match result {
+            Some(r) => writeln!(
+                out,
+                "{:<name_width$} statistic = {:>10.4}, p = {:.4}{} ({})",
+                name,
+                r.observed,
+                r.p_value,
+                stars(r.p_value),
+                r.describe()
+            )
+            .unwrap(),
+            None => writeln!(out, "{:<name_width$} not enough data", name).unwrap(),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const X: [f64; 6] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    const Y: [f64; 6] = [2.0, 1.0, 4.0, 3.0, 6.0, 5.0];
+
+    fn sample(name: &str, values: &[f64]) -> Sample {
+        (name.to_string(), values.to_vec())
+    }
+
+    #[test]
+    fn heaps_enumeration_gives_the_exact_correlation_p_value() {
+        // 42 of the 720 orderings of Y have |r| >= 0.8286
+        let result = Permutation::new(DEFAULT_PERMUTATIONS, 1).correlation(CorrelationMethod::Pearson, &X, &Y).unwrap();
+        assert!(result.exact);
+        assert_eq!(result.permutations, 720);
+        assert!((result.observed - 0.8285714285714286).abs() < 1e-12);
+        assert!((result.p_value - 42.0 / 720.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn monte_carlo_p_value_is_seeded_and_never_zero() {
+        let permutation = Permutation::new(500, 3);
+        let result = permutation.correlation(CorrelationMethod::Pearson, &X, &Y).unwrap();
+        assert!(!result.exact);
+        assert_eq!(result.permutations, 500);
+        assert!((result.p_value - 42.0 / 720.0).abs() < 0.04);
+        let again = permutation.correlation(CorrelationMethod::Pearson, &X, &Y).unwrap();
+        assert_eq!(result.p_value, again.p_value);
+
+        let x: Vec<f64> = (0..20).map(f64::from).collect();
+        let perfect = Permutation::new(99, 3).correlation(CorrelationMethod::Spearman, &x, &x).unwrap();
+        assert_eq!(perfect.p_value, 1.0 / 100.0);
+    }
+
+    #[test]
+    fn exact_group_difference_counts_both_tails() {
+        // Of the 20 ways to split six values into two groups of three, only the
+        // observed split and its mirror image separate the groups completely
+        let samples = [sample("low", &[1.0, 2.0, 3.0]), sample("high", &[4.0, 5.0, 6.0])];
+        let result = Permutation::new(DEFAULT_PERMUTATIONS, 1).group_difference(&samples).unwrap();
+        assert!(result.exact);
+        assert_eq!(result.permutations, 20);
+        assert!((result.p_value - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn group_difference_needs_two_groups() {
+        let samples = [sample("only", &[1.0, 2.0, 3.0]), sample("empty", &[])];
+        assert!(Permutation::new(99, 1).group_difference(&samples).is_err());
+    }
+}
@@ -0,0 +1,635 @@
+//! Regression estimators that resist outliers, reported in the same shape as
+//! the least squares line so they can be compared side by side.
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+
+use crate::correlation::pearson;
+use crate::distributions::{Continuous, FisherF, Normal};
+use crate::linalg::{Matrix, Qr};
+use crate::missing::median;
+use crate::ols::{Coefficient, SimpleRegression, CONFIDENCE_LEVEL};
+use crate::rng::Rng;
+use crate::summary::percentile_sorted;
+
+/// Huber's tuning constant: 95% efficiency when the errors are normal.
+pub const HUBER_K: f64 = 1.345;
+/// Random minimal subsets tried by RANSAC.
+pub const RANSAC_TRIALS: usize = 1000;
+/// RANSAC inliers lie within this many robust residual scales of the candidate fit.
+pub const RANSAC_THRESHOLD: f64 = 2.5;
+const MAX_ITERATIONS: usize = 200;
+const TOLERANCE: f64 = 1e-10;
+/// Consistency factor making the MAD estimate the standard deviation under normality.
+const MAD_SCALE: f64 = 1.4826;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegressionMethod {
+    Ols,
+    TheilSen,
+    Lad,
+    Huber,
+    Ransac,
+}
+
+impl RegressionMethod {
+    pub const ALL: [RegressionMethod; 5] = [
+        RegressionMethod::Ols,
+        RegressionMethod::TheilSen,
+        RegressionMethod::Lad,
+        RegressionMethod::Huber,
+        RegressionMethod::Ransac,
+    ];
+
+    pub fn parse(name: &str) -> Result<RegressionMethod, Box<dyn Error>> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "ols" => Ok(RegressionMethod::Ols),
+            "theil-sen" | "theilsen" => Ok(RegressionMethod::TheilSen),
+            "lad" => Ok(RegressionMethod::Lad),
+            "huber" => Ok(RegressionMethod::Huber),
+            "ransac" => Ok(RegressionMethod::Ransac),
+            _ => Err(format!(
+                "unknown regression method '{}' (expected ols, theil-sen, lad, huber or ransac)",
+                name
+            )
+            .into()),
+        }
+    }
+}
+
+impl fmt::Display for RegressionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RegressionMethod::Ols => "Least squares",
+            RegressionMethod::TheilSen => "Theil-Sen",
+            RegressionMethod::Lad => "Least absolute deviations",
+            RegressionMethod::Huber => "Huber M-estimation",
+            RegressionMethod::Ransac => "RANSAC",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Coefficients of a robust fit on a design matrix whose first column is the intercept.
+#[derive(Debug, Clone)]
+pub struct RobustFit {
+    pub coefficients: Vec<f64>,
+    /// Approximate large-sample covariance of the coefficients.
+    pub covariance: Matrix,
+    /// Robust estimate of the error standard deviation.
+    pub scale: f64,
+    pub df_residual: f64,
+    /// Confidence interval of the slope when the method has its own (Sen's
+    /// rank interval for Theil-Sen), instead of one from the covariance.
+    pub slope_interval: Option<(f64, f64)>,
+}
+
+/// Fits `y` on the columns of `x` with `method`. Theil-Sen supports a single
+/// predictor; `seed` drives the random subsets of RANSAC.
+pub fn fit_design(method: RegressionMethod, x: &Matrix, y: &[f64], seed: u64) -> Result<RobustFit, Box<dyn Error>> {
+    let (n, p) = (x.rows, x.cols);
+    if n <= p {
+        return Err(format!("{} observations are too few for {} coefficients", n, p).into());
+    }
+    let qr = Qr::new(x);
+    if !qr.is_full_rank() {
+        return Err("design matrix is rank deficient (collinear or constant terms)".into());
+    }
+    let df_residual = (n - p) as f64;
+    match method {
+        RegressionMethod::Ols => {
+            let coefficients = qr.solve(y);
+            let sse: f64 = residuals(x, y, &coefficients).iter().map(|e| e * e).sum();
+            let sigma2 = sse / df_residual;
+            Ok(RobustFit {
+                coefficients,
+                covariance: scaled(&qr.xtx_inverse(), sigma2),
+                scale: sigma2.sqrt(),
+                df_residual,
+                slope_interval: None,
+            })
+        }
+        RegressionMethod::TheilSen => theil_sen(x, y, &qr),
+        RegressionMethod::Lad => {
+            let coefficients = least_absolute_deviations(x, y, qr.solve(y));
+            Ok(median_type_fit(x, y, &qr, coefficients, None))
+        }
+        RegressionMethod::Huber => huber(x, y, &qr),
+        RegressionMethod::Ransac => ransac(x, y, seed),
+    }
+}
+
+/// Fits `y = intercept + slope * x` with `method`, filling every field of
+/// `SimpleRegression`. For the robust methods the standard errors are
+/// large-sample approximations, `residual_std_error` is the robust scale,
+/// R-squared is 1 - SSE/SST of the robust line, and the F test is the squared
+/// slope t test.
+pub fn fit_line(method: RegressionMethod, x: &[f64], y: &[f64], seed: u64) -> Result<SimpleRegression, Box<dyn Error>> {
+    if method == RegressionMethod::Ols {
+        return SimpleRegression::fit(x, y);
+    }
+    if x.len() != y.len() {
+        return Err("Input vectors must be of equal length".into());
+    }
+    if x.len() < 3 {
+        return Err(format!("need at least 3 observations, got {}", x.len()).into());
+    }
+    let design = Matrix::from_columns(&[vec![1.0; x.len()], x.to_vec()]);
+    let fit = fit_design(method, &design, y, seed)?;
+    let (intercept, slope) = (fit.coefficients[0], fit.coefficients[1]);
+
+    let nf = x.len() as f64;
+    let mean_y = y.iter().sum::<f64>() / nf;
+    let sse: f64 = x.iter().zip(y).map(|(xi, yi)| (yi - intercept - slope * xi).powi(2)).sum();
+    let sst: f64 = y.iter().map(|yi| (yi - mean_y).powi(2)).sum();
+    let r_squared = if sst == 0.0 { 0.0 } else { 1.0 - sse / sst };
+
+    let mut slope = Coefficient::new(slope, fit.covariance[(1, 1)].sqrt(), fit.df_residual);
+    if let Some(ci) = fit.slope_interval {
+        slope.ci = ci;
+    }
+    let f_statistic = slope.t_value * slope.t_value;
+    Ok(SimpleRegression {
+        method,
+        n: x.len(),
+        slope,
+        intercept: Coefficient::new(intercept, fit.covariance[(0, 0)].sqrt(), fit.df_residual),
+        correlation: pearson(x, y),
+        r_squared,
+        adj_r_squared: 1.0 - (1.0 - r_squared) * (nf - 1.0) / fit.df_residual,
+        residual_std_error: fit.scale,
+        df_residual: fit.df_residual,
+        f_statistic,
+        f_p_value: FisherF::new(1.0, fit.df_residual).sf(f_statistic),
+    })
+}
+
+/// Side-by-side table of lines fitted to the same data by different methods.
+pub fn render_comparison(title: &str, fits: &[SimpleRegression]) -> String {
+    let width = fits.iter().map(|f| f.method.to_string().len()).max().unwrap_or(0);
+    let mut out = String::new();
+    writeln!(out, "{}", title).unwrap();
+    writeln!(
+        out,
+        "{:<width$} {:>14} {:>12} {:>12} {:>29} {:>9} {:>14}",
+        "Method",
+        "Intercept",
+        "Slope",
+        "Slope SE",
+        format!("{:.0}% CI of slope", CONFIDENCE_LEVEL * 100.0),
+        "R-squared",
+        "Scale"
+    )
+    .unwrap();
+    for fit in fits {
+        writeln!(
+            out,
+            "{:<width$} {:>14.4} {:>12.4} {:>12.4} {:>29} {:>9.4} {:>14.4}",
+            fit.method.to_string(),
+            fit.intercept.estimate,
+            fit.slope.estimate,
+            fit.slope.std_error,
+            format!("[{:.4}, {:.4}]", fit.slope.ci.0, fit.slope.ci.1),
+            fit.r_squared,
+            fit.residual_std_error
+        )
+        .unwrap();
+    }
+    writeln!(out, "Scale: residual standard error for least squares, a robust estimate otherwise").unwrap();
+    out
+}
+
+fn residuals(x: &Matrix, y: &[f64], coefficients: &[f64]) -> Vec<f64> {
+    y.iter().zip(x.mul_vec(coefficients)).map(|(yi, fi)| yi - fi).collect()
+}
+
+fn scaled(m: &Matrix, factor: f64) -> Matrix {
+    let mut out = m.clone();
+    for i in 0..m.rows {
+        for j in 0..m.cols {
+            out[(i, j)] *= factor;
+        }
+    }
+    out
+}
+
+/// Normalized median absolute deviation of residuals from zero.
+fn mad_scale(residuals: &[f64]) -> f64 {
+    let absolute: Vec<f64> = residuals.iter().map(|e| e.abs()).collect();
+    MAD_SCALE * median(&absolute).unwrap_or(0.0)
+}
+
+/// Weighted least squares, or `None` when the weighted design is singular.
+fn weighted_least_squares(x: &Matrix, y: &[f64], weights: &[f64]) -> Option<Vec<f64>> {
+    let rows: Vec<Vec<f64>> = (0..x.rows)
+        .map(|i| x.row(i).iter().map(|v| v * weights[i].sqrt()).collect())
+        .collect();
+    let wy: Vec<f64> = y.iter().zip(weights).map(|(v, w)| v * w.sqrt()).collect();
+    let qr = Qr::new(&Matrix::from_rows(&rows));
+    qr.is_full_rank().then(|| qr.solve(&wy))
+}
+
+fn converged(old: &[f64], new: &[f64]) -> bool {
+    old.iter().zip(new).all(|(a, b)| (a - b).abs() <= TOLERANCE * a.abs().max(1.0))
+}
+
+/// Minimizes the sum of absolute residuals by iteratively reweighted least
+/// squares from `start`, with weights 1 / |residual|.
+fn least_absolute_deviations(x: &Matrix, y: &[f64], start: Vec<f64>) -> Vec<f64> {
+    let mut coefficients = start;
+    let floor = TOLERANCE * y.iter().fold(0.0_f64, |a, b| a.max(b.abs())).max(1.0);
+    for _ in 0..MAX_ITERATIONS {
+        let weights: Vec<f64> = residuals(x, y, &coefficients).iter().map(|e| 1.0 / e.abs().max(floor)).collect();
+        let Some(next) = weighted_least_squares(x, y, &weights) else { break };
+        let done = converged(&coefficients, &next);
+        coefficients = next;
+        if done {
+            break;
+        }
+    }
+    coefficients
+}
+
+/// Fit of a median-type estimator, with the iid large-sample covariance of
+/// median regression: (sparsity / 2)^2 (X'X)^-1, the sparsity 1 / f(0) being
+/// estimated from residual quantiles with the Hall-Sheather bandwidth.
+fn median_type_fit(
+    x: &Matrix,
+    y: &[f64],
+    qr: &Qr,
+    coefficients: Vec<f64>,
+    slope_interval: Option<(f64, f64)>,
+) -> RobustFit {
+    let mut sorted = residuals(x, y, &coefficients);
+    let scale = mad_scale(&sorted);
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = x.rows as f64;
+    let z = Normal.quantile(0.5 + CONFIDENCE_LEVEL / 2.0);
+    let density_at_median = 1.0 / (2.0 * std::f64::consts::PI).sqrt();
+    let bandwidth = (n.powf(-1.0 / 3.0) * z.powf(2.0 / 3.0) * (1.5 * density_at_median.powi(2)).powf(1.0 / 3.0)).min(0.49);
+    let sparsity = (percentile_sorted(&sorted, (0.5 + bandwidth) * 100.0)
+        - percentile_sorted(&sorted, (0.5 - bandwidth) * 100.0))
+        / (2.0 * bandwidth);
+    RobustFit {
+        coefficients,
+        covariance: scaled(&qr.xtx_inverse(), (sparsity / 2.0).powi(2)),
+        scale,
+        df_residual: (x.rows - x.cols) as f64,
+        slope_interval,
+    }
+}
+
+/// Median of pairwise slopes, with Sen's distribution-free interval from the
+/// null variance of Kendall's S (corrected for ties in x). The slopes are
+/// never listed: each order statistic is found by counting, so time is
+/// O(n log n) per statistic and memory O(n), whatever the number of pairs.
+fn theil_sen(x: &Matrix, y: &[f64], qr: &Qr) -> Result<RobustFit, Box<dyn Error>> {
+    if x.cols != 2 {
+        return Err("Theil-Sen regression supports a single predictor".into());
+    }
+    let xs = x.column(1);
+    let n = xs.len();
+    let slopes = PairwiseSlopes::new(&xs, y);
+    let count = slopes.pairs;
+    if count == 0 {
+        return Err("predictor has zero variance".into());
+    }
+    let slope = if count.is_multiple_of(2) {
+        (slopes.select(count / 2 - 1) + slopes.select(count / 2)) / 2.0
+    } else {
+        slopes.select(count / 2)
+    };
+    let intercept = median(&xs.iter().zip(y).map(|(xi, yi)| yi - slope * xi).collect::<Vec<_>>()).unwrap_or(0.0);
+
+    let tie_term: f64 = slopes
+        .groups
+        .iter()
+        .map(|&(start, end)| {
+            let t = (end - start) as f64;
+            t * (t - 1.0) * (2.0 * t + 5.0)
+        })
+        .sum();
+    let nf = n as f64;
+    let variance_s = (nf * (nf - 1.0) * (2.0 * nf + 5.0) - tie_term) / 18.0;
+    let z = Normal.quantile(0.5 + CONFIDENCE_LEVEL / 2.0);
+    let c = z * variance_s.sqrt();
+    let total = count as f64;
+    // 1-based ranks (N - C) / 2 and (N + C) / 2 + 1 of the ordered slopes
+    let rank = |r: f64| (r.round().clamp(1.0, total) as usize) - 1;
+    let interval = (slopes.select(rank((total - c) / 2.0)), slopes.select(rank((total + c) / 2.0 + 1.0)));
+
+    let mut fit = median_type_fit(x, y, qr, vec![intercept, slope], Some(interval));
+    // The median-regression approximation understates the slope's spread; use Sen's interval instead
+    let slope_se = (interval.1 - interval.0) / (2.0 * z);
+    let ratio = slope_se / fit.covariance[(1, 1)].sqrt();
+    if ratio.is_finite() {
+        for j in 0..2 {
+            fit.covariance[(1, j)] *= ratio;
+            fit.covariance[(j, 1)] *= ratio;
+        }
+    }
+    Ok(fit)
+}
+
+/// The slopes between every two points with different x, as order statistics.
+struct PairwiseSlopes {
+    /// Points in increasing order of x.
+    x: Vec<f64>,
+    y: Vec<f64>,
+    /// Index ranges of points sharing an x value.
+    groups: Vec<(usize, usize)>,
+    /// Number of slopes.
+    pairs: usize,
+}
+
+impl PairwiseSlopes {
+    fn new(xs: &[f64], y: &[f64]) -> Self {
+        let mut order: Vec<usize> = (0..xs.len()).collect();
+        order.sort_by(|&a, &b| xs[a].total_cmp(&xs[b]));
+        let x: Vec<f64> = order.iter().map(|&i| xs[i]).collect();
+        let y: Vec<f64> = order.iter().map(|&i| y[i]).collect();
+        let mut groups = Vec::new();
+        let mut start = 0;
+        for end in 1..=x.len() {
+            if end == x.len() || x[end] != x[start] {
+                groups.push((start, end));
+                start = end;
+            }
+        }
+        let n = x.len();
+        let tied: usize = groups.iter().map(|&(start, end)| (end - start) * (end - start - 1) / 2).sum();
+        PairwiseSlopes { x, y, groups, pairs: n * (n - 1) / 2 - tied }
+    }
+
+    /// Slopes at most `t`. For x_i < x_j the slope exceeds t exactly when
+    /// y_i - t x_i < y_j - t x_j, so this counts the ascending pairs of those
+    /// values with a merge sort, ordering each tie in x downwards so that its
+    /// pairs are never counted.
+    fn at_most(&self, t: f64) -> usize {
+        let mut values: Vec<f64> = self.x.iter().zip(&self.y).map(|(x, y)| y - t * x).collect();
+        for &(start, end) in &self.groups {
+            values[start..end].sort_by(|a, b| b.total_cmp(a));
+        }
+        let mut buffer = vec![0.0; values.len()];
+        self.pairs - ascending_pairs(&mut values, &mut buffer)
+    }
+
+    /// The slope of 0-based rank `k`, by bisection over the floating-point
+    /// numbers between the smallest and largest slope. Every slope is an
+    /// average of slopes between neighbouring x values, which bound them all.
+    fn select(&self, k: usize) -> f64 {
+        let (mut low, mut high) = (f64::INFINITY, f64::NEG_INFINITY);
+        for pair in self.groups.windows(2) {
+            let ((a, b), (c, d)) = (pair[0], pair[1]);
+            let dx = self.x[c] - self.x[a];
+            let (min_here, max_here) = extent(&self.y[a..b]);
+            let (min_next, max_next) = extent(&self.y[c..d]);
+            low = low.min((min_next - max_here) / dx);
+            high = high.max((max_next - min_here) / dx);
+        }
+        // Invariant: fewer than k + 1 slopes are at most `below`, and at least k + 1 are at most `above`
+        let (mut below, mut above) = (ordered(low) as i128 - 1, ordered(high) as i128);
+        while above - below > 1 {
+            let middle = (below + above) / 2;
+            if self.at_most(from_ordered(middle as i64)) > k {
+                above = middle;
+            } else {
+                below = middle;
+            }
+        }
+        from_ordered(above as i64)
+    }
+}
+
+fn extent(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| (min.min(v), max.max(v)))
+}
+
+/// Maps a float to an integer with the same order, so bisection can split the floats between two values evenly.
+fn ordered(value: f64) -> i64 {
+    let bits = value.to_bits() as i64;
+    if bits < 0 {
+        bits ^ i64::MAX
+    } else {
+        bits
+    }
+}
+
+/// The inverse of [`ordered`].
+fn from_ordered(key: i64) -> f64 {
+    f64::from_bits((if key < 0 { key ^ i64::MAX } else { key }) as u64)
+}
+
+/// Sorts `values` and returns the number of pairs i < j with values[i] < values[j].
+fn ascending_pairs(values: &mut [f64], buffer: &mut [f64]) -> usize {
+    let n = values.len();
+    if n < 2 {
+        return 0;
+    }
+    let middle = n / 2;
+    let mut count = ascending_pairs(&mut values[..middle], &mut buffer[..middle])
+        + ascending_pairs(&mut values[middle..], &mut buffer[middle..]);
+    let (left, right) = values.split_at(middle);
+    let mut smaller = 0;
+    for &value in right {
+        while smaller < left.len() && left[smaller] < value {
+            smaller += 1;
+        }
+        count += smaller;
+    }
+    let (mut i, mut j) = (0, 0);
+    for slot in buffer.iter_mut() {
+        if j == right.len() || (i < left.len() && left[i] <= right[j]) {
+            *slot = left[i];
+            i += 1;
+        } else {
+            *slot = right[j];
+            j += 1;
+        }
+    }
+    values.copy_from_slice(buffer);
+    count
+}
+
+/// Huber M-estimate by iteratively reweighted least squares, re-estimating
+/// the MAD scale each step, with the covariance of Huber (1981) as in R's `rlm`.
+fn huber(x: &Matrix, y: &[f64], qr: &Qr) -> Result<RobustFit, Box<dyn Error>> {
+    let (n, p) = (x.rows, x.cols);
+    let mut coefficients = qr.solve(y);
+    let mut scale = mad_scale(&residuals(x, y, &coefficients));
+    for _ in 0..MAX_ITERATIONS {
+        if scale == 0.0 {
+            break;
+        }
+        let weights: Vec<f64> = residuals(x, y, &coefficients)
+            .iter()
+            .map(|e| (HUBER_K / (e / scale).abs()).min(1.0))
+            .collect();
+        let next = weighted_least_squares(x, y, &weights).ok_or("weighted design became singular")?;
+        let done = converged(&coefficients, &next);
+        coefficients = next;
+        scale = mad_scale(&residuals(x, y, &coefficients));
+        if done {
+            break;
+        }
+    }
+    if scale == 0.0 {
+        return Err("more than half of the observations lie exactly on the fit".into());
+    }
+
+    let resid = residuals(x, y, &coefficients);
+    let nf = n as f64;
+    let df_residual = (n - p) as f64;
+    let psi_squared: f64 = resid.iter().map(|e| e.clamp(-HUBER_K * scale, HUBER_K * scale).powi(2)).sum();
+    let derivative: Vec<f64> = resid
+        .iter()
+        .map(|e| if (e / scale).abs() <= HUBER_K { 1.0 } else { 0.0 })
+        .collect();
+    let mean_derivative = derivative.iter().sum::<f64>() / nf;
+    if mean_derivative == 0.0 {
+        return Err("every residual lies beyond the Huber threshold".into());
+    }
+    let var_derivative = derivative.iter().map(|d| (d - mean_derivative).powi(2)).sum::<f64>() / (nf - 1.0);
+    let kappa = 1.0 + p as f64 * var_derivative / (nf * mean_derivative.powi(2));
+    let std_dev = (psi_squared / df_residual).sqrt() * kappa / mean_derivative;
+    Ok(RobustFit {
+        coefficients,
+        covariance: scaled(&qr.xtx_inverse(), std_dev * std_dev),
+        scale,
+        df_residual,
+        slope_interval: None,
+    })
+}
+
+/// Least squares on the largest consensus set found among `RANSAC_TRIALS`
+/// exact fits to random minimal subsets. The inlier threshold is scaled by
+/// the robust spread of the least absolute deviations residuals.
+fn ransac(x: &Matrix, y: &[f64], seed: u64) -> Result<RobustFit, Box<dyn Error>> {
+    let (n, p) = (x.rows, x.cols);
+    let start = Qr::new(x).solve(y);
+    let threshold = RANSAC_THRESHOLD * mad_scale(&residuals(x, y, &least_absolute_deviations(x, y, start)));
+
+    let mut rng = Rng::new(seed);
+    let mut indices: Vec<usize> = (0..n).collect();
+    let mut best: Option<(usize, f64, Vec<usize>)> = None;
+    for _ in 0..RANSAC_TRIALS {
+        // Partial Fisher-Yates: the first p indices are a uniform random subset
+        for i in 0..p {
+            let j = i + rng.below(n - i);
+            indices.swap(i, j);
+        }
+        let rows: Vec<Vec<f64>> = indices[..p].iter().map(|&i| x.row(i).to_vec()).collect();
+        let subset_qr = Qr::new(&Matrix::from_rows(&rows));
+        if !subset_qr.is_full_rank() {
+            continue;
+        }
+        let subset_y: Vec<f64> = indices[..p].iter().map(|&i| y[i]).collect();
+        let candidate = subset_qr.solve(&subset_y);
+        let resid = residuals(x, y, &candidate);
+        let inliers: Vec<usize> = (0..n).filter(|&i| resid[i].abs() <= threshold).collect();
+        let sse: f64 = inliers.iter().map(|&i| resid[i].powi(2)).sum();
+        let better = match &best {
+            None => true,
+            Some((count, best_sse, _)) => inliers.len() > *count || (inliers.len() == *count && sse < *best_sse),
+        };
+        if better {
+            best = Some((inliers.len(), sse, inliers));
+        }
+    }
+
+    let (_, _, inliers) = best.ok_or("no random subset gave a nonsingular fit")?;
+    if inliers.len() <= p {
+        return Err(format!("only {} inliers for {} coefficients", inliers.len(), p).into());
+    }
+    let rows: Vec<Vec<f64>> = inliers.iter().map(|&i| x.row(i).to_vec()).collect();
+    let inlier_y: Vec<f64> = inliers.iter().map(|&i| y[i]).collect();
+    let mut fit = fit_design(RegressionMethod::Ols, &Matrix::from_rows(&rows), &inlier_y, seed)?;
+    fit.df_residual = (inliers.len() - p) as f64;
+    Ok(fit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{close, DIST, SPEED, STACKLOSS};
+
+    fn stackloss() -> (Matrix, Vec<f64>) {
+        let rows: Vec<Vec<f64>> = STACKLOSS.iter().map(|r| vec![1.0, r[0], r[1], r[2]]).collect();
+        (Matrix::from_rows(&rows), STACKLOSS.iter().map(|r| r[3]).collect())
+    }
+
+    #[test]
+    fn huber_matches_rlm_on_stackloss() {
+        // MASS::rlm(stack.loss ~ ., stackloss)
+        let (x, y) = stackloss();
+        let fit = fit_design(RegressionMethod::Huber, &x, &y, 1).unwrap();
+        for (estimate, expected) in fit.coefficients.iter().zip([-41.0265, 0.8294, 0.9261, -0.1278]) {
+            close(*estimate, expected, 1e-4);
+        }
+        close(fit.scale, 2.441, 1e-3);
+    }
+
+    #[test]
+    fn lad_matches_rq_on_stackloss() {
+        // quantreg::rq(stack.loss ~ ., stackloss); IRLS approaches the exact vertex
+        let (x, y) = stackloss();
+        let fit = fit_design(RegressionMethod::Lad, &x, &y, 1).unwrap();
+        for (estimate, expected) in fit.coefficients.iter().zip([-39.68985507, 0.83188406, 0.57391304, -0.06086957]) {
+            close(*estimate, expected, 1e-3);
+        }
+    }
+
+    #[test]
+    fn theil_sen_matches_the_median_of_all_pairwise_slopes() {
+        // 1169 slopes between cars with different speeds
+        let fit = fit_line(RegressionMethod::TheilSen, &SPEED, &DIST, 1).unwrap();
+        close(fit.slope.estimate, 11.0 / 3.0, 1e-12);
+        close(fit.intercept.estimate, -47.0 / 3.0, 1e-12);
+        close(fit.slope.ci.0, 2.933333333333333, 1e-12);
+        close(fit.slope.ci.1, 4.5, 1e-12);
+    }
+
+    #[test]
+    fn slope_selection_agrees_with_sorting_every_slope() {
+        let mut rng = Rng::new(5);
+        let xs: Vec<f64> = (0..60).map(|_| rng.below(15) as f64).collect();
+        let y: Vec<f64> = xs.iter().map(|x| 2.0 * x + rng.below(1000) as f64 / 37.0).collect();
+        let mut all = Vec::new();
+        for i in 0..xs.len() {
+            for j in i + 1..xs.len() {
+                if xs[i] != xs[j] {
+                    all.push((y[j] - y[i]) / (xs[j] - xs[i]));
+                }
+            }
+        }
+        all.sort_by(|a, b| a.total_cmp(b));
+        let slopes = PairwiseSlopes::new(&xs, &y);
+        assert_eq!(slopes.pairs, all.len());
+        for (k, expected) in all.iter().enumerate().filter(|(k, _)| k % 7 == 0 || *k == all.len() - 1) {
+            let selected = slopes.select(k);
+            assert!((selected - expected).abs() <= 1e-12 * expected.abs().max(1.0), "rank {}: {} != {}", k, selected, expected);
+        }
+    }
+
+    #[test]
+    fn theil_sen_needs_two_distinct_x_values() {
+        assert!(fit_line(RegressionMethod::TheilSen, &[3.0; 5], &[1.0, 2.0, 3.0, 4.0, 5.0], 1).is_err());
+    }
+
+    #[test]
+    fn ransac_ignores_gross_outliers() {
+        let x: Vec<f64> = (0..40).map(f64::from).collect();
+        let mut y: Vec<f64> = x.iter().enumerate().map(|(i, v)| 3.0 + 0.5 * v + if i % 2 == 0 { 0.1 } else { -0.1 }).collect();
+        for i in [5, 17, 31] {
+            y[i] += 100.0;
+        }
+        let fit = fit_line(RegressionMethod::Ransac, &x, &y, 9).unwrap();
+        close(fit.slope.estimate, 0.5, 0.01);
+        close(fit.intercept.estimate, 3.0, 0.2);
+        let again = fit_line(RegressionMethod::Ransac, &x, &y, 9).unwrap();
+        assert_eq!(fit.slope.estimate, again.slope.estimate);
+    }
+}
@@ -0,0 +1,604 @@
+use std::error::Error;
+use std::fs;
+
+use crate::columns::ColumnAliases;
+use crate::encoders::OrdinalEncoder;
+use crate::missing::{ImputeStat, MissingPolicy};
+
+/// Built-in schema used when no schema file is supplied.
+const DEFAULT_SCHEMA: &str = include_str!("career_schema.toml");
+
+/// Pseudo-count pulling rare categories towards the global mean in target encoding.
+const DEFAULT_TARGET_SMOOTHING: f64 = 10.0;
+
+/// How the raw text of a column is turned into a value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnType {
+    Numeric,
+    /// Ordered levels (and their accepted spellings) mapped to numeric codes.
+    Ordinal(OrdinalEncoder),
+    Categorical,
+    Boolean,
+}
+
+/// What part a column plays in the analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Feature,
+    Target,
+    Ignore,
+}
+
+/// How a categorical feature is turned into numeric columns for analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Encoding {
+    /// 0/1 indicators, dropping `reference` when given.
+    OneHot { reference: Option<String> },
+    /// Smoothed mean of `target` (default: the first target column) per category.
+    Target { target: Option<String>, smoothing: f64 },
+    /// Share of rows in each category.
+    Frequency,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub kind: ColumnType,
+    pub role: Role,
+    pub missing: MissingPolicy,
+    /// Only for categorical columns; unencoded categoricals are not analysed numerically.
+    pub encoding: Option<Encoding>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub columns: Vec<ColumnSpec>,
+}
+
+impl Schema {
+    pub fn from_path(path: &str) -> Result<Schema, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        Schema::parse(&text).map_err(|e| format!("{}: {}", path, e).into())
+    }
+
+    /// Parses the `[[column]]` subset of TOML used by schema files:
+    ///
+    /// ```toml
+    /// [[column]]
+    /// name = "Family Influence"
+    /// type = "ordinal"
+    /// levels = { Low = 1, Medium = 2, High = 3 }
+    /// level_aliases = { Med = "Medium" }
+    /// role = "feature"
+    /// missing = "median"          # drop | mean | median | mode | sentinel | keep
+    /// group_by = "Gender"         # optional, for mean/median/mode
+    ///
+    /// [[column]]
+    /// name = "Gender"
+    /// type = "categorical"
+    /// encoding = "one_hot"        # one_hot | target | frequency
+    /// reference = "Female"        # one_hot only
+    /// ```
+    pub fn parse(text: &str) -> Result<Schema, Box<dyn Error>> {
+        let mut tables: Vec<Vec<(String, TomlValue, usize)>> = Vec::new();
+
+        for (n, raw) in text.lines().enumerate() {
+            let line_no = n + 1;
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "[[column]]" {
+                tables.push(Vec::new());
+                continue;
+            }
+            if line.starts_with('[') {
+                return Err(format!("line {}: unsupported table header {}", line_no, line).into());
+            }
+            let current = tables
+                .last_mut()
+                .ok_or_else(|| format!("line {}: key outside of a [[column]] table", line_no))?;
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected key = value", line_no))?;
+            let mut parser = ValueParser { chars: value.trim().chars().collect(), pos: 0 };
+            let value = parser.value().map_err(|e| format!("line {}: {}", line_no, e))?;
+            parser.skip_ws();
+            if parser.pos != parser.chars.len() {
+                return Err(format!("line {}: trailing characters after value", line_no).into());
+            }
+            current.push((unquote(key.trim()), value, line_no));
+        }
+
+        let columns = tables
+            .into_iter()
+            .map(column_from_table)
+            .collect::<Result<Vec<_>, _>>()?;
+        if columns.is_empty() {
+            return Err("schema declares no columns".into());
+        }
+        for (i, column) in columns.iter().enumerate() {
+            if columns[..i].iter().any(|c| c.name == column.name) {
+                return Err(format!("column '{}' is declared more than once", column.name).into());
+            }
+            if let Some(Encoding::Target { target: Some(target), .. }) = &column.encoding {
+                if !columns.iter().any(|c| &c.name == target && c.role != Role::Ignore) {
+                    return Err(format!("column '{}' is target-encoded against unknown column '{}'", column.name, target).into());
+                }
+            }
+            if let MissingPolicy::Impute { group_by: Some(group), .. } = &column.missing {
+                let loaded = columns.iter().any(|c| &c.name == group && c.role != Role::Ignore);
+                if !loaded || group == &column.name {
+                    return Err(format!(
+                        "column '{}' groups imputation by '{}', which is not another loaded column",
+                        column.name, group
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(Schema { columns })
+    }
+
+    /// Columns that are actually loaded, i.e. everything not marked `ignore`.
+    pub fn loaded_columns(&self) -> impl Iterator<Item = &ColumnSpec> {
+        self.columns.iter().filter(|c| c.role != Role::Ignore)
+    }
+
+    /// Accepts `alias` as another header spelling of the schema column `name`.
+    pub fn add_alias(&mut self, name: &str, alias: &str) -> Result<(), Box<dyn Error>> {
+        let column = self
+            .columns
+            .iter_mut()
+            .find(|c| c.name == name)
+            .ok_or_else(|| format!("schema has no column named '{}'", name))?;
+        column.aliases.push(alias.to_string());
+        Ok(())
+    }
+
+    /// The default aliases extended with every alias declared in the schema.
+    pub fn aliases(&self) -> ColumnAliases {
+        let mut aliases = ColumnAliases::default();
+        for column in &self.columns {
+            for alias in &column.aliases {
+                aliases.add_alias(&column.name, alias);
+            }
+        }
+        aliases
+    }
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Schema::parse(DEFAULT_SCHEMA).expect("built-in schema is valid")
+    }
+}
+
+fn column_from_table(entries: Vec<(String, TomlValue, usize)>) -> Result<ColumnSpec, Box<dyn Error>> {
+    let mut name = None;
+    let mut aliases = Vec::new();
+    let mut kind = None;
+    let mut levels = None;
+    let mut role = Role::Feature;
+    let mut missing = None;
+    let mut sentinel = None;
+    let mut group_by = None;
+    let mut level_aliases = Vec::new();
+    let mut encoding = None;
+    let mut reference = None;
+    let mut target = None;
+    let mut smoothing = None;
+
+    for (key, value, line_no) in entries {
+        let bad = |what: &str| format!("line {}: '{}' must be {}", line_no, key, what);
+        match key.as_str() {
+            "name" => name = Some(value.as_str().ok_or_else(|| bad("a string"))?.to_string()),
+            "aliases" => {
+                let items = value.as_array().ok_or_else(|| bad("an array of strings"))?;
+                for item in items {
+                    aliases.push(item.as_str().ok_or_else(|| bad("an array of strings"))?.to_string());
+                }
+            }
+            "type" => kind = Some((value.as_str().ok_or_else(|| bad("a string"))?.to_lowercase(), line_no)),
+            "levels" => {
+                let pairs = value.as_table().ok_or_else(|| bad("an inline table of level = code"))?;
+                let mut parsed = Vec::new();
+                for (level, code) in pairs {
+                    let code = code.as_number().ok_or_else(|| bad("an inline table of level = code"))?;
+                    parsed.push((level.clone(), code));
+                }
+                levels = Some(parsed);
+            }
+            "level_aliases" => {
+                let pairs = value.as_table().ok_or_else(|| bad("an inline table of alias = \"level\""))?;
+                for (alias, level) in pairs {
+                    let level = level.as_str().ok_or_else(|| bad("an inline table of alias = \"level\""))?;
+                    level_aliases.push((alias.clone(), level.to_string()));
+                }
+            }
+            "encoding" => encoding = Some((value.as_str().ok_or_else(|| bad("a string"))?.to_lowercase(), line_no)),
+            "reference" => reference = Some(value.as_str().ok_or_else(|| bad("a string"))?.to_string()),
+            "target" => target = Some(value.as_str().ok_or_else(|| bad("a string"))?.to_string()),
+            "smoothing" => smoothing = Some(value.as_number().ok_or_else(|| bad("a number"))?),
+            "role" => {
+                role = match value.as_str().map(str::to_lowercase).as_deref() {
+                    Some("feature") => Role::Feature,
+                    Some("target") => Role::Target,
+                    Some("ignore") => Role::Ignore,
+                    _ => return Err(bad("one of \"feature\", \"target\", \"ignore\"").into()),
+                }
+            }
+            "missing" => missing = Some((value.as_str().ok_or_else(|| bad("a string"))?.to_lowercase(), line_no)),
+            "sentinel" => sentinel = Some(value.as_number().ok_or_else(|| bad("a number"))?),
+            "group_by" => group_by = Some(value.as_str().ok_or_else(|| bad("a string"))?.to_string()),
+            _ => return Err(format!("line {}: unknown key '{}'", line_no, key).into()),
+        }
+    }
+
+    let name = name.ok_or("every [[column]] needs a name")?;
+    let kind = match kind {
+        None => return Err(format!("column '{}' has no type", name).into()),
+        Some((t, line_no)) => match t.as_str() {
+            "numeric" => ColumnType::Numeric,
+            "categorical" => ColumnType::Categorical,
+            "boolean" => ColumnType::Boolean,
+            "ordinal" => {
+                let levels = levels
+                    .take()
+                    .ok_or_else(|| format!("ordinal column '{}' needs a levels table", name))?;
+                let mut encoder = OrdinalEncoder::new(&levels);
+                for (alias, level) in level_aliases.drain(..) {
+                    encoder = encoder
+                        .with_alias(&alias, &level)
+                        .map_err(|e| format!("column '{}': {}", name, e))?;
+                }
+                ColumnType::Ordinal(encoder)
+            }
+            other => return Err(format!("line {}: unknown column type '{}'", line_no, other).into()),
+        },
+    };
+    if levels.is_some() || !level_aliases.is_empty() {
+        return Err(format!("column '{}' declares levels but is not ordinal", name).into());
+    }
+
+    let encoding = match encoding {
+        None => None,
+        Some(_) if kind != ColumnType::Categorical => {
+            return Err(format!("column '{}' sets an encoding but is not categorical", name).into())
+        }
+        Some((e, line_no)) => Some(match e.as_str() {
+            "one_hot" => Encoding::OneHot { reference: reference.take() },
+            "target" => Encoding::Target {
+                target: target.take(),
+                smoothing: smoothing.take().unwrap_or(DEFAULT_TARGET_SMOOTHING),
+            },
+            "frequency" => Encoding::Frequency,
+            other => return Err(format!("line {}: unknown encoding '{}'", line_no, other).into()),
+        }),
+    };
+    if reference.is_some() || target.is_some() || smoothing.is_some() {
+        return Err(format!("column '{}' sets reference/target/smoothing options its encoding does not use", name).into());
+    }
+
+    let numeric = matches!(kind, ColumnType::Numeric | ColumnType::Ordinal(_));
+    let missing = match missing {
+        None => MissingPolicy::Drop,
+        Some((policy, line_no)) => {
+            let mut impute = |stat| MissingPolicy::Impute { stat, group_by: group_by.take() };
+            match policy.as_str() {
+                "drop" => MissingPolicy::Drop,
+                "keep" => MissingPolicy::Keep,
+                "mode" => impute(ImputeStat::Mode),
+                "mean" | "median" | "sentinel" if !numeric => {
+                    return Err(format!(
+                        "line {}: '{}' policy needs a numeric or ordinal column, '{}' is not",
+                        line_no, policy, name
+                    )
+                    .into())
+                }
+                "mean" => impute(ImputeStat::Mean),
+                "median" => impute(ImputeStat::Median),
+                "sentinel" => MissingPolicy::Sentinel(
+                    sentinel
+                        .take()
+                        .ok_or_else(|| format!("column '{}' uses the sentinel policy but sets no sentinel", name))?,
+                ),
+                other => return Err(format!("line {}: unknown missing policy '{}'", line_no, other).into()),
+            }
+        }
+    };
+    if group_by.is_some() || sentinel.is_some() {
+        return Err(format!("column '{}' sets group_by/sentinel options its missing policy does not use", name).into());
+    }
+
+    Ok(ColumnSpec { name, aliases, kind, role, missing, encoding })
+}
+
+#[derive(Debug, Clone)]
+enum TomlValue {
+    Str(String),
+    Number(f64),
+    Array(Vec<TomlValue>),
+    Table(Vec<(String, TomlValue)>),
+}
+
+impl TomlValue {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            TomlValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            TomlValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&[TomlValue]> {
+        match self {
+            TomlValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn as_table(&self) -> Option<&[(String, TomlValue)]> {
+        match self {
+            TomlValue::Table(pairs) => Some(pairs),
+            _ => None,
+        }
+    }
+}
+
+struct ValueParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ValueParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_ws();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", c))
+        }
+    }
+
+    fn value(&mut self) -> Result<TomlValue, String> {
+        self.skip_ws();
+        match self.peek() {
+            Some('"') => self.string().map(TomlValue::Str),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_ws();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        break;
+                    }
+                    items.push(self.value()?);
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {}
+                        _ => return Err("expected ',' or ']' in array".to_string()),
+                    }
+                }
+                Ok(TomlValue::Array(items))
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut pairs = Vec::new();
+                loop {
+                    self.skip_ws();
+                    if self.peek() == Some('}') {
+                        self.pos += 1;
+                        break;
+                    }
+                    let key = self.key()?;
+                    self.expect('=')?;
+                    pairs.push((key, self.value()?));
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {}
+                        _ => return Err("expected ',' or '}' in inline table".to_string()),
+                    }
+                }
+                Ok(TomlValue::Table(pairs))
+            }
+            Some(_) => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| !matches!(c, ',' | ']' | '}') && !c.is_whitespace()) {
+                    self.pos += 1;
+                }
+                let word: String = self.chars[start..self.pos].iter().collect();
+                word.replace('_', "")
+                    .parse::<f64>()
+                    .map(TomlValue::Number)
+                    .map_err(|_| format!("invalid value '{}'", word))
+            }
+            None => Err("missing value".to_string()),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '\'')) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err("expected a key".to_string());
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1; // opening quote
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escaped = self.peek().ok_or("unterminated escape")?;
+                    self.pos += 1;
+                    out.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        other => other,
+                    });
+                }
+                _ => out.push(c),
+            }
+        }
+        Err("unterminated string".to_string())
+    }
+}
+
+/// Drops a trailing `# comment`, ignoring '#' inside quoted strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(key: &str) -> String {
+    key.trim_matches('"').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Schema::parse(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn default_schema_parses() {
+        let schema = Schema::default();
+        let influence = schema.columns.iter().find(|c| c.name == "Family Influence").unwrap();
+        assert!(matches!(influence.kind, ColumnType::Ordinal(_)));
+        assert_eq!(influence.missing, MissingPolicy::Keep);
+        assert!(schema.loaded_columns().any(|c| c.role == Role::Target));
+    }
+
+    #[test]
+    fn invalid_columns_are_rejected() {
+        let twice = r#"
+[[column]]
+name = "Age"
+type = "numeric"
+[[column]]
+name = "Age"
+type = "numeric"
+"#;
+        assert_eq!(error(twice), "column 'Age' is declared more than once");
+
+        let unknown_type = r#"
+[[column]]
+name = "Age"
+type = "integer"
+"#;
+        assert_eq!(error(unknown_type), "line 4: unknown column type 'integer'");
+
+        let unknown_role = r#"
+[[column]]
+name = "Age"
+type = "numeric"
+role = "label"
+"#;
+        assert_eq!(error(unknown_role), r#"line 5: 'role' must be one of "feature", "target", "ignore""#);
+
+        let no_levels = r#"
+[[column]]
+name = "Grade"
+type = "ordinal"
+"#;
+        assert_eq!(error(no_levels), "ordinal column 'Grade' needs a levels table");
+
+        let encoded_number = r#"
+[[column]]
+name = "Age"
+type = "numeric"
+encoding = "one_hot"
+"#;
+        assert_eq!(error(encoded_number), "column 'Age' sets an encoding but is not categorical");
+
+        let reference_without_encoding = r#"
+[[column]]
+name = "Gender"
+type = "categorical"
+reference = "Female"
+"#;
+        assert_eq!(
+            error(reference_without_encoding),
+            "column 'Gender' sets reference/target/smoothing options its encoding does not use"
+        );
+        assert_eq!(error("# nothing here\n"), "schema declares no columns");
+    }
+
+    #[test]
+    fn categorical_columns_need_no_encoding() {
+        let schema = Schema::parse("[[column]]\nname = \"Gender\"\ntype = \"categorical\"\n").unwrap();
+        assert_eq!(schema.columns[0].kind, ColumnType::Categorical);
+        assert_eq!(schema.columns[0].encoding, None);
+    }
+
+    #[test]
+    fn level_aliases_share_their_level_code() {
+        let schema = Schema::parse(
+            r#"
+[[column]]
+name = "Growth"
+type = "ordinal"
+levels = { Low = 1, Medium = 2, High = 3 }
+level_aliases = { Med = "Medium", Hi = "High" }
+"#,
+        )
+        .unwrap();
+        let ColumnType::Ordinal(encoder) = &schema.columns[0].kind else {
+            panic!("Growth is not ordinal");
+        };
+        assert_eq!(encoder.encode("Med"), Some(2.0));
+        assert_eq!(encoder.encode("hi"), Some(3.0));
+        assert_eq!(encoder.encode("Low"), Some(1.0));
+        assert_eq!(encoder.encode("Extreme"), None);
+        assert_eq!(encoder.level_name(2.0), Some("Medium"));
+
+        let unknown_level = r#"
+[[column]]
+name = "Growth"
+type = "ordinal"
+levels = { Low = 1 }
+level_aliases = { Hi = "High" }
+"#;
+        assert_eq!(error(unknown_level), "column 'Growth': alias 'Hi' refers to unknown level 'High'");
+    }
+}
@@ -0,0 +1,226 @@
+//! Descriptive statistics of a single numeric column.
+
+use std::fmt::Write;
+
+use crate::missing::mode_f64;
+
+/// Percentiles reported by default next to the quartiles.
+pub const DEFAULT_PERCENTILES: [f64; 4] = [5.0, 10.0, 90.0, 95.0];
+
+/// Descriptive statistics of the observed values of one column.
+///
+/// Variance and standard deviation use the `n - 1` denominator. Skewness and
+/// excess kurtosis are the bias-adjusted sample estimators (G1 and G2, as
+/// reported by SAS, SPSS and Excel) and need at least 3 and 4 values.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub name: String,
+    pub count: usize,
+    pub missing: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub mode: f64,
+    pub std_dev: f64,
+    pub variance: f64,
+    pub min: f64,
+    pub q1: f64,
+    pub q3: f64,
+    pub max: f64,
+    pub iqr: f64,
+    pub skewness: Option<f64>,
+    pub excess_kurtosis: Option<f64>,
+    /// Standard deviation over the absolute mean; `None` when the mean is 0.
+    pub coefficient_of_variation: Option<f64>,
+    pub std_error: f64,
+    sorted: Vec<f64>,
+}
+
+impl Summary {
+    /// Summarizes a column, counting `None` cells as missing. Returns `None`
+    /// when no value is observed.
+    pub fn from_column(name: &str, values: &[Option<f64>]) -> Option<Summary> {
+        let mut sorted: Vec<f64> = values.iter().flatten().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let count = sorted.len();
+        let n = count as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let central_moment = |k: i32| sorted.iter().map(|v| (v - mean).powi(k)).sum::<f64>() / n;
+        let (m2, m3, m4) = (central_moment(2), central_moment(3), central_moment(4));
+
+        let variance = if count > 1 { m2 * n / (n - 1.0) } else { 0.0 };
+        let std_dev = variance.sqrt();
+        let skewness = (count >= 3 && m2 > 0.0).then(|| m3 / m2.powf(1.5) * (n * (n - 1.0)).sqrt() / (n - 2.0));
+        let excess_kurtosis = (count >= 4 && m2 > 0.0)
+            .then(|| (n - 1.0) / ((n - 2.0) * (n - 3.0)) * ((n + 1.0) * (m4 / (m2 * m2) - 3.0) + 6.0));
+
+        let q1 = percentile_sorted(&sorted, 25.0);
+        let q3 = percentile_sorted(&sorted, 75.0);
+        Some(Summary {
+            name: name.to_string(),
+            count,
+            missing: values.len() - count,
+            mean,
+            median: percentile_sorted(&sorted, 50.0),
+            mode: mode_f64(&sorted).unwrap_or(mean),
+            std_dev,
+            variance,
+            min: sorted[0],
+            q1,
+            q3,
+            max: sorted[count - 1],
+            iqr: q3 - q1,
+            skewness,
+            excess_kurtosis,
+            coefficient_of_variation: (mean != 0.0).then(|| std_dev / mean.abs()),
+            std_error: std_dev / n.sqrt(),
+            sorted,
+        })
+    }
+
+    /// The `p`-th percentile (0 to 100), interpolating linearly between order
+    /// statistics (R's default, type 7).
+    pub fn percentile(&self, p: f64) -> f64 {
+        percentile_sorted(&self.sorted, p)
+    }
+
+    /// Multi-line text block with every statistic and the given percentiles.
+    pub fn render(&self, percentiles: &[f64]) -> String {
+        let opt = |v: Option<f64>| v.map_or("n/a".to_string(), |v| format!("{:.4}", v));
+        let mut out = String::new();
+        writeln!(out, "{} (n = {}, missing = {})", self.name, self.count, self.missing).unwrap();
+        writeln!(
+            out,
+            "  Mean: {:.4} (SE {:.4}), Median: {:.4}, Mode: {:.4}",
+            self.mean, self.std_error, self.median, self.mode
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  Std. dev.: {:.4}, Variance: {:.4}, CV: {}",
+            self.std_dev,
+            self.variance,
+            opt(self.coefficient_of_variation)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  Min: {:.4}, Q1: {:.4}, Q3: {:.4}, Max: {:.4}, IQR: {:.4}",
+            self.min, self.q1, self.q3, self.max, self.iqr
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  Skewness: {}, Excess kurtosis: {}",
+            opt(self.skewness),
+            opt(self.excess_kurtosis)
+        )
+        .unwrap();
+        if !percentiles.is_empty() {
+            let cells: Vec<String> = percentiles
+                .iter()
+                .map(|&p| format!("P{}: {:.4}", p, self.percentile(p)))
+                .collect();
+            writeln!(out, "  {}", cells.join(", ")).unwrap();
+        }
+        out
+    }
+
+    /// Column names matching [`Summary::csv_row`].
+    pub fn csv_header(percentiles: &[f64]) -> Vec<String> {
+        let mut header: Vec<String> = [
+            "column", "n", "missing", "mean", "se_mean", "median", "mode", "std_dev", "variance", "min", "q1", "q3",
+            "max", "iqr", "skewness", "excess_kurtosis", "cv",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        header.extend(percentiles.iter().map(|p| format!("p{}", p)));
+        header
+    }
+
+    pub fn csv_row(&self, percentiles: &[f64]) -> Vec<String> {
+        let opt = |v: Option<f64>| v.map_or(String::new(), |v| v.to_string());
+        let mut row = vec![self.name.clone(), self.count.to_string(), self.missing.to_string()];
+        row.extend(
+            [
+                self.mean,
+                self.std_error,
+                self.median,
+                self.mode,
+                self.std_dev,
+                self.variance,
+                self.min,
+                self.q1,
+                self.q3,
+                self.max,
+                self.iqr,
+            ]
+            .iter()
+            .map(|v| v.to_string()),
+        );
+        row.push(opt(self.skewness));
+        row.push(opt(self.excess_kurtosis));
+        row.push(opt(self.coefficient_of_variation));
+        row.extend(percentiles.iter().map(|&p| self.percentile(p).to_string()));
+        row
+    }
+}
+
+/// The `p`-th percentile (0 to 100) of already sorted values, R type 7.
+pub fn percentile_sorted(sorted: &[f64], p: f64) -> f64 {
+    let h = (sorted.len() - 1) as f64 * (p / 100.0).clamp(0.0, 1.0);
+    let lower = h.floor() as usize;
+    let upper = h.ceil() as usize;
+    sorted[lower] + (h - lower as f64) * (sorted[upper] - sorted[lower])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::close;
+
+    fn summary() -> Summary {
+        let values = [2.0, 4.0, 4.0, 5.0, 7.0, 9.0, 10.0, 15.0, 21.0, 3.0].map(Some);
+        let mut column = values.to_vec();
+        column.push(None);
+        Summary::from_column("x", &column).unwrap()
+    }
+
+    #[test]
+    fn moments_match_r() {
+        // mean(x); var(x); e1071::skewness(x, type = 2); e1071::kurtosis(x, type = 2)
+        let s = summary();
+        assert_eq!((s.count, s.missing), (10, 1));
+        close(s.mean, 8.0, 1e-12);
+        close(s.variance, 36.2222222222, 1e-9);
+        close(s.std_error, (36.2222222222_f64 / 10.0).sqrt(), 1e-9);
+        close(s.skewness.unwrap(), 1.3073209596, 1e-9);
+        close(s.excess_kurtosis.unwrap(), 1.2323021459, 1e-9);
+        assert_eq!(s.mode, 4.0);
+    }
+
+    #[test]
+    fn percentiles_match_quantile_type_7() {
+        // quantile(x, c(0.05, 0.1, 0.25, 0.5, 0.75, 0.9, 0.95), type = 7)
+        let s = summary();
+        for (p, expected) in [(5.0, 2.45), (10.0, 2.9), (25.0, 4.0), (50.0, 6.0), (75.0, 9.75), (90.0, 15.6), (95.0, 18.3)] {
+            close(s.percentile(p), expected, 1e-12);
+        }
+        assert_eq!((s.q1, s.median, s.q3, s.iqr), (4.0, 6.0, 9.75, 5.75));
+        assert_eq!((s.percentile(0.0), s.percentile(100.0)), (2.0, 21.0));
+    }
+
+    #[test]
+    fn small_or_constant_columns_have_no_shape_statistics() {
+        let s = Summary::from_column("x", &[Some(1.0), Some(2.0), Some(4.0)]).unwrap();
+        assert!(s.skewness.is_some() && s.excess_kurtosis.is_none());
+
+        let constant = Summary::from_column("x", &[Some(5.0); 5]).unwrap();
+        assert_eq!((constant.skewness, constant.excess_kurtosis), (None, None));
+        assert!(Summary::from_column("x", &[None, None]).is_none());
+    }
+}
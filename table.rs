@@ -0,0 +1,352 @@
+use std::error::Error;
+
+use crate::columns::{normalize_header, HeaderIndex};
+use crate::load_report::{IssueReason, LoadReport, ParseIssue};
+use crate::missing::{impute_mode, impute_numeric, ImputeStat, MissingPolicy};
+use crate::encoders::{FrequencyEncoder, OneHotEncoder, TargetEncoder};
+use crate::schema::{ColumnSpec, ColumnType, Encoding, Role, Schema};
+
+/// Parsed values of one column; ordinal columns are stored as their numeric codes.
+/// `None` marks a cell left missing under the `keep` policy.
+#[derive(Debug, Clone)]
+pub enum ColumnData {
+    Numeric(Vec<Option<f64>>),
+    Categorical(Vec<Option<String>>),
+    Boolean(Vec<Option<bool>>),
+}
+
+impl ColumnData {
+    /// Cell `row` rendered as a grouping key (`None` if missing).
+    fn key(&self, row: usize) -> Option<String> {
+        match self {
+            ColumnData::Numeric(values) => values[row].map(|v| v.to_string()),
+            ColumnData::Categorical(values) => values[row].clone(),
+            ColumnData::Boolean(values) => values[row].map(|v| v.to_string()),
+        }
+    }
+}
+
+/// A column name with its values, as consumed by the analyses.
+pub type NamedSeries = (String, Vec<Option<f64>>);
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub spec: ColumnSpec,
+    pub data: ColumnData,
+}
+
+/// A dataset loaded according to a `Schema`.
+#[derive(Debug, Clone)]
+pub struct Table {
+    /// Zero-based record index in the source file of each loaded row.
+    pub row_ids: Vec<usize>,
+    pub columns: Vec<Column>,
+}
+
+impl Table {
+    pub fn len(&self) -> usize {
+        self.row_ids.len()
+    }
+
+    /// Looks a column up by schema name, ignoring case and whitespace.
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        let wanted = normalize_header(name);
+        self.columns.iter().find(|c| normalize_header(&c.spec.name) == wanted)
+    }
+
+    /// Values of a numeric, ordinal or boolean column as `f64` (booleans become 0/1).
+    pub fn numeric(&self, name: &str) -> Option<Vec<Option<f64>>> {
+        match &self.column(name)?.data {
+            ColumnData::Numeric(values) => Some(values.clone()),
+            ColumnData::Boolean(values) => Some(
+                values
+                    .iter()
+                    .map(|b| b.map(|b| if b { 1.0 } else { 0.0 }))
+                    .collect(),
+            ),
+            ColumnData::Categorical(_) => None,
+        }
+    }
+
+    /// Values of a boolean column; `None` if there is no such column or it is not boolean.
+    pub fn boolean(&self, name: &str) -> Option<Vec<Option<bool>>> {
+        match &self.column(name)?.data {
+            ColumnData::Boolean(values) => Some(values.clone()),
+            _ => None,
+        }
+    }
+
+    /// Values of a categorical column; `None` if there is no such column or it is not categorical.
+    pub fn categorical(&self, name: &str) -> Option<Vec<Option<String>>> {
+        match &self.column(name)?.data {
+            ColumnData::Categorical(values) => Some(values.clone()),
+            _ => None,
+        }
+    }
+
+    /// Every feature column as named numeric series, with categorical features
+    /// expanded by their schema encoding (unencoded categoricals are skipped).
+    pub fn encoded_features(&self) -> Result<Vec<NamedSeries>, Box<dyn Error>> {
+        let mut features = Vec::new();
+        for column in self.columns.iter().filter(|c| c.spec.role == Role::Feature) {
+            let name = &column.spec.name;
+            let values = match &column.data {
+                ColumnData::Categorical(values) => values,
+                _ => {
+                    features.push((name.clone(), self.numeric(name).expect("non-categorical column")));
+                    continue;
+                }
+            };
+            match &column.spec.encoding {
+                None => {}
+                Some(Encoding::OneHot { reference }) => {
+                    let encoder = OneHotEncoder::fit(values, reference.as_deref())
+                        .map_err(|e| format!("column '{}': {}", name, e))?;
+                    features.extend(encoder.column_names(name).into_iter().zip(encoder.transform(values)));
+                }
+                Some(Encoding::Target { target, smoothing }) => {
+                    let target = match target {
+                        Some(target) => target.as_str(),
+                        None => self
+                            .columns
+                            .iter()
+                            .find(|c| c.spec.role == Role::Target)
+                            .map(|c| c.spec.name.as_str())
+                            .ok_or_else(|| format!("column '{}' is target-encoded but the schema has no target", name))?,
+                    };
+                    let y = self
+                        .numeric(target)
+                        .ok_or_else(|| format!("target-encoding target '{}' is not numeric", target))?;
+                    let encoder = TargetEncoder::fit(values, &y, *smoothing)
+                        .map_err(|e| format!("column '{}': {}", name, e))?;
+                    features.push((format!("{} (target-encoded)", name), encoder.transform(values)));
+                }
+                Some(Encoding::Frequency) => {
+                    let encoder = FrequencyEncoder::fit(values);
+                    features.push((format!("{} (frequency)", name), encoder.transform(values)));
+                }
+            }
+        }
+        Ok(features)
+    }
+
+    /// Each row's value of `name` as a grouping key, with ordinal codes shown
+    /// as their level names. `None` if there is no such column.
+    pub fn group_keys(&self, name: &str) -> Option<Vec<Option<String>>> {
+        let column = self.column(name)?;
+        Some(
+            (0..self.len())
+                .map(|r| match (&column.spec.kind, &column.data) {
+                    (ColumnType::Ordinal(encoder), ColumnData::Numeric(values)) => {
+                        values[r].map(|code| encoder.level_name(code).map_or(code.to_string(), str::to_string))
+                    }
+                    _ => column.data.key(r),
+                })
+                .collect(),
+        )
+    }
+
+    /// Fills the cells left empty by impute and sentinel policies, recording counts in `report`.
+    fn apply_missing_policies(&mut self, report: &mut LoadReport) {
+        for c in 0..self.columns.len() {
+            let policy = self.columns[c].spec.missing.clone();
+            let filled = match policy {
+                MissingPolicy::Drop | MissingPolicy::Keep => 0,
+                MissingPolicy::Sentinel(code) => match &mut self.columns[c].data {
+                    ColumnData::Numeric(values) => values
+                        .iter_mut()
+                        .filter(|v| v.is_none())
+                        .map(|v| *v = Some(code))
+                        .count(),
+                    _ => unreachable!("schema only allows sentinels on numeric columns"),
+                },
+                MissingPolicy::Impute { stat, group_by } => {
+                    let groups = group_by.map(|g| self.group_keys(&g).expect("schema validates group_by columns"));
+                    let groups = groups.as_deref();
+                    match (&mut self.columns[c].data, stat) {
+                        (ColumnData::Numeric(values), _) => impute_numeric(values, stat, groups),
+                        (ColumnData::Categorical(values), ImputeStat::Mode) => impute_mode(values, groups),
+                        (ColumnData::Boolean(values), ImputeStat::Mode) => impute_mode(values, groups),
+                        _ => unreachable!("schema only allows mean/median on numeric columns"),
+                    }
+                }
+            };
+            if filled > 0 {
+                report.imputed.insert(self.columns[c].spec.name.clone(), filled);
+            }
+        }
+    }
+}
+
+enum Cell {
+    Number(f64),
+    Text(String),
+    Flag(bool),
+}
+
+fn parse_cell(spec: &ColumnSpec, raw: &str) -> Result<Cell, IssueReason> {
+    match &spec.kind {
+        ColumnType::Numeric => raw.parse::<f64>().map(Cell::Number).map_err(|_| IssueReason::NotANumber),
+        ColumnType::Ordinal(encoder) => encoder.encode(raw).map(Cell::Number).ok_or(IssueReason::UnknownLevel),
+        ColumnType::Categorical => Ok(Cell::Text(raw.to_string())),
+        ColumnType::Boolean => match raw.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "y" => Ok(Cell::Flag(true)),
+            "0" | "false" | "no" | "n" => Ok(Cell::Flag(false)),
+            _ => Err(IssueReason::NotABoolean),
+        },
+    }
+}
+
+/// Loads every non-ignored schema column from `file_path`, resolving columns by header.
+/// Rows with an unparseable cell, or a blank cell under the `drop` policy, are
+/// dropped and described in the returned report; other blanks follow the
+/// column's missing-value policy.
+pub fn load_table(file_path: &str, schema: &Schema) -> Result<(Table, LoadReport), Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(file_path)?;
+    let specs: Vec<&ColumnSpec> = schema.loaded_columns().collect();
+    let names: Vec<&str> = specs.iter().map(|c| c.name.as_str()).collect();
+    let headers = rdr.headers()?.clone();
+    let columns = HeaderIndex::resolve(headers.iter(), &names, &schema.aliases())?;
+    let mut report = LoadReport::new(headers.iter().map(str::to_string).collect());
+
+    let mut data: Vec<ColumnData> = specs
+        .iter()
+        .map(|spec| match spec.kind {
+            ColumnType::Numeric | ColumnType::Ordinal(_) => ColumnData::Numeric(Vec::new()),
+            ColumnType::Categorical => ColumnData::Categorical(Vec::new()),
+            ColumnType::Boolean => ColumnData::Boolean(Vec::new()),
+        })
+        .collect();
+    let mut row_ids = Vec::new();
+
+    for (i, result) in rdr.records().enumerate() {
+        let record = result?;
+        report.rows_read += 1;
+
+        let mut cells = Vec::with_capacity(specs.len());
+        let mut issues = Vec::new();
+        for spec in &specs {
+            let raw = columns.get(&record, &spec.name);
+            let parsed = if raw.is_empty() {
+                match spec.missing {
+                    MissingPolicy::Drop => Err(IssueReason::Missing),
+                    _ => Ok(None),
+                }
+            } else {
+                parse_cell(spec, raw).map(Some)
+            };
+            match parsed {
+                Ok(cell) => cells.push(cell),
+                Err(reason) => issues.push(ParseIssue {
+                    row: i,
+                    column: spec.name.clone(),
+                    raw_value: raw.to_string(),
+                    reason,
+                }),
+            }
+        }
+        if !issues.is_empty() {
+            report.reject(i, &record, issues);
+            continue;
+        }
+
+        for (column, cell) in data.iter_mut().zip(cells) {
+            match (column, cell) {
+                (ColumnData::Numeric(values), Some(Cell::Number(v))) => values.push(Some(v)),
+                (ColumnData::Categorical(values), Some(Cell::Text(v))) => values.push(Some(v)),
+                (ColumnData::Boolean(values), Some(Cell::Flag(v))) => values.push(Some(v)),
+                (ColumnData::Numeric(values), None) => values.push(None),
+                (ColumnData::Categorical(values), None) => values.push(None),
+                (ColumnData::Boolean(values), None) => values.push(None),
+                _ => unreachable!("cell kind always matches its column"),
+            }
+        }
+        row_ids.push(i);
+        report.rows_loaded += 1;
+    }
+
+    let columns = specs
+        .into_iter()
+        .cloned()
+        .zip(data)
+        .map(|(spec, data)| Column { spec, data })
+        .collect();
+    let mut table = Table { row_ids, columns };
+    table.apply_missing_policies(&mut report);
+    Ok((table, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::load;
+
+    const SCHEMA: &str = r#"
+[[column]]
+name = "Growth"
+type = "ordinal"
+levels = { Low = 1, Medium = 2, High = 3 }
+level_aliases = { Med = "Medium", Hi = "High" }
+
+[[column]]
+name = "Mentorship"
+type = "boolean"
+missing = "keep"
+
+[[column]]
+name = "Gender"
+type = "categorical"
+missing = "keep"
+
+[[column]]
+name = "Salary"
+type = "numeric"
+role = "target"
+"#;
+
+    // Record 3 has an unknown level, record 4 a bad boolean and record 5 a blank
+    // under the default drop policy.
+    const CSV: &str = "Growth,Mentorship,Gender,Salary\n\
+        Low,yes,Female,100\n\
+        Med,No,Male,200\n\
+        hi,1,,300\n\
+        Extreme,true,Male,400\n\
+        High,maybe,Female,500\n\
+        ,false,Male,600\n\
+        Medium,,Male,700\n";
+
+    #[test]
+    fn cells_parse_by_column_type() {
+        let (table, report) = load("table", SCHEMA, CSV).unwrap();
+        assert_eq!(report.rows_read, 7);
+        assert_eq!(table.row_ids, [0, 1, 2, 6]);
+        assert_eq!(table.numeric("Growth").unwrap(), [Some(1.0), Some(2.0), Some(3.0), Some(2.0)]);
+        assert_eq!(table.group_keys("growth").unwrap()[1].as_deref(), Some("Medium"));
+        assert_eq!(table.boolean("Mentorship").unwrap(), [Some(true), Some(false), Some(true), None]);
+        assert_eq!(table.numeric("Mentorship").unwrap()[..2], [Some(1.0), Some(0.0)]);
+        assert_eq!(table.categorical("Gender").unwrap()[2], None);
+        assert!(table.numeric("Gender").is_none());
+    }
+
+    #[test]
+    fn unparseable_rows_are_reported() {
+        let (_, report) = load("table-issues", SCHEMA, CSV).unwrap();
+        let issues: Vec<(usize, &str, IssueReason)> =
+            report.issues.iter().map(|i| (i.row, i.column.as_str(), i.reason.clone())).collect();
+        assert_eq!(
+            issues,
+            [
+                (3, "Growth", IssueReason::UnknownLevel),
+                (4, "Mentorship", IssueReason::NotABoolean),
+                (5, "Growth", IssueReason::Missing),
+            ]
+        );
+    }
+
+    #[test]
+    fn unencoded_categoricals_and_targets_are_not_encoded_features() {
+        let (table, _) = load("table-features", SCHEMA, CSV).unwrap();
+        let names: Vec<String> = table.encoded_features().unwrap().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["Growth", "Mentorship"]);
+    }
+}
@@ -0,0 +1,72 @@
+//! Reference data sets and assertions shared by the unit tests.
+
+use std::error::Error;
+use std::fs;
+
+use crate::load_report::LoadReport;
+use crate::schema::Schema;
+use crate::table::{load_table, Table};
+
+/// Loads the CSV text `csv` under the schema text `schema`, through a
+/// temporary file named after `name`.
+pub fn load(name: &str, schema: &str, csv: &str) -> Result<(Table, LoadReport), Box<dyn Error>> {
+    let path = std::env::temp_dir().join(format!("finalproject-{}-{}.csv", std::process::id(), name));
+    fs::write(&path, csv)?;
+    let loaded = Schema::parse(schema).and_then(|schema| load_table(&path.to_string_lossy(), &schema));
+    fs::remove_file(&path)?;
+    loaded
+}
+
+/// Asserts `actual` is within `tolerance` of `expected`.
+pub fn close(actual: f64, expected: f64, tolerance: f64) {
+    assert!((actual - expected).abs() < tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+}
+
+/// Asserts `actual` is within `tolerance` of `expected`, relative to its size when larger than 1.
+pub fn close_scaled(actual: f64, expected: f64, tolerance: f64) {
+    let error = (actual - expected).abs() / expected.abs().max(1.0);
+    assert!(error < tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+}
+
+/// Asserts `actual` matches a tiny `expected` to `tolerance` relative error.
+pub fn relative(actual: f64, expected: f64, tolerance: f64) {
+    assert!(((actual - expected) / expected).abs() < tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+}
+
+/// R's `stackloss` data set: air flow, water temperature, acid concentration and stack loss.
+pub const STACKLOSS: [[f64; 4]; 21] = [
+    [80.0, 27.0, 89.0, 42.0],
+    [80.0, 27.0, 88.0, 37.0],
+    [75.0, 25.0, 90.0, 37.0],
+    [62.0, 24.0, 87.0, 28.0],
+    [62.0, 22.0, 87.0, 18.0],
+    [62.0, 23.0, 87.0, 18.0],
+    [62.0, 24.0, 93.0, 19.0],
+    [62.0, 24.0, 93.0, 20.0],
+    [58.0, 23.0, 87.0, 15.0],
+    [58.0, 18.0, 80.0, 14.0],
+    [58.0, 18.0, 89.0, 14.0],
+    [58.0, 17.0, 88.0, 13.0],
+    [58.0, 18.0, 82.0, 11.0],
+    [58.0, 19.0, 93.0, 12.0],
+    [50.0, 18.0, 89.0, 8.0],
+    [50.0, 18.0, 86.0, 7.0],
+    [50.0, 19.0, 72.0, 8.0],
+    [50.0, 19.0, 79.0, 8.0],
+    [50.0, 20.0, 80.0, 9.0],
+    [56.0, 20.0, 82.0, 15.0],
+    [70.0, 20.0, 91.0, 15.0],
+];
+
+/// R's `cars` data set: stopping distance against speed.
+pub const SPEED: [f64; 50] = [
+    4.0, 4.0, 7.0, 7.0, 8.0, 9.0, 10.0, 10.0, 10.0, 11.0, 11.0, 12.0, 12.0, 12.0, 12.0, 13.0, 13.0, 13.0, 13.0, 14.0,
+    14.0, 14.0, 14.0, 15.0, 15.0, 15.0, 16.0, 16.0, 17.0, 17.0, 17.0, 18.0, 18.0, 18.0, 18.0, 19.0, 19.0, 19.0, 20.0,
+    20.0, 20.0, 20.0, 20.0, 22.0, 23.0, 24.0, 24.0, 24.0, 24.0, 25.0,
+];
+pub const DIST: [f64; 50] = [
+    2.0, 10.0, 4.0, 22.0, 16.0, 10.0, 18.0, 26.0, 34.0, 17.0, 28.0, 14.0, 20.0, 24.0, 28.0, 26.0, 34.0, 34.0, 46.0,
+    26.0, 36.0, 60.0, 80.0, 20.0, 26.0, 54.0, 32.0, 40.0, 32.0, 40.0, 50.0, 42.0, 56.0, 76.0, 84.0, 36.0, 46.0, 68.0,
+    32.0, 48.0, 52.0, 56.0, 64.0, 66.0, 54.0, 70.0, 92.0, 93.0, 120.0, 85.0,
+];
+
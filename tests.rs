@@ -0,0 +1,513 @@
+//! Two-sample and contingency-table hypothesis tests.
+
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+use crate::correlation::ranks;
+use crate::distributions::{ln_gamma, ChiSquared, Continuous, Normal, StudentT};
+
+/// Mann-Whitney U is computed exactly below this size of either sample when there are no ties.
+const EXACT_MANN_WHITNEY_LIMIT: usize = 50;
+/// Relative tolerance when collecting tables as extreme as the observed one, as in R.
+const FISHER_TOLERANCE: f64 = 1e-7;
+
+/// Standardized size of the effect a test detects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EffectSize {
+    /// Mean difference over a standard deviation (pooled, or of the differences when paired).
+    CohensD(f64),
+    /// 2U / (n1 n2) - 1, from -1 to 1.
+    RankBiserial(f64),
+    CramersV(f64),
+}
+
+impl EffectSize {
+    pub fn name(self) -> &'static str {
+        match self {
+            EffectSize::CohensD(_) => "cohens_d",
+            EffectSize::RankBiserial(_) => "rank_biserial",
+            EffectSize::CramersV(_) => "cramers_v",
+        }
+    }
+
+    pub fn value(self) -> f64 {
+        match self {
+            EffectSize::CohensD(v) | EffectSize::RankBiserial(v) | EffectSize::CramersV(v) => v,
+        }
+    }
+}
+
+impl fmt::Display for EffectSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EffectSize::CohensD(d) => write!(f, "Cohen's d = {:.4}", d),
+            EffectSize::RankBiserial(r) => write!(f, "rank-biserial r = {:.4}", r),
+            EffectSize::CramersV(v) => write!(f, "Cramer's V = {:.4}", v),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub test: &'static str,
+    pub statistic: f64,
+    /// `None` for tests without a reference distribution indexed by degrees of freedom.
+    pub df: Option<f64>,
+    pub p_value: f64,
+    pub effect_size: Option<EffectSize>,
+    /// Observations used (pairs for the paired t-test).
+    pub n: usize,
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: statistic = {:.4}", self.test, self.statistic)?;
+        if let Some(df) = self.df {
+            write!(f, ", df = {}", (df * 100.0).round() / 100.0)?;
+        }
+        write!(f, ", p = {:.4}, n = {}", self.p_value, self.n)?;
+        if let Some(effect) = self.effect_size {
+            write!(f, ", {}", effect)?;
+        }
+        Ok(())
+    }
+}
+
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance)
+}
+
+fn check_samples(a: &[f64], b: &[f64]) -> Result<(), Box<dyn Error>> {
+    if a.len() < 2 || b.len() < 2 {
+        return Err("Each sample needs at least two values".into());
+    }
+    Ok(())
+}
+
+fn pooled_sd(a: &[f64], b: &[f64]) -> f64 {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let (_, v1) = mean_and_variance(a);
+    let (_, v2) = mean_and_variance(b);
+    (((n1 - 1.0) * v1 + (n2 - 1.0) * v2) / (n1 + n2 - 2.0)).sqrt()
+}
+
+/// Student's two-sample t-test of mean(a) - mean(b), assuming equal variances.
+pub fn student_t_test(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    check_samples(a, b)?;
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let (m1, _) = mean_and_variance(a);
+    let (m2, _) = mean_and_variance(b);
+    let sd = pooled_sd(a, b);
+    if sd == 0.0 {
+        return Err("Both samples are constant".into());
+    }
+    let df = n1 + n2 - 2.0;
+    let t = (m1 - m2) / (sd * (1.0 / n1 + 1.0 / n2).sqrt());
+    Ok(TestResult {
+        test: "Student t-test",
+        statistic: t,
+        df: Some(df),
+        p_value: StudentT::new(df).two_sided_p(t),
+        effect_size: Some(EffectSize::CohensD((m1 - m2) / sd)),
+        n: a.len() + b.len(),
+    })
+}
+
+/// Welch's two-sample t-test with Satterthwaite degrees of freedom. Cohen's d
+/// still uses the pooled standard deviation.
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    check_samples(a, b)?;
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let (m1, v1) = mean_and_variance(a);
+    let (m2, v2) = mean_and_variance(b);
+    let (s1, s2) = (v1 / n1, v2 / n2);
+    if s1 + s2 == 0.0 {
+        return Err("Both samples are constant".into());
+    }
+    let t = (m1 - m2) / (s1 + s2).sqrt();
+    let df = (s1 + s2).powi(2) / (s1 * s1 / (n1 - 1.0) + s2 * s2 / (n2 - 1.0));
+    Ok(TestResult {
+        test: "Welch t-test",
+        statistic: t,
+        df: Some(df),
+        p_value: StudentT::new(df).two_sided_p(t),
+        effect_size: Some(EffectSize::CohensD((m1 - m2) / pooled_sd(a, b))),
+        n: a.len() + b.len(),
+    })
+}
+
+/// Paired t-test of the mean of a[i] - b[i]; Cohen's d here is d_z, the mean
+/// difference over the standard deviation of the differences.
+pub fn paired_t_test(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    if a.len() != b.len() {
+        return Err("Paired samples must be of equal length".into());
+    }
+    if a.len() < 2 {
+        return Err("Need at least two pairs".into());
+    }
+    let differences: Vec<f64> = a.iter().zip(b).map(|(x, y)| x - y).collect();
+    let (mean, variance) = mean_and_variance(&differences);
+    if variance == 0.0 {
+        return Err("The paired differences are constant".into());
+    }
+    let n = differences.len() as f64;
+    let t = mean / (variance / n).sqrt();
+    Ok(TestResult {
+        test: "Paired t-test",
+        statistic: t,
+        df: Some(n - 1.0),
+        p_value: StudentT::new(n - 1.0).two_sided_p(t),
+        effect_size: Some(EffectSize::CohensD(mean / variance.sqrt())),
+        n: differences.len(),
+    })
+}
+
+/// Mann-Whitney U test (Wilcoxon rank-sum) reporting U for `a`. Exact when both
+/// samples are small and untied, otherwise the normal approximation with tie
+/// and continuity corrections, matching R's `wilcox.test`.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Result<TestResult, Box<dyn Error>> {
+    if a.is_empty() || b.is_empty() {
+        return Err("Each sample needs at least one value".into());
+    }
+    let (n1, n2) = (a.len(), b.len());
+    let pooled: Vec<f64> = a.iter().chain(b).copied().collect();
+    let pooled_ranks = ranks(&pooled);
+    let rank_sum: f64 = pooled_ranks[..n1].iter().sum();
+    let (f1, f2) = (n1 as f64, n2 as f64);
+    let u = rank_sum - f1 * (f1 + 1.0) / 2.0;
+
+    let mut sorted = pooled.clone();
+    sorted.sort_by(|x, y| x.total_cmp(y));
+    let mut tie_sum = 0.0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let t = sorted[i..].iter().take_while(|&&v| v == sorted[i]).count() as f64;
+        tie_sum += t * t * t - t;
+        i += t as usize;
+    }
+
+    let p_value = if n1 < EXACT_MANN_WHITNEY_LIMIT && n2 < EXACT_MANN_WHITNEY_LIMIT && tie_sum == 0.0 {
+        exact_mann_whitney_p(u, n1, n2)
+    } else {
+        let n = f1 + f2;
+        let variance = f1 * f2 / 12.0 * ((n + 1.0) - tie_sum / (n * (n - 1.0)));
+        if variance <= 0.0 {
+            return Err("Every observation is tied".into());
+        }
+        let centred = u - f1 * f2 / 2.0;
+        let continuity = if centred == 0.0 { 0.0 } else { 0.5 * centred.signum() };
+        let z = (centred - continuity) / variance.sqrt();
+        Normal.two_sided_p(z)
+    };
+
+    Ok(TestResult {
+        test: "Mann-Whitney U",
+        statistic: u,
+        df: None,
+        p_value,
+        effect_size: Some(EffectSize::RankBiserial(2.0 * u / (f1 * f2) - 1.0)),
+        n: n1 + n2,
+    })
+}
+
+/// Two-sided exact p-value of U from the null distribution of rank sums.
+fn exact_mann_whitney_p(u: f64, n1: usize, n2: usize) -> f64 {
+    // counts[k][s]: ways for k of the first j ranks to sum (as U) to s, built up over j
+    let max_u = n1 * n2;
+    let mut counts = vec![vec![0.0f64; max_u + 1]; n1 + 1];
+    counts[0][0] = 1.0;
+    for j in 0..n1 + n2 {
+        for k in (1..=n1.min(j + 1)).rev() {
+            // Choosing rank j + 1 as the k-th member adds j + 1 - k to U
+            let shift = j + 1 - k;
+            for s in (shift..=max_u).rev() {
+                counts[k][s] += counts[k - 1][s - shift];
+            }
+        }
+    }
+    let distribution = &counts[n1];
+    let total: f64 = distribution.iter().sum();
+    let u = u.round() as usize;
+    let lower: f64 = distribution[..=u.min(max_u)].iter().sum::<f64>() / total;
+    let upper: f64 = distribution[u.min(max_u)..].iter().sum::<f64>() / total;
+    (2.0 * lower.min(upper)).min(1.0)
+}
+
+/// Cross-tabulated counts of two categorical variables.
+#[derive(Debug, Clone)]
+pub struct ContingencyTable {
+    pub row_name: String,
+    pub column_name: String,
+    pub row_labels: Vec<String>,
+    pub column_labels: Vec<String>,
+    pub counts: Vec<Vec<f64>>,
+}
+
+impl ContingencyTable {
+    /// Cross-tabulates rows where both keys are present; labels are sorted.
+    pub fn from_keys(
+        row_name: &str,
+        rows: &[Option<String>],
+        column_name: &str,
+        columns: &[Option<String>],
+    ) -> ContingencyTable {
+        let pairs: Vec<(&String, &String)> = rows
+            .iter()
+            .zip(columns)
+            .filter_map(|(r, c)| Some((r.as_ref()?, c.as_ref()?)))
+            .collect();
+        let row_labels: Vec<String> = pairs.iter().map(|(r, _)| (*r).clone()).collect::<BTreeSet<_>>().into_iter().collect();
+        let column_labels: Vec<String> =
+            pairs.iter().map(|(_, c)| (*c).clone()).collect::<BTreeSet<_>>().into_iter().collect();
+        let mut counts = vec![vec![0.0; column_labels.len()]; row_labels.len()];
+        for (r, c) in pairs {
+            let i = row_labels.binary_search(r).expect("label collected above");
+            let j = column_labels.binary_search(c).expect("label collected above");
+            counts[i][j] += 1.0;
+        }
+        ContingencyTable {
+            row_name: row_name.to_string(),
+            column_name: column_name.to_string(),
+            row_labels,
+            column_labels,
+            counts,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.counts.iter().flatten().sum()
+    }
+
+    fn row_totals(&self) -> Vec<f64> {
+        self.counts.iter().map(|row| row.iter().sum()).collect()
+    }
+
+    fn column_totals(&self) -> Vec<f64> {
+        (0..self.column_labels.len())
+            .map(|j| self.counts.iter().map(|row| row[j]).sum())
+            .collect()
+    }
+
+    /// Expected counts under independence.
+    pub fn expected(&self) -> Vec<Vec<f64>> {
+        let total = self.total();
+        let columns = self.column_totals();
+        self.row_totals()
+            .iter()
+            .map(|r| columns.iter().map(|c| r * c / total).collect())
+            .collect()
+    }
+
+    pub fn render(&self) -> String {
+        let corner = format!("{} \\ {}", self.row_name, self.column_name);
+        let label_width = self.row_labels.iter().map(String::len).chain([corner.len()]).max().unwrap_or(0);
+        let widths: Vec<usize> = self
+            .column_labels
+            .iter()
+            .enumerate()
+            .map(|(j, label)| {
+                self.counts
+                    .iter()
+                    .map(|row| row[j].to_string().len())
+                    .chain([label.len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let mut out = format!("{:<label_width$}", corner);
+        for (label, width) in self.column_labels.iter().zip(&widths) {
+            out.push_str(&format!("  {:>width$}", label));
+        }
+        out.push('\n');
+        for (label, row) in self.row_labels.iter().zip(&self.counts) {
+            out.push_str(&format!("{:<label_width$}", label));
+            for (count, width) in row.iter().zip(&widths) {
+                out.push_str(&format!("  {:>width$}", count));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn cramers_v(chi_square: f64, table: &ContingencyTable) -> f64 {
+    let k = table.row_labels.len().min(table.column_labels.len()) as f64;
+    (chi_square / (table.total() * (k - 1.0))).sqrt()
+}
+
+/// Pearson's chi-square test of independence, without continuity correction.
+/// The approximation is doubtful when expected counts fall below 5; prefer
+/// [`fisher_exact`] for small 2x2 tables.
+pub fn chi_square_independence(table: &ContingencyTable) -> Result<TestResult, Box<dyn Error>> {
+    if table.row_labels.len() < 2 || table.column_labels.len() < 2 {
+        return Err("The table needs at least two rows and two columns".into());
+    }
+    let statistic: f64 = table
+        .counts
+        .iter()
+        .flatten()
+        .zip(table.expected().iter().flatten())
+        .map(|(o, e)| (o - e).powi(2) / e)
+        .sum();
+    let df = ((table.row_labels.len() - 1) * (table.column_labels.len() - 1)) as f64;
+    Ok(TestResult {
+        test: "Chi-square independence",
+        statistic,
+        df: Some(df),
+        p_value: ChiSquared::new(df).sf(statistic),
+        effect_size: Some(EffectSize::CramersV(cramers_v(statistic, table))),
+        n: table.total() as usize,
+    })
+}
+
+/// Fisher's exact test of a 2x2 table. The statistic is the sample odds ratio,
+/// with 0.5 added to every cell when any is zero (the Haldane-Anscombe
+/// correction) so that it stays finite; the p-value sums every table with the
+/// same margins no more likely than the observed one.
+pub fn fisher_exact(table: &ContingencyTable) -> Result<TestResult, Box<dyn Error>> {
+    if table.row_labels.len() != 2 || table.column_labels.len() != 2 {
+        return Err(format!(
+            "Fisher's exact test needs a 2x2 table, got {}x{}",
+            table.row_labels.len(),
+            table.column_labels.len()
+        )
+        .into());
+    }
+    let [a, b] = [table.counts[0][0], table.counts[0][1]];
+    let [c, d] = [table.counts[1][0], table.counts[1][1]];
+    let (row1, col1, n) = (a + b, a + c, a + b + c + d);
+    let ln_choose = |n: f64, k: f64| ln_gamma(n + 1.0) - ln_gamma(k + 1.0) - ln_gamma(n - k + 1.0);
+    // Hypergeometric probability of `x` in the top-left cell given the margins
+    let probability = |x: f64| (ln_choose(col1, x) + ln_choose(n - col1, row1 - x) - ln_choose(n, row1)).exp();
+
+    let observed = probability(a);
+    let low = (row1 + col1 - n).max(0.0) as usize;
+    let high = row1.min(col1) as usize;
+    let p_value: f64 = (low..=high)
+        .map(|x| probability(x as f64))
+        .filter(|&p| p <= observed * (1.0 + FISHER_TOLERANCE))
+        .sum();
+
+    let correction = if a * b * c * d == 0.0 { 0.5 } else { 0.0 };
+    let odds_ratio = ((a + correction) * (d + correction)) / ((b + correction) * (c + correction));
+    // Cramer's V is undefined when a whole row or column is empty
+    let effect_size = chi_square_independence(table)
+        .ok()
+        .filter(|r| r.statistic.is_finite())
+        .map(|r| EffectSize::CramersV(cramers_v(r.statistic, table)));
+    Ok(TestResult {
+        test: "Fisher exact",
+        statistic: odds_ratio,
+        df: None,
+        p_value: p_value.min(1.0),
+        effect_size,
+        n: n as usize,
+    })
+}
+
+#[cfg(test)]
+mod reference {
+    use super::*;
+    use crate::testing::close;
+
+    // Student's sleep data, as in R's `t.test(extra ~ group, data = sleep)`
+    const SLEEP_1: [f64; 10] = [0.7, -1.6, -0.2, -1.2, -0.1, 3.4, 3.7, 0.8, 0.0, 2.0];
+    const SLEEP_2: [f64; 10] = [1.9, 0.8, 1.1, 0.1, -0.1, 4.4, 5.5, 1.6, 4.6, 3.4];
+
+    fn two_by_two(counts: [[f64; 2]; 2]) -> ContingencyTable {
+        ContingencyTable {
+            row_name: "guess".to_string(),
+            column_name: "truth".to_string(),
+            row_labels: vec!["milk".to_string(), "tea".to_string()],
+            column_labels: vec!["milk".to_string(), "tea".to_string()],
+            counts: counts.iter().map(|row| row.to_vec()).collect(),
+        }
+    }
+
+    #[test]
+    fn student_t_test_matches_r_with_equal_variances() {
+        // t.test(extra ~ group, data = sleep, var.equal = TRUE)
+        let student = student_t_test(&SLEEP_1, &SLEEP_2).unwrap();
+        close(student.statistic, -1.8608135, 1e-6);
+        assert_eq!(student.df, Some(18.0));
+        close(student.p_value, 0.07918671, 1e-7);
+        close(student.effect_size.unwrap().value(), -0.8321811, 1e-6);
+        assert!(student_t_test(&[1.0, 1.0], &[2.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn chi_square_matches_r_without_continuity_correction() {
+        // chisq.test(matrix(c(12, 7, 5, 14, 9, 3), 2), correct = FALSE)
+        let table = ContingencyTable {
+            row_name: "group".to_string(),
+            column_name: "answer".to_string(),
+            row_labels: vec!["a".to_string(), "b".to_string()],
+            column_labels: vec!["x".to_string(), "y".to_string(), "z".to_string()],
+            counts: vec![vec![12.0, 5.0, 9.0], vec![7.0, 14.0, 3.0]],
+        };
+        let result = chi_square_independence(&table).unwrap();
+        close(result.statistic, 8.5125675, 1e-6);
+        assert_eq!(result.df, Some(2.0));
+        close(result.p_value, 0.01417488, 1e-7);
+        // sqrt(X² / (n (min(r, c) - 1)))
+        close(result.effect_size.unwrap().value(), 0.4126153, 1e-6);
+        assert_eq!(result.n, 50);
+
+        // A 2x2 table is not Yates-corrected: X² = 2 for the tea tasting counts.
+        let tea = chi_square_independence(&two_by_two([[3.0, 1.0], [1.0, 3.0]])).unwrap();
+        close(tea.statistic, 2.0, 1e-12);
+        close(tea.p_value, 0.1572992, 1e-7);
+        close(tea.effect_size.unwrap().value(), 0.5, 1e-12);
+    }
+
+    #[test]
+    fn welch_and_paired_t_tests_match_r_on_the_sleep_data() {
+        let welch = welch_t_test(&SLEEP_1, &SLEEP_2).unwrap();
+        close(welch.statistic, -1.860813, 1e-6);
+        close(welch.df.unwrap(), 17.77647, 1e-5);
+        close(welch.p_value, 0.07939414, 1e-6);
+
+        let paired = paired_t_test(&SLEEP_1, &SLEEP_2).unwrap();
+        close(paired.statistic, -4.062128, 1e-6);
+        close(paired.p_value, 0.002832890, 1e-7);
+    }
+
+    #[test]
+    fn mann_whitney_is_exact_for_small_untied_samples() {
+        let x = [0.80, 0.83, 1.89, 1.04, 1.45, 1.38, 1.91, 1.64, 0.73, 1.46];
+        let y = [1.15, 0.88, 0.90, 0.74, 1.21];
+        let result = mann_whitney_u(&x, &y).unwrap();
+        assert_eq!(result.statistic, 35.0);
+        close(result.p_value, 0.2544123, 1e-7);
+        close(result.effect_size.unwrap().value(), 0.4, 1e-12);
+    }
+
+    #[test]
+    fn mann_whitney_falls_back_to_the_tie_corrected_normal_approximation() {
+        let result = mann_whitney_u(&[1.0, 2.0, 2.0, 3.0, 4.0], &[2.0, 3.0, 5.0, 6.0, 6.0, 7.0]).unwrap();
+        assert_eq!(result.statistic, 4.5);
+        close(result.p_value, 0.06414662, 1e-7);
+    }
+
+    #[test]
+    fn fisher_exact_matches_the_tea_tasting_experiment() {
+        let result = fisher_exact(&two_by_two([[3.0, 1.0], [1.0, 3.0]])).unwrap();
+        close(result.statistic, 9.0, 1e-12);
+        close(result.p_value, 0.4857143, 1e-7);
+    }
+
+    #[test]
+    fn fisher_odds_ratio_stays_finite_with_empty_cells() {
+        let result = fisher_exact(&two_by_two([[0.0, 5.0], [4.0, 1.0]])).unwrap();
+        close(result.statistic, 0.5 * 1.5 / (5.5 * 4.5), 1e-12);
+        close(result.p_value, 0.04761905, 1e-7);
+
+        let result = fisher_exact(&two_by_two([[0.0, 0.0], [4.0, 1.0]])).unwrap();
+        assert!(result.statistic.is_finite());
+        assert!(result.effect_size.is_none());
+    }
+}